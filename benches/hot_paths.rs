@@ -0,0 +1,89 @@
+#[macro_use]
+extern crate criterion;
+extern crate bing_rs;
+
+use bing_rs::audio::{self, Spec};
+use bing_rs::speech::protocol;
+use criterion::{black_box, Criterion};
+
+const HYPOTHESIS: &str = "X-RequestId: 123e4567e89b12d3a456426655440000\r\nPath: speech.hypothesis\r\nContent-Type: application/json; charset=utf-8\r\n\r\n{\"Text\":\"what's the weather\",\"Offset\":2300000,\"Duration\":11100000}";
+
+const PHRASE: &str = "X-RequestId: 123e4567e89b12d3a456426655440000\r\nPath: speech.phrase\r\nContent-Type: application/json; charset=utf-8\r\n\r\n{\"RecognitionStatus\":\"Success\",\"DisplayText\":\"What's the weather like?\",\"Offset\":2300000,\"Duration\":15100000}";
+
+fn chunk() -> Vec<u8> {
+    (0..audio::frame::DEFAULT_FRAME_SIZE)
+        .map(|i| (i % 256) as u8)
+        .collect()
+}
+
+fn frame_construction(c: &mut Criterion) {
+    c.bench_function("build_audio_message 4 KiB", |b| {
+        let chunk = chunk();
+        b.iter(|| {
+            protocol::build_audio_message(
+                black_box("123e4567e89b12d3a456426655440000"),
+                black_box("2018-08-01T12:00:00.000Z"),
+                black_box(&chunk),
+            )
+        })
+    });
+    c.bench_function("build_text_message", |b| {
+        b.iter(|| {
+            protocol::build_text_message(
+                black_box("speech.config"),
+                black_box("123e4567e89b12d3a456426655440000"),
+                black_box("2018-08-01T12:00:00.000Z"),
+                black_box("application/json; charset=utf-8"),
+                black_box("{\"context\":{}}"),
+            )
+        })
+    });
+}
+
+fn message_parsing(c: &mut Criterion) {
+    c.bench_function("parse_server_message hypothesis", |b| {
+        b.iter(|| protocol::parse_server_message(black_box(HYPOTHESIS)))
+    });
+    c.bench_function("parse_server_message phrase", |b| {
+        b.iter(|| protocol::parse_server_message(black_box(PHRASE)))
+    });
+    c.bench_function("parse_binary_message 4 KiB", |b| {
+        let message = protocol::build_audio_message(
+            "123e4567e89b12d3a456426655440000",
+            "2018-08-01T12:00:00.000Z",
+            &chunk(),
+        );
+        b.iter(|| protocol::parse_binary_message(black_box(&message)).is_ok())
+    });
+}
+
+fn wav_header(c: &mut Criterion) {
+    c.bench_function("wav_header", |b| {
+        let spec = Spec::default();
+        b.iter(|| spec.wav_header(black_box(32000)))
+    });
+}
+
+fn pcm_conversion(c: &mut Criterion) {
+    c.bench_function("to_samples 1 s", |b| {
+        let pcm: Vec<u8> = (0..32000).map(|i| (i % 256) as u8).collect();
+        b.iter(|| audio::to_samples(black_box(&pcm)))
+    });
+    c.bench_function("from_samples 1 s", |b| {
+        let samples: Vec<i16> = (0..16000).map(|i| (i % 2000) as i16 - 1000).collect();
+        b.iter(|| audio::from_samples(black_box(&samples)))
+    });
+    c.bench_function("resample 44.1 kHz to 16 kHz 1 s", |b| {
+        let samples: Vec<i16> = (0..44100).map(|i| (i % 2000) as i16 - 1000).collect();
+        b.iter(|| audio::resample(black_box(&samples), 44100, audio::SAMPLE_RATE))
+    });
+}
+
+criterion_group!(
+    benches,
+    frame_construction,
+    message_parsing,
+    wav_header,
+    pcm_conversion
+);
+criterion_main!(benches);
@@ -1,21 +1,20 @@
 extern crate bing_rs;
 
+use bing_rs::audio;
 use bing_rs::speech::*;
 use std::env;
-use std::fs::File;
-use std::io::Read;
 
 fn main() {
-    let mut client = Speech::new(&env::var("SUBSCRIPTION_KEY").unwrap()).unwrap();
-    client.set_custom_speech(true);
-    client.set_endpoint_id(&env::var("ENDPOINT_ID").unwrap());
+    let mut client = Speech::builder()
+        .subscription_key(&env::var("SUBSCRIPTION_KEY").unwrap())
+        .custom_endpoint(&env::var("ENDPOINT_ID").unwrap())
+        .build()
+        .unwrap();
     assert!(client.fetch_token().is_ok());
-    let mut file = File::open("assets/audio.raw").unwrap();
-    let mut audio = Vec::new();
-    assert!(file.read_to_end(&mut audio).is_ok());
+    let (_, audio) = audio::read_wav("assets/audio.wav").unwrap();
 
-    let mode = Mode::Interactive(InteractiveDictationLanguage::EnglishUnitedStates);
-    match client.recognize(audio, &mode, &Format::Detailed) {
+    let mode = Mode::Interactive(Language::EnglishUnitedStates);
+    match client.recognize(audio, mode, Format::Detailed) {
         Ok((_, _, Some(phrase))) => println!("{}", phrase),
         Ok((_, _, None)) => println!("Empty response"),
         Err(err) => println!("Error: {}", err),
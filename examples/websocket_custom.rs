@@ -10,13 +10,12 @@ extern crate serde_json;
 extern crate ws;
 
 use std::env;
-use std::fs::File;
-use std::io::Read;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+use bing_rs::audio;
 use bing_rs::speech::websocket::*;
 use bing_rs::speech::*;
 use chan_signal::Signal;
@@ -63,10 +62,8 @@ fn main() {
     let signal = chan_signal::notify(&[Signal::INT, Signal::TERM]);
 
     // Load audio data
-    let mut file = File::open("assets/audio.raw").unwrap();
-    let mut audio = Vec::new();
+    let (_, mut audio) = audio::read_wav("assets/audio.wav").unwrap();
     let mut i = 0;
-    file.read_to_end(&mut audio).unwrap();
 
     // Add some silence to the end of audio data
     for _ in 0..1024 * 100 {
@@ -74,22 +71,24 @@ fn main() {
     }
 
     // Setup Bing Speech Client
-    let mut client = Speech::new(&env::var("SUBSCRIPTION_KEY").unwrap()).unwrap();
+    let mut client = Speech::builder()
+        .subscription_key(&env::var("SUBSCRIPTION_KEY").unwrap())
+        .custom_endpoint(&env::var("ENDPOINT_ID").unwrap())
+        .build()
+        .unwrap();
     let token = client.token.clone();
-    client.set_custom_speech(true);
-    client.set_endpoint_id(&env::var("ENDPOINT_ID").unwrap());
     client.fetch_token().unwrap();
-    client.auto_fetch_token();
+    let _refresher = client.auto_fetch_token();
 
     // Setup Bing Speech Websocket
-    let mode = Mode::Interactive(InteractiveDictationLanguage::EnglishUnitedStates);
+    let mode = Mode::Interactive(Language::EnglishUnitedStates);
     let format = Format::Detailed;
     let handler = Arc::new(Mutex::new(MyHandler {}));
     let mut ws = Websocket::new();
     ws.connect(
         token.clone(),
-        &mode,
-        &format,
+        mode,
+        format,
         true,
         &env::var("ENDPOINT_ID").unwrap(),
         handler.clone(),
@@ -0,0 +1,10 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate bing_rs;
+
+use bing_rs::speech::protocol;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = protocol::parse_binary_message(data);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate bing_rs;
+extern crate serde_json;
+
+use bing_rs::speech::Phrase;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(value) = serde_json::from_slice::<serde_json::Value>(data) {
+        let _ = Phrase::from_json_value(&value);
+    }
+});
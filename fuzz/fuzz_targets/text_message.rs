@@ -0,0 +1,13 @@
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate bing_rs;
+
+use bing_rs::speech::protocol;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = ::std::str::from_utf8(data) {
+        let _ = protocol::parse_text_message(text);
+        let _ = protocol::parse_server_message(text);
+    }
+});
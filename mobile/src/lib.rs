@@ -0,0 +1,261 @@
+//! Kotlin and Swift bindings to bing-rs
+//!
+//! The interface is described in `src/bing.udl`; uniffi generates the
+//! scaffolding at build time and the foreign bindings with
+//! `cargo run --bin uniffi-bindgen generate`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bing_rs::speech::{self, voice, Language, ModeKind};
+
+uniffi::include_scaffolding!("bing");
+
+#[derive(Debug, thiserror::Error)]
+pub enum BingError {
+    #[error("{0}")]
+    Request(String),
+    #[error("websocket is not connected")]
+    NotConnected,
+}
+
+impl From<bing_rs::errors::Error> for BingError {
+    fn from(err: bing_rs::errors::Error) -> Self {
+        BingError::Request(err.to_string())
+    }
+}
+
+impl From<ws::Error> for BingError {
+    fn from(err: ws::Error) -> Self {
+        BingError::Request(err.to_string())
+    }
+}
+
+pub enum RecognitionMode {
+    Interactive,
+    Conversation,
+    Dictation,
+}
+
+pub enum ResultFormat {
+    Simple,
+    Detailed,
+}
+
+pub struct Recognition {
+    pub status: String,
+    pub text: Option<String>,
+    pub confidence: Option<f64>,
+    pub offset_ms: u64,
+    pub duration_ms: u64,
+}
+
+pub struct Hypothesis {
+    pub text: String,
+    pub offset_ms: u64,
+    pub duration_ms: u64,
+}
+
+pub trait RecognitionListener: Send + Sync {
+    fn on_turn_start(&self);
+    fn on_turn_end(&self);
+    fn on_speech_start(&self);
+    fn on_speech_end(&self);
+    fn on_hypothesis(&self, hypothesis: Hypothesis);
+    fn on_phrase(&self, phrase: Recognition);
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+}
+
+fn mode(mode: RecognitionMode, language: &str) -> Result<speech::Mode, BingError> {
+    let language: Language = language.parse()?;
+    let kind = match mode {
+        RecognitionMode::Interactive => ModeKind::Interactive,
+        RecognitionMode::Conversation => ModeKind::Conversation,
+        RecognitionMode::Dictation => ModeKind::Dictation,
+    };
+    Ok(speech::Mode::new(kind, language)?)
+}
+
+fn format(format: ResultFormat) -> speech::Format {
+    match format {
+        ResultFormat::Simple => speech::Format::Simple,
+        ResultFormat::Detailed => speech::Format::Detailed,
+    }
+}
+
+impl From<speech::Phrase> for Recognition {
+    fn from(phrase: speech::Phrase) -> Self {
+        let status = match phrase {
+            speech::Phrase::Simple(ref simple) => simple.recognition_status.clone(),
+            speech::Phrase::Detailed(ref detailed) => detailed.recognition_status.clone(),
+            speech::Phrase::Silence(ref silence) => silence.recognition_status.clone(),
+            speech::Phrase::LowConfidence(ref detailed) => detailed.recognition_status.clone(),
+            speech::Phrase::Unknown => "Unknown".to_string(),
+        };
+        Recognition {
+            status,
+            text: phrase.text().map(String::from),
+            confidence: phrase.confidence(),
+            offset_ms: phrase.offset().map_or(0, millis),
+            duration_ms: phrase.duration().map_or(0, millis),
+        }
+    }
+}
+
+/// Connection settings shared by `Speech` and `Websocket`
+///
+/// `bing_rs::speech::Speech` owns a non-`Send` transport, so a client is
+/// created for every call while the token is shared between them.
+pub struct Speech {
+    subscription_key: String,
+    token: Arc<Mutex<String>>,
+    is_custom_speech: Mutex<bool>,
+    endpoint_id: Mutex<String>,
+}
+
+impl Speech {
+    pub fn new(subscription_key: String) -> Self {
+        Speech {
+            subscription_key,
+            token: Arc::new(Mutex::new(String::new())),
+            is_custom_speech: Mutex::new(false),
+            endpoint_id: Mutex::new(String::new()),
+        }
+    }
+
+    pub fn set_custom_speech(&self, is_custom_speech: bool) {
+        *self.is_custom_speech.lock().unwrap() = is_custom_speech;
+    }
+
+    pub fn set_endpoint_id(&self, endpoint_id: String) {
+        *self.endpoint_id.lock().unwrap() = endpoint_id;
+    }
+
+    pub fn fetch_token(&self) -> Result<(), BingError> {
+        self.client()?.fetch_token()?;
+        Ok(())
+    }
+
+    pub fn recognize(
+        &self,
+        audio: Vec<u8>,
+        recognition_mode: RecognitionMode,
+        language: String,
+        result_format: ResultFormat,
+    ) -> Result<Recognition, BingError> {
+        let mode = mode(recognition_mode, &language)?;
+        let (_, status, phrase) = self
+            .client()?
+            .recognize(audio, mode, format(result_format))?;
+        match phrase {
+            Some(phrase) => Ok(phrase.into()),
+            None => Err(BingError::Request(format!("empty response ({})", status))),
+        }
+    }
+
+    pub fn synthesize(&self, text: String, language: String) -> Result<Vec<u8>, BingError> {
+        let font = voice::default_for(&language)
+            .ok_or_else(|| BingError::Request(format!("no voice for {:?}", language)))?;
+        let (_, status, audio) = self.client()?.synthesize(&text, font)?;
+        audio.ok_or_else(|| BingError::Request(format!("empty response ({})", status)))
+    }
+
+    fn client(&self) -> Result<speech::Speech, BingError> {
+        let mut builder = speech::Speech::builder().subscription_key(&self.subscription_key);
+        if *self.is_custom_speech.lock().unwrap() {
+            builder = builder.custom_endpoint(&self.endpoint_id.lock().unwrap());
+        }
+        let mut client = builder.build()?;
+        client.token = self.token.clone();
+        Ok(client)
+    }
+}
+
+/// Forwards websocket events to a foreign listener
+struct ListenerHandler(Box<dyn RecognitionListener>);
+
+impl speech::websocket::Handler for ListenerHandler {
+    fn on_turn_start(&mut self) {
+        self.0.on_turn_start();
+    }
+
+    fn on_turn_end(&mut self) {
+        self.0.on_turn_end();
+    }
+
+    fn on_speech_start(&mut self) {
+        self.0.on_speech_start();
+    }
+
+    fn on_speech_end(&mut self) {
+        self.0.on_speech_end();
+    }
+
+    fn on_speech_hypothesis(&mut self, hypothesis: speech::Hypothesis) {
+        self.0.on_hypothesis(Hypothesis {
+            offset_ms: millis(hypothesis.offset()),
+            duration_ms: millis(hypothesis.duration()),
+            text: hypothesis.text,
+        });
+    }
+
+    fn on_speech_phrase(&mut self, phrase: speech::Phrase) {
+        self.0.on_phrase(phrase.into());
+    }
+}
+
+pub struct Websocket {
+    inner: Mutex<speech::websocket::Websocket>,
+}
+
+impl Websocket {
+    pub fn new() -> Self {
+        Websocket {
+            inner: Mutex::new(speech::websocket::Websocket::new()),
+        }
+    }
+
+    pub fn connect(
+        &self,
+        speech: Arc<Speech>,
+        recognition_mode: RecognitionMode,
+        language: String,
+        result_format: ResultFormat,
+        listener: Box<dyn RecognitionListener>,
+    ) -> Result<(), BingError> {
+        let mode = mode(recognition_mode, &language)?;
+        let handler = Arc::new(Mutex::new(ListenerHandler(listener)));
+        self.inner.lock().unwrap().connect(
+            speech.token.clone(),
+            mode,
+            format(result_format),
+            *speech.is_custom_speech.lock().unwrap(),
+            &speech.endpoint_id.lock().unwrap(),
+            handler,
+        )?;
+        Ok(())
+    }
+
+    pub fn send_audio(&self, audio: Vec<u8>) -> Result<(), BingError> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.is_connected() {
+            return Err(BingError::NotConnected);
+        }
+        Ok(inner.audio(&audio)?)
+    }
+
+    pub fn end_audio(&self) -> Result<(), BingError> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.is_connected() {
+            return Err(BingError::NotConnected);
+        }
+        Ok(inner.end_audio()?)
+    }
+
+    pub fn disconnect(&self) -> Result<(), BingError> {
+        Ok(self.inner.lock().unwrap().disconnect()?)
+    }
+}
@@ -0,0 +1,171 @@
+//! Conversion of captured audio to the layout Bing Speech expects
+//!
+//! Capture devices usually deliver 44.1 or 48 kHz stereo. Sending that as
+//! is produces empty results, so downmix it to mono and resample it to
+//! 16 kHz first, either in one go with `convert` or chunk by chunk with a
+//! `Converter`.
+//!
+//! # Examples
+//!
+//! ```
+//! use bing_rs::audio;
+//!
+//! // 10 ms of 48 kHz stereo silence
+//! let captured = vec![0; 480 * 2 * 2];
+//! let audio = audio::convert(&captured, 48000, 2);
+//! assert_eq!(audio.len(), 160 * 2);
+//! ```
+
+use super::{downmix, from_samples, resample, to_samples, SAMPLE_RATE};
+
+/// Convert interleaved 16-bit PCM at `in_rate` Hz with `in_channels`
+/// channels into 16 kHz mono
+///
+/// Channels are averaged and samples linearly interpolated.
+pub fn convert(input: &[u8], in_rate: u32, in_channels: u16) -> Vec<u8> {
+    let samples = downmix(&to_samples(input), in_channels as usize);
+    from_samples(&resample(&samples, in_rate, SAMPLE_RATE))
+}
+
+/// Streaming version of `convert`
+///
+/// Keeps incomplete frames and the interpolation position between calls,
+/// so audio can be converted in chunks of any size without clicks or
+/// drift at chunk boundaries.
+#[derive(Debug, Clone)]
+pub struct Converter {
+    in_rate: u32,
+    in_channels: u16,
+    /// Input bytes of a frame split across calls
+    pending: Vec<u8>,
+    /// Last input sample of the previous call
+    previous: Option<i16>,
+    /// Position of the next output sample, in input samples from
+    /// `previous`
+    position: f64,
+}
+
+impl Converter {
+    pub fn new(in_rate: u32, in_channels: u16) -> Self {
+        Converter {
+            in_rate,
+            in_channels: in_channels.max(1),
+            pending: Vec::new(),
+            previous: None,
+            position: 0.0,
+        }
+    }
+
+    /// Whether input is already 16 kHz mono and passes through unchanged
+    pub fn is_passthrough(&self) -> bool {
+        self.in_rate == SAMPLE_RATE && self.in_channels == 1
+    }
+
+    /// Convert the next chunk of input, returning the 16 kHz mono audio
+    /// available so far
+    ///
+    /// Input at a rate of zero can't be converted and produces nothing.
+    pub fn process(&mut self, input: &[u8]) -> Vec<u8> {
+        if self.is_passthrough() {
+            return input.to_vec();
+        }
+        if self.in_rate == 0 {
+            return Vec::new();
+        }
+
+        let frame_len = self.in_channels as usize * 2;
+        self.pending.extend_from_slice(input);
+        let complete = self.pending.len() - self.pending.len() % frame_len;
+        let frames: Vec<u8> = self.pending.drain(..complete).collect();
+        let mut samples = downmix(&to_samples(&frames), self.in_channels as usize);
+        if self.in_rate == SAMPLE_RATE {
+            return from_samples(&samples);
+        }
+
+        // Interpolate across the boundary with the previous chunk
+        if let Some(previous) = self.previous {
+            samples.insert(0, previous);
+        }
+        if samples.len() < 2 {
+            if let Some(&last) = samples.last() {
+                self.previous = Some(last);
+            }
+            return Vec::new();
+        }
+
+        let ratio = self.in_rate as f64 / SAMPLE_RATE as f64;
+        let last = (samples.len() - 1) as f64;
+        let mut output = Vec::with_capacity((last / ratio) as usize + 1);
+        while self.position < last {
+            let index = self.position as usize;
+            let fraction = self.position - index as f64;
+            let a = f64::from(samples[index]);
+            let b = f64::from(samples[index + 1]);
+            output.push((a + (b - a) * fraction).round() as i16);
+            self.position += ratio;
+        }
+        self.position -= last;
+        self.previous = samples.last().cloned();
+        from_samples(&output)
+    }
+
+    /// Forget buffered input, e.g. before converting an unrelated stream
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.previous = None;
+        self.position = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_16_khz_mono_through() {
+        let mut converter = Converter::new(SAMPLE_RATE, 1);
+        assert!(converter.is_passthrough());
+        assert_eq!(converter.process(&[1, 2, 3]), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn zero_rate_produces_nothing() {
+        let mut converter = Converter::new(0, 2);
+        assert!(converter.process(&[1, 0, 2, 0]).is_empty());
+    }
+
+    #[test]
+    fn downmixes_frames_split_across_chunks() {
+        let mut converter = Converter::new(SAMPLE_RATE, 2);
+        let input = from_samples(&[100, 300, -100, -300]);
+        assert!(converter.process(&input[..3]).is_empty());
+        assert_eq!(converter.process(&input[3..]), from_samples(&[200, -200]));
+    }
+
+    #[test]
+    fn reset_drops_partial_frames() {
+        let mut converter = Converter::new(SAMPLE_RATE, 2);
+        converter.process(&[0xFF, 0x7F, 0xFF]);
+        converter.reset();
+        assert_eq!(
+            converter.process(&from_samples(&[100, 300])),
+            from_samples(&[200])
+        );
+    }
+
+    #[test]
+    fn chunked_conversion_matches_single_pass() {
+        let samples: Vec<i16> = (0..4800).map(|i| (i % 200) as i16 * 100).collect();
+        let input = from_samples(&samples);
+
+        let whole = Converter::new(48000, 1).process(&input);
+        assert_eq!(whole.len(), 1600 * 2);
+
+        let mut converter = Converter::new(48000, 1);
+        let mut chunked = Vec::new();
+        for chunk in input.chunks(7) {
+            chunked.extend(converter.process(chunk));
+        }
+        assert_eq!(chunked, whole);
+    }
+}
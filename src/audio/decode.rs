@@ -0,0 +1,101 @@
+//! Compressed audio decoding (requires the `codec` feature)
+//!
+//! Decodes MP3, Ogg, FLAC and WAV files via symphonia and converts them to
+//! the 16 kHz 16-bit mono PCM that Bing Speech expects.
+
+// std
+use std::fs::File;
+use std::io::ErrorKind as IoErrorKind;
+use std::path::Path;
+
+// symphonia
+use symphonia;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::DecoderOptions;
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+// internal
+use super::{downmix, from_samples, resample, SAMPLE_RATE};
+use errors::*;
+
+const EXTENSIONS: &[&str] = &["mp3", "ogg", "oga", "flac", "wav"];
+
+/// Whether the file extension is one the decoder handles
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Decode a compressed audio file into 16 kHz 16-bit mono PCM
+pub fn decode_file<P>(path: P) -> Result<Vec<u8>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe().format(
+        &hint,
+        stream,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+    let mut reader = probed.format;
+
+    let (track_id, codec_params) = match reader.default_track() {
+        Some(track) => (track.id, track.codec_params.clone()),
+        None => bail!(ErrorKind::UnsupportedAudio("no audio track".to_string())),
+    };
+    let sample_rate = match codec_params.sample_rate {
+        Some(sample_rate) => sample_rate,
+        None => bail!(ErrorKind::UnsupportedAudio("unknown sample rate".to_string())),
+    };
+    let mut decoder =
+        symphonia::default::get_codecs().make(&codec_params, &DecoderOptions::default())?;
+
+    let mut channels = 1;
+    let mut samples = Vec::new();
+    loop {
+        let packet = match reader.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(ref err)) if err.kind() == IoErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(err)) => {
+                warn!("Skipping undecodable packet: {}", err);
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let spec = *decoded.spec();
+        channels = spec.channels.count();
+        let mut buffer = SampleBuffer::<i16>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+    }
+
+    let samples = downmix(&samples, channels);
+    let samples = resample(&samples, sample_rate, SAMPLE_RATE);
+    Ok(from_samples(&samples))
+}
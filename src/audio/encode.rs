@@ -0,0 +1,131 @@
+//! Ogg/Opus encoding (requires the `ogg-opus` feature)
+//!
+//! Converts raw PCM, such as `Speech::synthesize` output, into an Ogg/Opus
+//! stream for compact storage and web delivery.
+
+// std
+use std::io::Write;
+
+// codecs
+use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+use opus::{Application, Channels, Encoder};
+
+// internal
+use super::to_samples;
+use errors::*;
+
+/// Opus granule positions are always expressed at 48 kHz
+const GRANULE_RATE: u32 = 48000;
+
+/// Frame duration used for encoding, in milliseconds
+const FRAME_MILLIS: u32 = 20;
+
+/// Largest Opus packet we expect to produce for a single frame
+const MAX_PACKET_SIZE: usize = 4000;
+
+/// Encode 16-bit mono PCM as Ogg/Opus
+///
+/// `sample_rate` must be one supported by Opus (8000, 12000, 16000, 24000
+/// or 48000). Synthesized audio is 16 kHz.
+pub fn to_ogg_opus(audio: &[u8], sample_rate: u32) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    write_ogg_opus(&mut output, audio, sample_rate)?;
+    Ok(output)
+}
+
+/// Encode 16-bit mono PCM as Ogg/Opus into `writer`
+pub fn write_ogg_opus<W>(writer: W, audio: &[u8], sample_rate: u32) -> Result<()>
+where
+    W: Write,
+{
+    match sample_rate {
+        8000 | 12000 | 16000 | 24000 | 48000 => {}
+        _ => bail!(ErrorKind::UnsupportedAudio(format!(
+            "Opus can't encode {} Hz audio",
+            sample_rate
+        ))),
+    };
+
+    let mut encoder = Encoder::new(sample_rate, Channels::Mono, Application::Voip)?;
+    let scale = u64::from(GRANULE_RATE / sample_rate);
+    let pre_skip = encoder.get_lookahead()? as u64 * scale;
+    let serial = 1;
+    let mut packets = PacketWriter::new(writer);
+
+    // Identification and comment headers each go on their own page
+    packets.write_packet(
+        opus_head(pre_skip as u16, sample_rate).into_boxed_slice(),
+        serial,
+        PacketWriteEndInfo::EndPage,
+        0,
+    )?;
+    packets.write_packet(
+        opus_tags().into_boxed_slice(),
+        serial,
+        PacketWriteEndInfo::EndPage,
+        0,
+    )?;
+
+    let frame_size = (sample_rate * FRAME_MILLIS / 1000) as usize;
+    let samples = to_samples(audio);
+    let frames = (samples.len() + frame_size - 1) / frame_size;
+    let mut granule = pre_skip;
+    let mut packet = vec![0; MAX_PACKET_SIZE];
+
+    for (i, chunk) in samples.chunks(frame_size).enumerate() {
+        // Pad the final frame with silence
+        let mut frame = chunk.to_vec();
+        frame.resize(frame_size, 0);
+
+        let len = encoder.encode(&frame, &mut packet)?;
+        granule += chunk.len() as u64 * scale;
+        let end = if i + 1 == frames {
+            PacketWriteEndInfo::EndStream
+        } else {
+            PacketWriteEndInfo::NormalPacket
+        };
+        packets.write_packet(
+            packet[..len].to_vec().into_boxed_slice(),
+            serial,
+            end,
+            granule,
+        )?;
+    }
+
+    // Terminate the stream even if there was no audio at all
+    if frames == 0 {
+        packets.write_packet(
+            Vec::new().into_boxed_slice(),
+            serial,
+            PacketWriteEndInfo::EndStream,
+            granule,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn opus_head(pre_skip: u16, sample_rate: u32) -> Vec<u8> {
+    let mut head = Vec::with_capacity(19);
+    head.extend_from_slice(b"OpusHead");
+    head.push(1); // version
+    head.push(1); // channel count
+    head.push((pre_skip & 0xFF) as u8);
+    head.push((pre_skip >> 8) as u8);
+    for i in 0..4 {
+        head.push(((sample_rate >> (i * 8)) & 0xFF) as u8);
+    }
+    head.extend_from_slice(&[0, 0]); // output gain
+    head.push(0); // channel mapping family
+    head
+}
+
+fn opus_tags() -> Vec<u8> {
+    let vendor = b"bing-rs";
+    let mut tags = Vec::new();
+    tags.extend_from_slice(b"OpusTags");
+    tags.extend_from_slice(&[vendor.len() as u8, 0, 0, 0]);
+    tags.extend_from_slice(vendor);
+    tags.extend_from_slice(&[0, 0, 0, 0]); // user comment count
+    tags
+}
@@ -0,0 +1,80 @@
+//! Automatic end-pointing
+//!
+//! Watches outgoing audio for sustained trailing silence after speech so the
+//! turn can be closed without push-to-talk.
+
+use std::time::Duration;
+
+use super::{gain, Spec};
+
+/// State reported by the `Endpointer` for each chunk of audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// No speech has been heard yet
+    Waiting,
+    /// Speech is in progress (including short pauses)
+    Speech,
+    /// Speech was followed by enough silence to end the utterance
+    End,
+}
+
+/// Detects the end of an utterance from trailing silence
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use bing_rs::audio::endpoint::{Endpoint, Endpointer};
+///
+/// let mut endpointer = Endpointer::new(0.01, Duration::from_millis(800));
+/// assert_eq!(endpointer.process(&[0; 3200]), Endpoint::Waiting);
+/// ```
+pub struct Endpointer {
+    threshold: f32,
+    trailing_silence: Duration,
+    silence: Duration,
+    heard_speech: bool,
+}
+
+impl Endpointer {
+    /// Creates an endpointer for 16 kHz audio
+    ///
+    /// Audio with an RMS level under `threshold` (0.0 - 1.0 of full scale)
+    /// counts as silence, and `trailing_silence` of it after speech ends
+    /// the utterance.
+    pub fn new(threshold: f32, trailing_silence: Duration) -> Self {
+        Endpointer {
+            threshold,
+            trailing_silence,
+            silence: Duration::new(0, 0),
+            heard_speech: false,
+        }
+    }
+
+    /// Feed the next chunk of 16-bit PCM audio
+    pub fn process(&mut self, audio: &[u8]) -> Endpoint {
+        if gain::rms(audio) >= self.threshold {
+            self.heard_speech = true;
+            self.silence = Duration::new(0, 0);
+            return Endpoint::Speech;
+        }
+
+        if !self.heard_speech {
+            return Endpoint::Waiting;
+        }
+
+        self.silence += Spec::default().duration(audio.len());
+        if self.silence >= self.trailing_silence {
+            self.reset();
+            Endpoint::End
+        } else {
+            Endpoint::Speech
+        }
+    }
+
+    /// Forget any speech heard so far and start waiting for the next utterance
+    pub fn reset(&mut self) {
+        self.silence = Duration::new(0, 0);
+        self.heard_speech = false;
+    }
+}
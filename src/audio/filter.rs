@@ -0,0 +1,93 @@
+//! DC-offset removal and high-pass filtering
+//!
+//! Cheap microphones often add a DC bias and low-frequency rumble that waste
+//! bandwidth and confuse speech detection.
+
+use std::f32::consts::PI;
+
+use super::{clamp_sample, map_samples, Processor, SAMPLE_RATE};
+
+/// Cutoff frequency suitable for removing DC bias and rumble from speech
+pub const DEFAULT_CUTOFF: f32 = 80.0;
+
+/// First-order high-pass filter
+///
+/// Keeps its state between calls so it can be fed consecutive audio chunks.
+///
+/// # Examples
+///
+/// ```
+/// use bing_rs::audio::filter::HighPass;
+///
+/// let mut filter = HighPass::new(80.0);
+/// let mut audio = vec![0x00, 0x10, 0x00, 0x10];
+/// filter.process(&mut audio);
+/// ```
+pub struct HighPass {
+    alpha: f32,
+    previous_input: f32,
+    previous_output: f32,
+}
+
+impl HighPass {
+    /// Creates a high-pass filter for 16 kHz audio with the given cutoff in Hz
+    pub fn new(cutoff: f32) -> Self {
+        Self::with_sample_rate(cutoff, SAMPLE_RATE)
+    }
+
+    /// Creates a high-pass filter for audio at an arbitrary sample rate
+    pub fn with_sample_rate(cutoff: f32, sample_rate: u32) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff);
+        let dt = 1.0 / sample_rate as f32;
+        HighPass {
+            alpha: rc / (rc + dt),
+            previous_input: 0.0,
+            previous_output: 0.0,
+        }
+    }
+
+    /// Filter a chunk of 16-bit PCM audio in place
+    pub fn process(&mut self, audio: &mut [u8]) {
+        let alpha = self.alpha;
+        let previous_input = &mut self.previous_input;
+        let previous_output = &mut self.previous_output;
+
+        map_samples(audio, |sample| {
+            let input = sample as f32;
+            let output = alpha * (*previous_output + input - *previous_input);
+            *previous_input = input;
+            *previous_output = output;
+            clamp_sample(output)
+        });
+    }
+
+    /// Clear the filter state, e.g. when starting a new utterance
+    pub fn reset(&mut self) {
+        self.previous_input = 0.0;
+        self.previous_output = 0.0;
+    }
+}
+
+impl Default for HighPass {
+    fn default() -> Self {
+        Self::new(DEFAULT_CUTOFF)
+    }
+}
+
+impl Processor for HighPass {
+    fn process(&mut self, audio: &mut [u8]) {
+        HighPass::process(self, audio);
+    }
+}
+
+/// Subtract the mean of the audio from every sample
+pub fn remove_dc_offset(audio: &mut [u8]) {
+    let samples = super::to_samples(audio);
+    if samples.is_empty() {
+        return;
+    }
+
+    let sum: i64 = samples.iter().map(|sample| *sample as i64).sum();
+    let mean = (sum / samples.len() as i64) as f32;
+    map_samples(audio, |sample| clamp_sample(sample as f32 - mean));
+}
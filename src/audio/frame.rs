@@ -0,0 +1,100 @@
+//! Fixed-size audio framing
+
+// std
+use std::io::{self, ErrorKind, Read};
+use std::time::Duration;
+
+use super::SAMPLE_RATE;
+
+/// Frame size used by the examples and the C API when streaming audio
+pub const DEFAULT_FRAME_SIZE: usize = 4096;
+
+/// Wraps any `Read` and yields fixed-size audio frames
+///
+/// Every frame is exactly `frame_size` bytes except for the last one, which
+/// holds whatever was left when the reader hit EOF.
+///
+/// # Examples
+///
+/// ```
+/// use bing_rs::audio::FrameReader;
+///
+/// let audio = vec![0u8; 10000];
+/// let frames: Vec<Vec<u8>> = FrameReader::new(&audio[..], 4096)
+///     .map(|frame| frame.unwrap())
+///     .collect();
+/// assert_eq!(frames.len(), 3);
+/// assert_eq!(frames[2].len(), 10000 - 2 * 4096);
+/// ```
+pub struct FrameReader<R> {
+    reader: R,
+    frame_size: usize,
+    done: bool,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Creates a frame reader yielding `frame_size` bytes per frame
+    pub fn new(reader: R, frame_size: usize) -> Self {
+        FrameReader {
+            reader,
+            frame_size: frame_size.max(1),
+            done: false,
+        }
+    }
+
+    /// Creates a frame reader yielding `duration` of 16 kHz 16-bit mono audio per frame
+    pub fn with_duration(reader: R, duration: Duration) -> Self {
+        let millis = duration.as_secs() * 1000 + u64::from(duration.subsec_millis());
+        let samples = millis * u64::from(SAMPLE_RATE) / 1000;
+        Self::new(reader, samples as usize * 2)
+    }
+
+    /// Size in bytes of every full frame
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    /// Unwraps the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_frame(&mut self) -> io::Result<Vec<u8>> {
+        let mut frame = vec![0; self.frame_size];
+        let mut len = 0;
+
+        while len < self.frame_size {
+            match self.reader.read(&mut frame[len..]) {
+                Ok(0) => {
+                    self.done = true;
+                    break;
+                }
+                Ok(n) => len += n,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        frame.truncate(len);
+        Ok(frame)
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        match self.read_frame() {
+            Ok(ref frame) if frame.is_empty() => None,
+            Ok(frame) => Some(Ok(frame)),
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
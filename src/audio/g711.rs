@@ -0,0 +1,148 @@
+//! G.711 µ-law / A-law transcoding
+//!
+//! Telephony audio (SIP/RTP) is usually 8 kHz G.711. These helpers convert
+//! it to and from linear 16-bit PCM, and upsample to the 16 kHz Bing Speech
+//! expects so call audio can be fed to recognition directly.
+
+use super::{from_samples, resample, to_samples, SAMPLE_RATE};
+
+/// Sample rate of G.711 telephony audio
+pub const G711_SAMPLE_RATE: u32 = 8000;
+
+const ULAW_BIAS: i32 = 0x84;
+const ULAW_CLIP: i32 = 32635;
+const ALAW_SEGMENT_END: [i32; 8] = [0x1F, 0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF];
+
+/// Decode a single µ-law byte into a linear sample
+pub fn ulaw_to_linear(value: u8) -> i16 {
+    let value = !value;
+    let sign = value & 0x80;
+    let exponent = (value >> 4) & 0x07;
+    let mantissa = (value & 0x0F) as i32;
+    let sample = (((mantissa << 3) + ULAW_BIAS) << exponent) - ULAW_BIAS;
+
+    if sign != 0 {
+        -sample as i16
+    } else {
+        sample as i16
+    }
+}
+
+/// Encode a single linear sample as µ-law
+pub fn linear_to_ulaw(sample: i16) -> u8 {
+    let mut sample = sample as i32;
+    let sign = if sample < 0 {
+        sample = -sample;
+        0x80
+    } else {
+        0x00
+    };
+    sample = sample.min(ULAW_CLIP) + ULAW_BIAS;
+
+    let mut exponent = 7;
+    let mut mask = 0x4000;
+    while sample & mask == 0 && exponent > 0 {
+        exponent -= 1;
+        mask >>= 1;
+    }
+    let mantissa = (sample >> (exponent + 3)) & 0x0F;
+
+    !(sign | (exponent << 4) | mantissa) as u8
+}
+
+/// Decode a single A-law byte into a linear sample
+pub fn alaw_to_linear(value: u8) -> i16 {
+    let value = value ^ 0x55;
+    let mut sample = ((value & 0x0F) as i32) << 4;
+    let segment = ((value & 0x70) >> 4) as i32;
+    match segment {
+        0 => sample += 8,
+        1 => sample += 0x108,
+        _ => {
+            sample += 0x108;
+            sample <<= segment - 1;
+        }
+    };
+
+    if value & 0x80 != 0 {
+        sample as i16
+    } else {
+        -sample as i16
+    }
+}
+
+/// Encode a single linear sample as A-law
+pub fn linear_to_alaw(sample: i16) -> u8 {
+    let mut pcm = (sample as i32) >> 3;
+    let mask = if pcm >= 0 {
+        0xD5
+    } else {
+        pcm = -pcm - 1;
+        0x55
+    };
+
+    match ALAW_SEGMENT_END.iter().position(|end| pcm <= *end) {
+        Some(segment) => {
+            let shift = if segment < 2 { 1 } else { segment };
+            let value = ((segment as i32) << 4) | ((pcm >> shift) & 0x0F);
+            (value ^ mask) as u8
+        }
+        None => (0x7F ^ mask) as u8,
+    }
+}
+
+/// Decode µ-law audio into 16-bit PCM at the same (8 kHz) rate
+pub fn decode_ulaw(audio: &[u8]) -> Vec<u8> {
+    let samples: Vec<i16> = audio.iter().map(|value| ulaw_to_linear(*value)).collect();
+    from_samples(&samples)
+}
+
+/// Encode 16-bit PCM as µ-law
+pub fn encode_ulaw(audio: &[u8]) -> Vec<u8> {
+    to_samples(audio)
+        .into_iter()
+        .map(linear_to_ulaw)
+        .collect()
+}
+
+/// Decode A-law audio into 16-bit PCM at the same (8 kHz) rate
+pub fn decode_alaw(audio: &[u8]) -> Vec<u8> {
+    let samples: Vec<i16> = audio.iter().map(|value| alaw_to_linear(*value)).collect();
+    from_samples(&samples)
+}
+
+/// Encode 16-bit PCM as A-law
+pub fn encode_alaw(audio: &[u8]) -> Vec<u8> {
+    to_samples(audio)
+        .into_iter()
+        .map(linear_to_alaw)
+        .collect()
+}
+
+/// Decode 8 kHz µ-law audio into 16 kHz 16-bit PCM ready for recognition
+///
+/// # Examples
+///
+/// ```
+/// use bing_rs::audio::g711;
+///
+/// let rtp_payload = vec![0xFF; 160];
+/// let audio = g711::ulaw_to_pcm16k(&rtp_payload);
+/// assert_eq!(audio.len(), 640);
+/// ```
+pub fn ulaw_to_pcm16k(audio: &[u8]) -> Vec<u8> {
+    upsample(audio, ulaw_to_linear)
+}
+
+/// Decode 8 kHz A-law audio into 16 kHz 16-bit PCM ready for recognition
+pub fn alaw_to_pcm16k(audio: &[u8]) -> Vec<u8> {
+    upsample(audio, alaw_to_linear)
+}
+
+fn upsample<F>(audio: &[u8], decode: F) -> Vec<u8>
+where
+    F: Fn(u8) -> i16,
+{
+    let samples: Vec<i16> = audio.iter().map(|value| decode(*value)).collect();
+    from_samples(&resample(&samples, G711_SAMPLE_RATE, SAMPLE_RATE))
+}
@@ -0,0 +1,129 @@
+//! Gain control and normalization
+//!
+//! Quiet microphone input measurably hurts recognition accuracy, so these
+//! can be applied to each audio chunk before it's sent to Bing.
+
+use super::{clamp_sample, map_samples, to_samples, Processor};
+
+/// Multiply every sample by a fixed gain factor
+///
+/// # Examples
+///
+/// ```
+/// use bing_rs::audio::gain;
+///
+/// let mut audio = vec![0x00, 0x10, 0x00, 0xF0];
+/// gain::apply(&mut audio, 2.0);
+/// ```
+pub fn apply(audio: &mut [u8], gain: f32) {
+    map_samples(audio, |sample| clamp_sample(sample as f32 * gain));
+}
+
+/// Apply a gain expressed in decibels
+pub fn apply_db(audio: &mut [u8], db: f32) {
+    apply(audio, db_to_gain(db));
+}
+
+/// Convert decibels to a linear gain factor
+pub fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Return the absolute peak sample value of the audio
+pub fn peak(audio: &[u8]) -> i16 {
+    let peak = to_samples(audio)
+        .iter()
+        .map(|sample| (*sample as i32).abs())
+        .max()
+        .unwrap_or(0);
+    peak.min(i16::max_value() as i32) as i16
+}
+
+/// Scale the audio so its peak reaches `target` (0.0 - 1.0 of full scale)
+///
+/// Returns the gain that was applied. Silent audio is left untouched.
+pub fn normalize(audio: &mut [u8], target: f32) -> f32 {
+    let peak = peak(audio);
+    if peak == 0 {
+        return 1.0;
+    }
+
+    let gain = target * i16::max_value() as f32 / peak as f32;
+    apply(audio, gain);
+    gain
+}
+
+/// Simple automatic gain control
+///
+/// Tracks the RMS level of incoming audio and smoothly adjusts the gain so
+/// the output approaches the target level.
+pub struct Agc {
+    target: f32,
+    max_gain: f32,
+    gain: f32,
+    attack: f32,
+    release: f32,
+}
+
+impl Agc {
+    /// Creates a new AGC with the target RMS level (0.0 - 1.0 of full scale)
+    /// and the maximum gain it's allowed to apply
+    pub fn new(target: f32, max_gain: f32) -> Self {
+        Agc {
+            target,
+            max_gain,
+            gain: 1.0,
+            attack: 0.5,
+            release: 0.05,
+        }
+    }
+
+    /// Sets how fast the gain drops (attack) and rises (release), 0.0 - 1.0
+    pub fn set_rates(&mut self, attack: f32, release: f32) {
+        self.attack = attack;
+        self.release = release;
+    }
+
+    /// Current gain factor
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Process a chunk of audio in place
+    pub fn process(&mut self, audio: &mut [u8]) {
+        let rms = rms(audio);
+        if rms > 0.0 {
+            let desired = (self.target / rms).min(self.max_gain);
+            let rate = if desired < self.gain {
+                self.attack
+            } else {
+                self.release
+            };
+            self.gain += (desired - self.gain) * rate;
+        }
+
+        apply(audio, self.gain);
+    }
+}
+
+impl Processor for Agc {
+    fn process(&mut self, audio: &mut [u8]) {
+        Agc::process(self, audio);
+    }
+}
+
+/// Root mean square level of the audio (0.0 - 1.0 of full scale)
+pub fn rms(audio: &[u8]) -> f32 {
+    let samples = to_samples(audio);
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    let sum: f64 = samples
+        .iter()
+        .map(|sample| {
+            let value = *sample as f64 / i16::max_value() as f64;
+            value * value
+        }).sum();
+    (sum / samples.len() as f64).sqrt() as f32
+}
@@ -0,0 +1,94 @@
+//! Noise gate
+//!
+//! Mutes audio while its level stays under a threshold so constant
+//! background hiss doesn't keep triggering speech detection.
+
+use std::time::Duration;
+
+use super::{clamp_sample, map_samples, Processor, SAMPLE_RATE};
+
+/// Noise gate with attack and release smoothing
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use bing_rs::audio::gate::NoiseGate;
+///
+/// let mut gate = NoiseGate::new(0.02, Duration::from_millis(5), Duration::from_millis(200));
+/// let mut audio = vec![0x10, 0x00, 0xF0, 0xFF];
+/// gate.process(&mut audio);
+/// ```
+pub struct NoiseGate {
+    threshold: f32,
+    attack: f32,
+    release: f32,
+    envelope: f32,
+    gain: f32,
+}
+
+impl NoiseGate {
+    /// Creates a noise gate for 16 kHz audio
+    ///
+    /// `threshold` is the level (0.0 - 1.0 of full scale) under which audio
+    /// is muted, `attack` is how fast the gate opens and `release` is how
+    /// fast it closes again.
+    pub fn new(threshold: f32, attack: Duration, release: Duration) -> Self {
+        NoiseGate {
+            threshold,
+            attack: coefficient(attack),
+            release: coefficient(release),
+            envelope: 0.0,
+            gain: 0.0,
+        }
+    }
+
+    /// Sets the level (0.0 - 1.0 of full scale) under which audio is muted
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Whether the gate is currently letting audio through
+    pub fn is_open(&self) -> bool {
+        self.gain > 0.5
+    }
+
+    /// Gate a chunk of 16-bit PCM audio in place
+    pub fn process(&mut self, audio: &mut [u8]) {
+        let threshold = self.threshold;
+        let attack = self.attack;
+        let release = self.release;
+        let envelope = &mut self.envelope;
+        let gain = &mut self.gain;
+
+        map_samples(audio, |sample| {
+            // Follow the signal level quickly on the way up, slowly on the way down
+            let level = (sample as f32 / i16::max_value() as f32).abs();
+            let rate = if level > *envelope { attack } else { release };
+            *envelope += (level - *envelope) * rate;
+
+            let target = if *envelope >= threshold { 1.0 } else { 0.0 };
+            let rate = if target > *gain { attack } else { release };
+            *gain += (target - *gain) * rate;
+
+            clamp_sample(sample as f32 * *gain)
+        });
+    }
+}
+
+impl Processor for NoiseGate {
+    fn process(&mut self, audio: &mut [u8]) {
+        NoiseGate::process(self, audio);
+    }
+}
+
+/// Per-sample smoothing coefficient reaching ~63% of the target in `time`
+fn coefficient(time: Duration) -> f32 {
+    let seconds = time.as_secs() as f32 + time.subsec_nanos() as f32 / 1_000_000_000.0;
+    let samples = seconds * SAMPLE_RATE as f32;
+    if samples < 1.0 {
+        1.0
+    } else {
+        1.0 - (-1.0 / samples).exp()
+    }
+}
@@ -0,0 +1,300 @@
+//! Audio preprocessing utilities
+//!
+//! Bing Speech expects 16 kHz, 16-bit, mono little-endian PCM. The helpers in
+//! this module operate on that layout and can be applied to audio buffers
+//! right before they're passed to `Speech::recognize` or `Websocket::audio`.
+
+pub mod convert;
+#[cfg(feature = "codec")]
+pub mod decode;
+#[cfg(feature = "ogg-opus")]
+pub mod encode;
+pub mod endpoint;
+pub mod filter;
+pub mod frame;
+pub mod g711;
+pub mod gain;
+pub mod gate;
+pub mod preroll;
+pub mod vad;
+pub mod wav;
+
+pub use self::convert::{convert, Converter};
+pub use self::frame::FrameReader;
+
+// std
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+// internal
+use errors::*;
+
+/// Sample rate expected by Bing Speech
+pub const SAMPLE_RATE: u32 = 16000;
+
+/// Layout of uncompressed PCM audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spec {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+}
+
+impl Spec {
+    /// Creates a new PCM layout description
+    pub fn new(sample_rate: u32, channels: u16, bits_per_sample: u16) -> Self {
+        Spec {
+            sample_rate,
+            channels,
+            bits_per_sample,
+        }
+    }
+
+    /// Number of bytes per second of audio
+    pub fn byte_rate(&self) -> u32 {
+        self.sample_rate * u32::from(self.block_align())
+    }
+
+    /// Number of bytes per frame (one sample for every channel)
+    pub fn block_align(&self) -> u16 {
+        self.channels * self.bits_per_sample / 8
+    }
+
+    /// Playback duration of `len` bytes of audio in this layout
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bing_rs::audio::Spec;
+    ///
+    /// assert_eq!(Spec::default().duration(32000), Duration::from_secs(1));
+    /// ```
+    pub fn duration(&self, len: usize) -> Duration {
+        let byte_rate = u64::from(self.byte_rate());
+        if byte_rate == 0 {
+            return Duration::new(0, 0);
+        }
+
+        let len = len as u64;
+        Duration::new(
+            len / byte_rate,
+            ((len % byte_rate) * 1_000_000_000 / byte_rate) as u32,
+        )
+    }
+
+    /// Number of bytes needed to hold `duration` of audio in this layout
+    pub fn bytes_for(&self, duration: Duration) -> usize {
+        let block_align = u64::from(self.block_align());
+        let nanos = duration.as_secs() * 1_000_000_000 + u64::from(duration.subsec_nanos());
+        let frames = nanos * u64::from(self.sample_rate) / 1_000_000_000;
+        (frames * block_align) as usize
+    }
+
+    /// Canonical 44-byte RIFF/WAVE header for `data_len` bytes of audio in
+    /// this layout
+    ///
+    /// Shorthand for `wav::WavHeader::for_spec(*self, data_len).to_bytes()`.
+    pub fn wav_header(&self, data_len: u32) -> Vec<u8> {
+        wav::WavHeader::for_spec(*self, data_len).to_bytes()
+    }
+}
+
+impl Default for Spec {
+    /// 16 kHz 16-bit mono, the layout Bing Speech expects
+    fn default() -> Self {
+        Spec::new(SAMPLE_RATE, 1, 16)
+    }
+}
+
+/// Playback duration of 16 kHz 16-bit mono PCM audio
+pub fn duration(audio: &[u8]) -> Duration {
+    Spec::default().duration(audio.len())
+}
+
+/// Convert 16-bit little-endian PCM bytes into samples
+pub fn to_samples(audio: &[u8]) -> Vec<i16> {
+    audio
+        .chunks(2)
+        .filter(|chunk| chunk.len() == 2)
+        .map(|chunk| (chunk[0] as u16 | (chunk[1] as u16) << 8) as i16)
+        .collect()
+}
+
+/// Convert samples into 16-bit little-endian PCM bytes
+pub fn from_samples(samples: &[i16]) -> Vec<u8> {
+    let mut audio = Vec::with_capacity(samples.len() * 2);
+    for sample in samples {
+        audio.push((*sample as u16 & 0xFF) as u8);
+        audio.push(((*sample as u16 >> 8) & 0xFF) as u8);
+    }
+    audio
+}
+
+/// Audio processing stage that can be inserted before audio is sent
+///
+/// See `Websocket::add_processor`.
+pub trait Processor {
+    fn process(&mut self, audio: &mut [u8]);
+}
+
+/// Read an audio file into 16 kHz 16-bit mono PCM
+///
+/// Files are expected to contain raw PCM. With the `codec` feature enabled,
+/// MP3, Ogg, FLAC and WAV files are decoded and resampled first.
+pub fn read_file<P>(path: P) -> Result<Vec<u8>>
+where
+    P: AsRef<Path>,
+{
+    #[cfg(feature = "codec")]
+    {
+        if decode::is_supported(path.as_ref()) {
+            return decode::decode_file(path);
+        }
+    }
+
+    let mut audio = Vec::new();
+    File::open(path)?.read_to_end(&mut audio)?;
+    Ok(audio)
+}
+
+/// Read a PCM WAV file, returning its layout and audio
+///
+/// Pass the audio through `convert` if the layout isn't 16 kHz mono.
+///
+/// # Examples
+///
+/// ```no_run
+/// use bing_rs::audio;
+///
+/// let (spec, pcm) = audio::read_wav("assets/audio.wav").unwrap();
+/// let pcm = audio::convert(&pcm, spec.sample_rate, spec.channels);
+/// ```
+pub fn read_wav<P>(path: P) -> Result<(Spec, Vec<u8>)>
+where
+    P: AsRef<Path>,
+{
+    wav::read(BufReader::new(File::open(path)?))
+}
+
+/// Write `pcm` in the layout `spec` to a WAV file at `path`
+///
+/// E.g. `Spec::default()` for audio from `Speech::synthesize`.
+pub fn write_wav<P>(path: P, spec: Spec, pcm: &[u8]) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let mut file = BufWriter::new(File::create(path)?);
+    wav::write(&mut file, spec, pcm)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Mix interleaved multi-channel samples down to mono
+pub fn downmix(samples: &[i16], channels: usize) -> Vec<i16> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+
+    samples
+        .chunks(channels)
+        .map(|frame| {
+            let sum: i32 = frame.iter().map(|sample| *sample as i32).sum();
+            (sum / frame.len() as i32) as i16
+        }).collect()
+}
+
+/// Extract a single channel from interleaved multi-channel 16-bit PCM
+///
+/// Useful for picking e.g. the customer leg out of a stereo call recording.
+/// Fails if `channel` is out of range.
+///
+/// # Examples
+///
+/// ```
+/// use bing_rs::audio;
+///
+/// // Two stereo frames: (L0, R0), (L1, R1)
+/// let stereo = vec![0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04, 0x00];
+/// assert_eq!(
+///     audio::select_channel(&stereo, 1, 2).unwrap(),
+///     vec![0x02, 0x00, 0x04, 0x00]
+/// );
+/// assert!(audio::select_channel(&stereo, 2, 2).is_err());
+/// ```
+pub fn select_channel(audio: &[u8], channel: usize, channels: usize) -> Result<Vec<u8>> {
+    if channel >= channels.max(1) {
+        bail!(ErrorKind::InvalidRequest(format!(
+            "channel {} out of range for {} channel(s)",
+            channel, channels
+        )));
+    }
+    if channels <= 1 {
+        return Ok(audio.to_vec());
+    }
+
+    let samples: Vec<i16> = to_samples(audio)
+        .chunks(channels)
+        .filter(|frame| frame.len() == channels)
+        .map(|frame| frame[channel])
+        .collect();
+    Ok(from_samples(&samples))
+}
+
+/// Resample mono samples from one rate to another using linear interpolation
+///
+/// Returns no samples if either rate is zero.
+pub fn resample(samples: &[i16], from_rate: u32, to_rate: u32) -> Vec<i16> {
+    if from_rate == 0 || to_rate == 0 {
+        return Vec::new();
+    }
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+
+    let ratio = from_rate as f64 / to_rate as f64;
+    let len = (samples.len() as f64 / ratio) as usize;
+    let mut output = Vec::with_capacity(len);
+    for i in 0..len {
+        let position = i as f64 * ratio;
+        let index = position as usize;
+        let fraction = position - index as f64;
+        let a = samples[index] as f64;
+        let b = if index + 1 < samples.len() {
+            samples[index + 1] as f64
+        } else {
+            a
+        };
+        output.push(clamp_sample((a + (b - a) * fraction) as f32));
+    }
+    output
+}
+
+/// Clamp a floating point sample into the 16-bit range
+fn clamp_sample(value: f32) -> i16 {
+    if value > i16::max_value() as f32 {
+        i16::max_value()
+    } else if value < i16::min_value() as f32 {
+        i16::min_value()
+    } else {
+        value as i16
+    }
+}
+
+/// Run `f` over every sample of a 16-bit PCM buffer in place
+fn map_samples<F>(audio: &mut [u8], mut f: F)
+where
+    F: FnMut(i16) -> i16,
+{
+    for chunk in audio.chunks_mut(2) {
+        if chunk.len() < 2 {
+            break;
+        }
+        let sample = (chunk[0] as u16 | (chunk[1] as u16) << 8) as i16;
+        let sample = f(sample) as u16;
+        chunk[0] = (sample & 0xFF) as u8;
+        chunk[1] = ((sample >> 8) & 0xFF) as u8;
+    }
+}
@@ -0,0 +1,119 @@
+//! Pre-roll ring buffer for wake-word scenarios
+//!
+//! Keeps the last N milliseconds of audio around so that, once a wake word
+//! triggers, the buffered audio can be sent to the websocket first and the
+//! start of the utterance isn't lost.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use speech::websocket::Websocket;
+use ws;
+
+use super::frame::DEFAULT_FRAME_SIZE;
+use super::{from_samples, to_samples, SAMPLE_RATE};
+
+/// Lock-free ring buffer holding the most recent audio samples
+///
+/// Cloning a `PreRoll` gives another handle to the same buffer, so the
+/// capture thread can keep pushing audio while another thread takes
+/// snapshots. Only one thread should push at a time.
+#[derive(Clone)]
+pub struct PreRoll {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    samples: Vec<AtomicUsize>,
+    written: AtomicUsize,
+    floor: AtomicUsize,
+}
+
+impl PreRoll {
+    /// Creates a pre-roll buffer holding `duration` worth of 16 kHz audio
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use bing_rs::audio::preroll::PreRoll;
+    ///
+    /// let preroll = PreRoll::new(Duration::from_millis(500));
+    /// preroll.push(&[0; 4096]);
+    /// assert_eq!(preroll.snapshot().len(), 4096);
+    /// ```
+    pub fn new(duration: Duration) -> Self {
+        let millis = duration.as_secs() * 1000 + u64::from(duration.subsec_millis());
+        let samples = (millis * u64::from(SAMPLE_RATE) / 1000) as usize;
+        Self::with_capacity(samples)
+    }
+
+    /// Creates a pre-roll buffer holding up to `samples` samples
+    pub fn with_capacity(samples: usize) -> Self {
+        let samples = samples.max(1);
+        PreRoll {
+            inner: Arc::new(Inner {
+                samples: (0..samples).map(|_| AtomicUsize::new(0)).collect(),
+                written: AtomicUsize::new(0),
+                floor: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Maximum number of samples kept in the buffer
+    pub fn capacity(&self) -> usize {
+        self.inner.samples.len()
+    }
+
+    /// Append 16-bit PCM audio, overwriting the oldest samples when full
+    pub fn push(&self, audio: &[u8]) {
+        let capacity = self.capacity();
+        let mut written = self.inner.written.load(Ordering::Relaxed);
+        for sample in to_samples(audio) {
+            self.inner.samples[written % capacity].store(sample as u16 as usize, Ordering::Relaxed);
+            written += 1;
+        }
+        self.inner.written.store(written, Ordering::Release);
+    }
+
+    /// Copy the buffered audio out, oldest sample first
+    pub fn snapshot(&self) -> Vec<u8> {
+        let capacity = self.capacity();
+        let end = self.inner.written.load(Ordering::Acquire);
+        let floor = self.inner.floor.load(Ordering::Acquire);
+        let start = end.saturating_sub(capacity).max(floor);
+
+        let samples: Vec<i16> = (start..end)
+            .map(|i| self.inner.samples[i % capacity].load(Ordering::Relaxed) as u16 as i16)
+            .collect();
+
+        // Drop whatever the writer overwrote while we were copying
+        let overwritten = self
+            .inner
+            .written
+            .load(Ordering::Acquire)
+            .saturating_sub(capacity)
+            .saturating_sub(start)
+            .min(samples.len());
+
+        from_samples(&samples[overwritten..])
+    }
+
+    /// Discard the buffered audio
+    pub fn clear(&self) {
+        let written = self.inner.written.load(Ordering::Acquire);
+        self.inner.floor.store(written, Ordering::Release);
+    }
+
+    /// Send the buffered audio to the websocket and clear the buffer
+    pub fn send(&self, websocket: &mut Websocket) -> ws::Result<()> {
+        let audio = self.snapshot();
+        self.clear();
+        for chunk in audio.chunks(DEFAULT_FRAME_SIZE) {
+            websocket.audio(chunk)?;
+        }
+
+        Ok(())
+    }
+}
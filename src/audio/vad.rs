@@ -0,0 +1,164 @@
+//! Energy-based voice activity detection
+//!
+//! Tells speech from silence by the level of each chunk of audio, keeping
+//! voice active for a hangover after the level drops so short pauses
+//! between words don't cut the speech up.
+
+use std::time::Duration;
+
+use super::{gain, Spec};
+
+/// State reported by the `Detector` for each chunk of audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Activity {
+    /// No voice
+    Silence,
+    /// Voice started with this chunk
+    Start,
+    /// Voice continues, including pauses shorter than the hangover
+    Voice,
+    /// Voice ended with this chunk, after the hangover ran out
+    End,
+}
+
+impl Activity {
+    /// Whether the chunk is part of the speech
+    pub fn is_voice(&self) -> bool {
+        match *self {
+            Activity::Start | Activity::Voice => true,
+            Activity::Silence | Activity::End => false,
+        }
+    }
+}
+
+/// Detects voice from the audio level
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use bing_rs::audio::vad::{Activity, Detector};
+///
+/// let mut detector = Detector::new(0.01, Duration::from_millis(300));
+/// assert_eq!(detector.process(&[0; 3200]), Activity::Silence);
+/// ```
+pub struct Detector {
+    threshold: f32,
+    hangover: Duration,
+    silence: Duration,
+    active: bool,
+}
+
+impl Detector {
+    /// Creates a detector for 16 kHz audio
+    ///
+    /// Audio with an RMS level at or over `threshold` (0.0 - 1.0 of full
+    /// scale) is voice, and voice ends after `hangover` of audio under it.
+    pub fn new(threshold: f32, hangover: Duration) -> Self {
+        Detector {
+            threshold,
+            hangover,
+            silence: Duration::new(0, 0),
+            active: false,
+        }
+    }
+
+    /// Sets the level (0.0 - 1.0 of full scale) from which audio is voice
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Sets how much silence ends the voice
+    pub fn set_hangover(&mut self, hangover: Duration) {
+        self.hangover = hangover;
+    }
+
+    /// Whether voice is currently detected
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Feed the next chunk of 16-bit PCM audio
+    pub fn process(&mut self, audio: &[u8]) -> Activity {
+        if gain::rms(audio) >= self.threshold {
+            self.silence = Duration::new(0, 0);
+            if self.active {
+                return Activity::Voice;
+            }
+            self.active = true;
+            return Activity::Start;
+        }
+
+        if !self.active {
+            return Activity::Silence;
+        }
+
+        self.silence += Spec::default().duration(audio.len());
+        if self.silence >= self.hangover {
+            self.reset();
+            Activity::End
+        } else {
+            Activity::Voice
+        }
+    }
+
+    /// Go back to silence
+    pub fn reset(&mut self) {
+        self.silence = Duration::new(0, 0);
+        self.active = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use audio::from_samples;
+
+    /// 100 ms of audio at a constant level
+    fn chunk(level: i16) -> Vec<u8> {
+        from_samples(&[level; 1600])
+    }
+
+    #[test]
+    fn detects_voice_over_threshold() {
+        let mut detector = Detector::new(0.01, Duration::from_millis(300));
+        assert_eq!(detector.process(&chunk(0)), Activity::Silence);
+        assert!(!detector.is_active());
+        assert_eq!(detector.process(&chunk(8000)), Activity::Start);
+        assert_eq!(detector.process(&chunk(8000)), Activity::Voice);
+        assert!(detector.is_active());
+    }
+
+    #[test]
+    fn ends_voice_after_hangover() {
+        let mut detector = Detector::new(0.01, Duration::from_millis(300));
+        assert_eq!(detector.process(&chunk(8000)), Activity::Start);
+        assert_eq!(detector.process(&chunk(0)), Activity::Voice);
+        assert_eq!(detector.process(&chunk(0)), Activity::Voice);
+        // Voice in the pause starts the hangover over
+        assert_eq!(detector.process(&chunk(8000)), Activity::Voice);
+        assert_eq!(detector.process(&chunk(0)), Activity::Voice);
+        assert_eq!(detector.process(&chunk(0)), Activity::Voice);
+        assert_eq!(detector.process(&chunk(0)), Activity::End);
+        assert!(!detector.is_active());
+        assert_eq!(detector.process(&chunk(0)), Activity::Silence);
+    }
+
+    #[test]
+    fn reset_goes_back_to_silence() {
+        let mut detector = Detector::new(0.01, Duration::from_millis(300));
+        detector.process(&chunk(8000));
+        detector.reset();
+        assert_eq!(detector.process(&chunk(0)), Activity::Silence);
+        detector.set_threshold(0.5);
+        assert_eq!(detector.process(&chunk(8000)), Activity::Silence);
+    }
+
+    #[test]
+    fn start_and_voice_are_speech() {
+        assert!(Activity::Start.is_voice());
+        assert!(Activity::Voice.is_voice());
+        assert!(!Activity::Silence.is_voice());
+        assert!(!Activity::End.is_voice());
+    }
+}
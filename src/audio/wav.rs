@@ -0,0 +1,330 @@
+//! RIFF/WAVE headers for raw PCM, and reading and writing of WAV files
+//!
+//! # Examples
+//!
+//! ```
+//! use bing_rs::audio::wav::WavHeader;
+//!
+//! let pcm = vec![0u8; 32000];
+//! let mut wav = WavHeader::new(16000, 1, 16, pcm.len() as u32).to_bytes();
+//! wav.extend_from_slice(&pcm);
+//! assert_eq!(wav.len(), 44 + 32000);
+//! ```
+
+// std
+use std::io::{self, Read, Write};
+
+use super::{Spec, SAMPLE_RATE};
+use errors::*;
+
+/// `fmt ` chunk format tag of uncompressed PCM
+const FORMAT_PCM: u16 = 1;
+
+/// `fmt ` chunk format tag of WAVE_FORMAT_EXTENSIBLE files
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Bytes 2..16 of every KSDATAFORMAT_SUBTYPE GUID used by extensible files
+const SUBFORMAT_GUID_TAIL: &[u8] = &[
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+];
+
+/// Size in bytes of a canonical PCM WAV header
+pub const HEADER_LEN: usize = 44;
+
+/// Canonical 44-byte header of an uncompressed PCM WAV file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavHeader {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    /// Length in bytes of the audio following the header
+    pub data_len: u32,
+}
+
+impl WavHeader {
+    /// Header for `data_len` bytes of PCM audio
+    pub fn new(sample_rate: u32, channels: u16, bits_per_sample: u16, data_len: u32) -> Self {
+        WavHeader {
+            sample_rate,
+            channels,
+            bits_per_sample,
+            data_len,
+        }
+    }
+
+    /// Header for `data_len` bytes of audio in the layout `spec`
+    pub fn for_spec(spec: Spec, data_len: u32) -> Self {
+        Self::new(
+            spec.sample_rate,
+            spec.channels,
+            spec.bits_per_sample,
+            data_len,
+        )
+    }
+
+    /// Header for a stream of 16 kHz 16-bit mono audio whose length isn't
+    /// known up front
+    pub fn streaming() -> Self {
+        Self::new(SAMPLE_RATE, 1, 16, u32::max_value() - 36)
+    }
+
+    /// PCM layout described by the header
+    pub fn spec(&self) -> Spec {
+        Spec::new(self.sample_rate, self.channels, self.bits_per_sample)
+    }
+
+    /// Serialized header
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let spec = self.spec();
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&le_u32(self.data_len.saturating_add(36)));
+        header.extend_from_slice(b"WAVE");
+        header.extend_from_slice(b"fmt ");
+        header.extend_from_slice(&le_u32(16));
+        header.extend_from_slice(&le_u16(1));
+        header.extend_from_slice(&le_u16(self.channels));
+        header.extend_from_slice(&le_u32(self.sample_rate));
+        header.extend_from_slice(&le_u32(spec.byte_rate()));
+        header.extend_from_slice(&le_u16(spec.block_align()));
+        header.extend_from_slice(&le_u16(self.bits_per_sample));
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&le_u32(self.data_len));
+        header
+    }
+
+    /// Write the serialized header to `writer`
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+}
+
+/// Whether `audio` already starts with a RIFF/WAVE header
+pub fn has_header(audio: &[u8]) -> bool {
+    audio.len() >= 12 && &audio[0..4] == b"RIFF" && &audio[8..12] == b"WAVE"
+}
+
+/// Read a PCM WAV file from `reader`, returning its layout and audio
+///
+/// Chunks other than `fmt ` and `data` are skipped. Anything but 16-bit
+/// integer PCM fails with `ErrorKind::UnsupportedAudio`.
+pub fn read<R: Read>(mut reader: R) -> Result<(Spec, Vec<u8>)> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let (spec, audio) = parse(&bytes)?;
+    Ok((spec, audio.to_vec()))
+}
+
+/// Split an in-memory PCM WAV file into its layout and audio
+///
+/// A `data` chunk longer than the file, as written by `WavHeader::streaming`,
+/// extends to the end of the file.
+pub fn parse(bytes: &[u8]) -> Result<(Spec, &[u8])> {
+    if !has_header(bytes) {
+        bail!(ErrorKind::UnsupportedAudio(
+            "not a RIFF/WAVE file".to_string()
+        ));
+    }
+
+    let mut spec = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let len = read_u32(bytes, offset + 4) as usize;
+        let start = offset + 8;
+        let end = start.saturating_add(len).min(bytes.len());
+        let body = &bytes[start..end];
+        match id {
+            b"fmt " => {
+                if body.len() < 16 {
+                    bail!(ErrorKind::UnsupportedAudio(
+                        "truncated fmt chunk".to_string()
+                    ));
+                }
+                let mut format = read_u16(body, 0);
+                if format == FORMAT_EXTENSIBLE {
+                    if body.len() < 40 {
+                        bail!(ErrorKind::UnsupportedAudio(
+                            "truncated extensible fmt chunk".to_string()
+                        ));
+                    }
+                    // The sub-format GUID starts with the actual format tag
+                    if &body[26..40] != SUBFORMAT_GUID_TAIL {
+                        bail!(ErrorKind::UnsupportedAudio(
+                            "unknown WAV sub-format".to_string()
+                        ));
+                    }
+                    format = read_u16(body, 24);
+                }
+                if format != FORMAT_PCM {
+                    bail!(ErrorKind::UnsupportedAudio(format!(
+                        "WAV format tag {:#06x}; only PCM is supported",
+                        format
+                    )));
+                }
+                let bits_per_sample = read_u16(body, 14);
+                if bits_per_sample != 16 {
+                    bail!(ErrorKind::UnsupportedAudio(format!(
+                        "{}-bit WAV; only 16-bit PCM is supported",
+                        bits_per_sample
+                    )));
+                }
+                spec = Some(Spec::new(
+                    read_u32(body, 4),
+                    read_u16(body, 2),
+                    bits_per_sample,
+                ));
+            }
+            b"data" => match spec {
+                Some(spec) => return Ok((spec, body)),
+                None => bail!(ErrorKind::UnsupportedAudio(
+                    "data chunk before fmt chunk".to_string()
+                )),
+            },
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        offset = end.saturating_add(len & 1);
+    }
+    bail!(ErrorKind::UnsupportedAudio("no data chunk".to_string()))
+}
+
+/// Write `audio` in the layout `spec` to `writer` as a WAV file
+pub fn write<W: Write>(mut writer: W, spec: Spec, audio: &[u8]) -> io::Result<()> {
+    WavHeader::for_spec(spec, audio.len() as u32).write(&mut writer)?;
+    writer.write_all(audio)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from(bytes[offset]) | u16::from(bytes[offset + 1]) << 8
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from(read_u16(bytes, offset)) | u32::from(read_u16(bytes, offset + 2)) << 16
+}
+
+fn le_u16(value: u16) -> [u8; 2] {
+    [value as u8, (value >> 8) as u8]
+}
+
+fn le_u32(value: u32) -> [u8; 4] {
+    [
+        value as u8,
+        (value >> 8) as u8,
+        (value >> 16) as u8,
+        (value >> 24) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// WAVE_FORMAT_EXTENSIBLE file of 16 kHz mono audio in `sub_format`
+    fn extensible(sub_format: u16, bits_per_sample: u16) -> Vec<u8> {
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend_from_slice(&le_u32(4 + 8 + 40 + 8 + 4));
+        bytes.extend_from_slice(b"WAVE");
+        bytes.extend_from_slice(b"fmt ");
+        bytes.extend_from_slice(&le_u32(40));
+        bytes.extend_from_slice(&le_u16(FORMAT_EXTENSIBLE));
+        bytes.extend_from_slice(&le_u16(1));
+        bytes.extend_from_slice(&le_u32(16000));
+        bytes.extend_from_slice(&le_u32(16000 * u32::from(bits_per_sample) / 8));
+        bytes.extend_from_slice(&le_u16(bits_per_sample / 8));
+        bytes.extend_from_slice(&le_u16(bits_per_sample));
+        bytes.extend_from_slice(&le_u16(22));
+        bytes.extend_from_slice(&le_u16(bits_per_sample));
+        bytes.extend_from_slice(&le_u32(4));
+        bytes.extend_from_slice(&le_u16(sub_format));
+        bytes.extend_from_slice(SUBFORMAT_GUID_TAIL);
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&le_u32(4));
+        bytes.extend_from_slice(&[1, 0, 2, 0]);
+        bytes
+    }
+
+    fn is_unsupported(bytes: &[u8]) -> bool {
+        match parse(bytes) {
+            Err(err) => match *err.kind() {
+                ErrorKind::UnsupportedAudio(_) => true,
+                _ => false,
+            },
+            Ok(_) => false,
+        }
+    }
+
+    #[test]
+    fn parses_written_file() {
+        let spec = Spec::new(8000, 2, 16);
+        let mut bytes = Vec::new();
+        write(&mut bytes, spec, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(bytes.len(), HEADER_LEN + 4);
+        assert_eq!(parse(&bytes).unwrap(), (spec, &[1, 2, 3, 4][..]));
+    }
+
+    #[test]
+    fn streaming_data_extends_to_end_of_file() {
+        let mut bytes = WavHeader::streaming().to_bytes();
+        bytes.extend_from_slice(&[5, 6, 7, 8]);
+        let (spec, audio) = parse(&bytes).unwrap();
+        assert_eq!(spec, Spec::new(SAMPLE_RATE, 1, 16));
+        assert_eq!(audio, &[5, 6, 7, 8][..]);
+    }
+
+    #[test]
+    fn skips_padded_unknown_chunks() {
+        let header = WavHeader::new(SAMPLE_RATE, 1, 16, 2).to_bytes();
+        let mut bytes = header[..36].to_vec();
+        bytes.extend_from_slice(b"LIST");
+        bytes.extend_from_slice(&le_u32(3));
+        bytes.extend_from_slice(&[b'a', b'b', b'c', 0]);
+        bytes.extend_from_slice(&header[36..]);
+        bytes.extend_from_slice(&[9, 9]);
+        assert_eq!(parse(&bytes).unwrap().1, &[9, 9][..]);
+    }
+
+    #[test]
+    fn accepts_extensible_pcm() {
+        let bytes = extensible(FORMAT_PCM, 16);
+        let (spec, audio) = parse(&bytes).unwrap();
+        assert_eq!(spec, Spec::new(16000, 1, 16));
+        assert_eq!(audio, &[1, 0, 2, 0][..]);
+    }
+
+    #[test]
+    fn rejects_anything_but_16_bit_pcm() {
+        // 8-bit PCM
+        let mut bytes = WavHeader::new(SAMPLE_RATE, 1, 8, 2).to_bytes();
+        bytes.extend_from_slice(&[0, 0]);
+        assert!(is_unsupported(&bytes));
+
+        // IEEE float format tag
+        let mut bytes = WavHeader::new(SAMPLE_RATE, 1, 16, 2).to_bytes();
+        bytes[20] = 3;
+        bytes.extend_from_slice(&[0, 0]);
+        assert!(is_unsupported(&bytes));
+
+        // Extensible float, 24-bit PCM and unknown GUIDs
+        assert!(is_unsupported(&extensible(3, 16)));
+        assert!(is_unsupported(&extensible(FORMAT_PCM, 24)));
+        let mut bytes = extensible(FORMAT_PCM, 16);
+        bytes[50] ^= 0xFF;
+        assert!(is_unsupported(&bytes));
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(is_unsupported(b"not a wav file at all"));
+        assert!(is_unsupported(
+            &WavHeader::new(SAMPLE_RATE, 1, 16, 0).to_bytes()[..36]
+        ));
+
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend_from_slice(&le_u32(12));
+        bytes.extend_from_slice(b"WAVE");
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&le_u32(0));
+        assert!(is_unsupported(&bytes));
+    }
+}
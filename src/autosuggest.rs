@@ -0,0 +1,112 @@
+//! Bing Autosuggest v7 client
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::autosuggest::*;
+//!
+//! let autosuggest = Autosuggest::new("your_subscription_key").unwrap();
+//! let response = autosuggest.suggest("sail", "en-US").unwrap();
+//! for suggestion in response.suggestions() {
+//!     println!("{}", suggestion.display_text);
+//! }
+//! ```
+
+// internal
+use client::{ApiClient, HyperTransport, Transport};
+use errors::*;
+use search::{QueryContext, DEFAULT_ENDPOINT};
+
+/// Bing Autosuggest client
+pub struct Autosuggest {
+    client: ApiClient,
+}
+
+impl Autosuggest {
+    /// Creates a new Bing Autosuggest handle
+    pub fn new<T>(subscription_key: &T) -> Result<Self>
+    where
+        T: ToString,
+    {
+        let transport = HyperTransport::new()?;
+        Ok(Self::with_transport(subscription_key, transport))
+    }
+
+    /// Creates a new Bing Autosuggest handle sending requests through
+    /// `transport`
+    pub fn with_transport<T, U>(subscription_key: &T, transport: U) -> Self
+    where
+        T: ToString,
+        U: Transport + 'static,
+    {
+        Autosuggest {
+            client: ApiClient::new(&subscription_key.to_string(), DEFAULT_ENDPOINT, transport),
+        }
+    }
+
+    /// Sets Bing Autosuggest subscription key
+    pub fn set_subscription_key(&mut self, key: &str) {
+        self.client.set_subscription_key(key);
+    }
+
+    /// Override the base URL, e.g. for a custom Azure resource
+    pub fn set_endpoint(&mut self, endpoint: &str) {
+        self.client.set_endpoint(endpoint);
+    }
+
+    /// Suggest queries completing the partial `query`
+    ///
+    /// `market` is a market code such as "en-US".
+    pub fn suggest(&self, query: &str, market: &str) -> Result<SuggestResponse> {
+        let params = vec![
+            ("q".to_string(), query.to_string()),
+            ("mkt".to_string(), market.to_string()),
+        ];
+        self.client.get("Suggestions", &params)
+    }
+}
+
+/// Response of `Autosuggest::suggest`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestResponse {
+    #[serde(default)]
+    pub query_context: Option<QueryContext>,
+    #[serde(default)]
+    pub suggestion_groups: Vec<SuggestionGroup>,
+}
+
+impl SuggestResponse {
+    /// Suggestions of every group, in order
+    pub fn suggestions(&self) -> Vec<&Suggestion> {
+        self.suggestion_groups
+            .iter()
+            .flat_map(|group| group.search_suggestions.iter())
+            .collect()
+    }
+}
+
+/// Suggestions of one kind, e.g. "Web"
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionGroup {
+    pub name: String,
+    #[serde(default)]
+    pub search_suggestions: Vec<Suggestion>,
+}
+
+/// Suggested query
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Suggestion {
+    /// Text to show the user
+    pub display_text: String,
+    /// Query to send to Bing Search
+    pub query: String,
+    /// Bing search page for the query
+    #[serde(default)]
+    pub url: Option<String>,
+    /// "WebSearch", or "CustomSearch" for custom search suggestions
+    #[serde(default)]
+    pub search_kind: Option<String>,
+}
@@ -0,0 +1,316 @@
+//! Command line client for Bing Speech
+//!
+//! Build with `cargo build --features cli --bin bing`. The subscription key
+//! is read from `--key` or the `SUBSCRIPTION_KEY` environment variable, and
+//! the Custom Speech endpoint ID from `--endpoint-id` or `ENDPOINT_ID`.
+
+extern crate bing_rs;
+#[macro_use]
+extern crate clap;
+extern crate cpal;
+extern crate env_logger;
+#[macro_use]
+extern crate error_chain;
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::process;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use bing_rs::audio::{self, FrameReader, Spec};
+use bing_rs::errors::*;
+use bing_rs::speech::websocket::{Handler, Websocket};
+use bing_rs::speech::*;
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+
+fn main() {
+    env_logger::init();
+
+    let matches = App::new("bing")
+        .version(crate_version!())
+        .about("Bing Speech from the command line")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("key")
+                .long("key")
+                .takes_value(true)
+                .env("SUBSCRIPTION_KEY")
+                .help("Subscription key"),
+        )
+        .arg(
+            Arg::with_name("endpoint-id")
+                .long("endpoint-id")
+                .takes_value(true)
+                .env("ENDPOINT_ID")
+                .help("Custom Speech endpoint ID; enables Custom Speech"),
+        )
+        .subcommand(SubCommand::with_name("token").about("Fetch and print an access token"))
+        .subcommand(
+            SubCommand::with_name("recognize")
+                .about("Recognize speech in an audio file")
+                .arg(Arg::with_name("FILE").required(true))
+                .arg(mode_arg())
+                .arg(format_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("synthesize")
+                .about("Synthesize speech into a WAV file")
+                .arg(Arg::with_name("TEXT").required(true))
+                .arg(
+                    Arg::with_name("voice")
+                        .long("voice")
+                        .takes_value(true)
+                        .default_value("en-US:JessaRUS")
+                        .help("Voice as <language>:<name>, e.g. en-GB:George, Apollo"),
+                )
+                .arg(
+                    Arg::with_name("gender")
+                        .long("gender")
+                        .takes_value(true)
+                        .possible_values(&["Female", "Male"])
+                        .default_value("Female"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .takes_value(true)
+                        .default_value("output.wav"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stream")
+                .about("Stream audio through the websocket API and print results live")
+                .arg(
+                    Arg::with_name("FILE")
+                        .required_unless("mic")
+                        .help("Raw 16 kHz 16-bit mono PCM file, or - for stdin"),
+                )
+                .arg(
+                    Arg::with_name("mic")
+                        .long("mic")
+                        .conflicts_with("FILE")
+                        .help("Capture from the default microphone until interrupted"),
+                )
+                .arg(mode_arg())
+                .arg(format_arg()),
+        )
+        .get_matches();
+
+    if let Err(err) = run(&matches) {
+        eprintln!("error: {}", err);
+        for cause in err.iter().skip(1) {
+            eprintln!("caused by: {}", cause);
+        }
+        process::exit(1);
+    }
+}
+
+fn mode_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("mode")
+        .long("mode")
+        .takes_value(true)
+        .default_value("interactive:en-US")
+        .help("Recognition mode and language, e.g. dictation:fr-FR")
+}
+
+fn format_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(&["simple", "detailed"])
+        .default_value("simple")
+}
+
+fn run(matches: &ArgMatches) -> Result<()> {
+    let key = match matches.value_of("key") {
+        Some(key) => key,
+        None => bail!("missing subscription key; pass --key or set SUBSCRIPTION_KEY"),
+    };
+    let mut builder = Speech::builder().subscription_key(key);
+    let endpoint_id = matches.value_of("endpoint-id");
+    if let Some(endpoint_id) = endpoint_id {
+        builder = builder.custom_endpoint(endpoint_id);
+    }
+    let mut speech = builder.build()?;
+
+    let (_, status, token) = speech.fetch_token()?;
+    let token = match token {
+        Some(token) => token,
+        None => bail!("no token issued ({})", status),
+    };
+
+    match matches.subcommand() {
+        ("token", _) => {
+            println!("{}", token);
+            Ok(())
+        }
+        ("recognize", Some(matches)) => recognize(&speech, matches),
+        ("synthesize", Some(matches)) => synthesize(&speech, matches),
+        ("stream", Some(matches)) => {
+            let _refresher = speech.auto_fetch_token();
+            stream(&speech, matches, endpoint_id)
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn recognize(speech: &Speech, matches: &ArgMatches) -> Result<()> {
+    let mode: Mode = matches.value_of("mode").unwrap().parse()?;
+    let format: Format = matches.value_of("format").unwrap().parse()?;
+    let (_, status, phrase) =
+        speech.recognize_file(matches.value_of("FILE").unwrap(), mode, format)?;
+    match phrase {
+        Some(ref phrase) if format == Format::Detailed => print!("{}", phrase),
+        Some(ref phrase) => println!("{}", phrase.text().unwrap_or("")),
+        None => bail!("no result ({})", status),
+    };
+    Ok(())
+}
+
+fn synthesize(speech: &Speech, matches: &ArgMatches) -> Result<()> {
+    let voice = matches.value_of("voice").unwrap();
+    let mut parts = voice.splitn(2, ':');
+    let (lang, name) = match (parts.next(), parts.next()) {
+        (Some(lang), Some(name)) => (lang, name),
+        _ => bail!("invalid voice {:?}; expected <language>:<name>", voice),
+    };
+    let font = voice::Font {
+        lang: lang.to_string().into(),
+        gender: matches.value_of("gender").unwrap().parse()?,
+        name: format!(
+            "Microsoft Server Speech Text to Speech Voice ({}, {})",
+            lang, name
+        ).into(),
+        voice_type: voice::VoiceType::Standard,
+    };
+
+    let (_, status, audio) = speech.synthesize(matches.value_of("TEXT").unwrap(), &font)?;
+    let audio = match audio {
+        Some(audio) => audio,
+        None => bail!("no audio returned ({})", status),
+    };
+
+    let path = matches.value_of("output").unwrap();
+    audio::write_wav(path, Spec::default(), &audio)?;
+    eprintln!("Wrote {} bytes of audio to {}", audio.len(), path);
+    Ok(())
+}
+
+/// Prints results as they arrive and signals the end of each turn
+struct PrintHandler {
+    turn_end: Mutex<Sender<()>>,
+}
+
+impl Handler for PrintHandler {
+    fn on_speech_hypothesis(&mut self, hypothesis: Hypothesis) {
+        eprint!("\r\x1b[K… {}", hypothesis.text);
+    }
+
+    fn on_speech_phrase(&mut self, phrase: Phrase) {
+        eprint!("\r\x1b[K");
+        if let Some(text) = phrase.text() {
+            println!("{}", text);
+        }
+    }
+
+    fn on_turn_end(&mut self) {
+        let _ = self.turn_end.lock().unwrap().send(());
+    }
+}
+
+fn stream(speech: &Speech, matches: &ArgMatches, endpoint_id: Option<&str>) -> Result<()> {
+    let mode: Mode = matches.value_of("mode").unwrap().parse()?;
+    let format: Format = matches.value_of("format").unwrap().parse()?;
+    let (turn_end_tx, turn_end_rx) = channel();
+    let handler = Arc::new(Mutex::new(PrintHandler {
+        turn_end: Mutex::new(turn_end_tx),
+    }));
+
+    let mut ws = Websocket::new();
+    ws.connect(
+        speech.token.clone(),
+        mode,
+        format,
+        endpoint_id.is_some(),
+        endpoint_id.unwrap_or(""),
+        handler,
+    )?;
+    ws.config(&default_speech_config())?;
+
+    let chunk_duration = Spec::default().duration(audio::frame::DEFAULT_FRAME_SIZE);
+    if matches.is_present("mic") {
+        let chunks = microphone()?;
+        for chunk in chunks {
+            ws.audio(&chunk)?;
+        }
+        return Ok(());
+    }
+
+    let input: Box<Read> = match matches.value_of("FILE").unwrap() {
+        "-" => Box::new(io::stdin()),
+        path => Box::new(File::open(path)?),
+    };
+    for chunk in FrameReader::new(input, audio::frame::DEFAULT_FRAME_SIZE) {
+        ws.audio(&chunk?)?;
+        // Pace the upload in real time like a live source would
+        thread::sleep(chunk_duration);
+    }
+    ws.end_audio()?;
+
+    // Wait for the final phrase before disconnecting
+    let _ = turn_end_rx.recv_timeout(Duration::from_secs(30));
+    ws.disconnect()
+}
+
+/// Capture the default input device as 16 kHz 16-bit mono PCM chunks
+fn microphone() -> Result<Receiver<Vec<u8>>> {
+    let device = match cpal::default_input_device() {
+        Some(device) => device,
+        None => bail!("no input device available"),
+    };
+    let format = match device.default_input_format() {
+        Ok(format) => format,
+        Err(err) => bail!("failed to query input format: {:?}", err),
+    };
+    let event_loop = cpal::EventLoop::new();
+    let stream_id = match event_loop.build_input_stream(&device, &format) {
+        Ok(stream_id) => stream_id,
+        Err(err) => bail!("failed to open input stream: {:?}", err),
+    };
+    event_loop.play_stream(stream_id);
+
+    let (tx, rx) = channel();
+    let channels = format.channels as usize;
+    let sample_rate = format.sample_rate.0;
+    thread::spawn(move || {
+        event_loop.run(move |_, data| {
+            let samples: Vec<i16> = match data {
+                cpal::StreamData::Input {
+                    buffer: cpal::UnknownTypeInputBuffer::I16(buffer),
+                } => buffer.iter().cloned().collect(),
+                cpal::StreamData::Input {
+                    buffer: cpal::UnknownTypeInputBuffer::U16(buffer),
+                } => buffer
+                    .iter()
+                    .map(|&sample| (i32::from(sample) - 32768) as i16)
+                    .collect(),
+                cpal::StreamData::Input {
+                    buffer: cpal::UnknownTypeInputBuffer::F32(buffer),
+                } => buffer
+                    .iter()
+                    .map(|&sample| (sample.max(-1.0).min(1.0) * 32767.0) as i16)
+                    .collect(),
+                _ => return,
+            };
+            let samples = audio::downmix(&samples, channels);
+            let samples = audio::resample(&samples, sample_rate, audio::SAMPLE_RATE);
+            let _ = tx.send(audio::from_samples(&samples));
+        });
+    });
+
+    Ok(rx)
+}
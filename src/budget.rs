@@ -0,0 +1,261 @@
+//! Client-side quota budgeting
+//!
+//! A `Budget` counts the audio sent for recognition and the characters sent
+//! for synthesis per calendar month (UTC). Soft limits invoke a callback
+//! once when crossed; hard limits make further requests fail with
+//! `ErrorKind::BudgetExceeded` before they are sent. Create one budget per
+//! subscription key and attach it with `Speech::set_budget` /
+//! `Websocket::set_budget`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use bing_rs::budget::{Budget, Limits};
+//! use bing_rs::speech::Speech;
+//!
+//! let mut limits = Limits::default();
+//! limits.soft_audio = Some(Duration::from_secs(4 * 3600));
+//! limits.hard_audio = Some(Duration::from_secs(5 * 3600));
+//! limits.hard_characters = Some(5_000_000);
+//!
+//! let budget = Budget::load("budget.json", limits).unwrap();
+//! budget.on_soft_limit(|event| println!("{:?}", event));
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.set_budget(Some(budget.clone()));
+//! // ...
+//! budget.save("budget.json").unwrap();
+//! ```
+
+// std
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// chrono / serde
+use chrono::prelude::*;
+use serde_json;
+
+// internal
+use errors::*;
+
+/// Soft and hard monthly limits; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub soft_audio: Option<Duration>,
+    pub hard_audio: Option<Duration>,
+    pub soft_characters: Option<u64>,
+    pub hard_characters: Option<u64>,
+}
+
+/// Usage in one month
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// Milliseconds of audio sent for recognition
+    pub audio_ms: u64,
+    /// Characters sent for synthesis
+    pub characters: u64,
+}
+
+impl Usage {
+    pub fn audio(&self) -> Duration {
+        Duration::from_millis(self.audio_ms)
+    }
+}
+
+/// Soft limit crossing passed to the `on_soft_limit` callback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitEvent {
+    Audio {
+        month: String,
+        used: Duration,
+        limit: Duration,
+    },
+    Characters {
+        month: String,
+        used: u64,
+        limit: u64,
+    },
+}
+
+struct State {
+    limits: Limits,
+    /// Usage by month, formatted as "YYYY-MM"
+    months: BTreeMap<String, Usage>,
+    on_soft_limit: Option<Box<Fn(&LimitEvent) + Send + Sync>>,
+}
+
+/// Monthly usage tracker for one subscription key
+///
+/// Clones share the same counters.
+#[derive(Clone)]
+pub struct Budget {
+    state: Arc<Mutex<State>>,
+}
+
+impl Budget {
+    pub fn new(limits: Limits) -> Self {
+        Budget {
+            state: Arc::new(Mutex::new(State {
+                limits,
+                months: BTreeMap::new(),
+                on_soft_limit: None,
+            })),
+        }
+    }
+
+    /// Restore usage saved with `save`, or start fresh if `path` doesn't exist
+    pub fn load<P>(path: P, limits: Limits) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let budget = Budget::new(limits);
+        if path.as_ref().exists() {
+            let months = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+            budget.state.lock().unwrap().months = months;
+        }
+        Ok(budget)
+    }
+
+    /// Persist usage so it survives restarts
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(serde_json::to_writer_pretty(
+            writer,
+            &self.state.lock().unwrap().months,
+        )?)
+    }
+
+    /// Call `callback` once whenever a soft limit is crossed
+    pub fn on_soft_limit<F>(&self, callback: F)
+    where
+        F: Fn(&LimitEvent) + Send + Sync + 'static,
+    {
+        self.state.lock().unwrap().on_soft_limit = Some(Box::new(callback));
+    }
+
+    pub fn set_limits(&self, limits: Limits) {
+        self.state.lock().unwrap().limits = limits;
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.state.lock().unwrap().limits
+    }
+
+    /// Usage in the current month
+    pub fn current_usage(&self) -> Usage {
+        self.usage(&current_month())
+    }
+
+    /// Usage in `month`, formatted as "YYYY-MM"
+    pub fn usage(&self, month: &str) -> Usage {
+        self.state
+            .lock()
+            .unwrap()
+            .months
+            .get(month)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Count `audio` of recognition against the current month
+    ///
+    /// Fails without counting anything if the hard limit would be exceeded.
+    pub fn charge_audio(&self, audio: Duration) -> Result<()> {
+        let month = current_month();
+        let mut state = self.state.lock().unwrap();
+        let limits = state.limits;
+        let before = state
+            .months
+            .get(&month)
+            .cloned()
+            .unwrap_or_default()
+            .audio();
+        let after = before + audio;
+        if let Some(limit) = limits.hard_audio {
+            if after > limit {
+                bail!(ErrorKind::BudgetExceeded(format!(
+                    "audio limit of {} s for {} reached",
+                    limit.as_secs(),
+                    month
+                )));
+            }
+        }
+
+        state
+            .months
+            .entry(month.clone())
+            .or_insert_with(Usage::default)
+            .audio_ms = after.as_secs() * 1000 + u64::from(after.subsec_millis());
+        if let Some(limit) = limits.soft_audio {
+            if before <= limit && after > limit {
+                state.notify(&LimitEvent::Audio {
+                    month,
+                    used: after,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Count `characters` of synthesis against the current month
+    ///
+    /// Fails without counting anything if the hard limit would be exceeded.
+    pub fn charge_characters(&self, characters: u64) -> Result<()> {
+        let month = current_month();
+        let mut state = self.state.lock().unwrap();
+        let limits = state.limits;
+        let before = state
+            .months
+            .get(&month)
+            .cloned()
+            .unwrap_or_default()
+            .characters;
+        let after = before + characters;
+        if let Some(limit) = limits.hard_characters {
+            if after > limit {
+                bail!(ErrorKind::BudgetExceeded(format!(
+                    "character limit of {} for {} reached",
+                    limit, month
+                )));
+            }
+        }
+
+        state
+            .months
+            .entry(month.clone())
+            .or_insert_with(Usage::default)
+            .characters = after;
+        if let Some(limit) = limits.soft_characters {
+            if before <= limit && after > limit {
+                state.notify(&LimitEvent::Characters {
+                    month,
+                    used: after,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl State {
+    fn notify(&self, event: &LimitEvent) {
+        warn!("Soft limit crossed: {:?}", event);
+        if let Some(ref callback) = self.on_soft_limit {
+            callback(event);
+        }
+    }
+}
+
+fn current_month() -> String {
+    Utc::now().format("%Y-%m").to_string()
+}
@@ -0,0 +1,182 @@
+//! Microphone capture
+//!
+//! Enabled with the `capture` feature. Records the default input device and
+//! delivers 16-bit mono PCM at the requested sample rate, converting from
+//! whatever format the device prefers.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::capture::Microphone;
+//! use bing_rs::speech::*;
+//! use std::time::{Duration, Instant};
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.fetch_token().unwrap();
+//! let mode = Mode::Interactive(Language::EnglishUnitedStates);
+//! let mut session = speech.recognition_session(ContinuousOptions::new(mode, Format::Simple));
+//! let events = session.take_events().unwrap();
+//!
+//! // Listen for ten seconds
+//! let start = Instant::now();
+//! session
+//!     .from_microphone(|| start.elapsed() > Duration::from_secs(10))
+//!     .unwrap();
+//! for event in events.try_iter() {
+//!     if let SessionEvent::Phrase(phrase) = event {
+//!         println!("{}", phrase.text().unwrap_or(""));
+//!     }
+//! }
+//! ```
+
+// std
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// cpal
+use cpal;
+
+// internal
+use audio::{self, SAMPLE_RATE};
+use errors::*;
+use speech::RecognitionSession;
+
+/// Recording from the default input device
+///
+/// Recording stops when the `Microphone` is dropped.
+pub struct Microphone {
+    event_loop: Arc<cpal::EventLoop>,
+    stream_id: cpal::StreamId,
+    receiver: Receiver<Vec<u8>>,
+    stop: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Microphone {
+    /// Start recording, converting to 16-bit mono PCM at `sample_rate`
+    pub fn start(sample_rate: u32) -> Result<Self> {
+        let device = match cpal::default_input_device() {
+            Some(device) => device,
+            None => bail!("no input device available"),
+        };
+        let format = match device.default_input_format() {
+            Ok(format) => format,
+            Err(err) => bail!("failed to query input format: {:?}", err),
+        };
+
+        let channels = format.channels as usize;
+        let device_rate = format.sample_rate.0;
+        let event_loop = Arc::new(cpal::EventLoop::new());
+        let stream_id = match event_loop.build_input_stream(&device, &format) {
+            Ok(stream_id) => stream_id,
+            Err(err) => bail!("failed to open input stream: {:?}", err),
+        };
+        event_loop.play_stream(stream_id.clone());
+
+        let (tx, receiver) = channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_1 = stop.clone();
+        let event_loop_1 = event_loop.clone();
+        let worker = thread::spawn(move || {
+            event_loop_1.run(move |_, data| {
+                // `run` never returns, so unwind out of it to end the thread
+                if stop_1.load(Ordering::SeqCst) {
+                    panic::resume_unwind(Box::new(()));
+                }
+
+                let samples: Vec<i16> = match data {
+                    cpal::StreamData::Input {
+                        buffer: cpal::UnknownTypeInputBuffer::I16(buffer),
+                    } => buffer.iter().cloned().collect(),
+                    cpal::StreamData::Input {
+                        buffer: cpal::UnknownTypeInputBuffer::U16(buffer),
+                    } => buffer
+                        .iter()
+                        .map(|&sample| (i32::from(sample) - 32768) as i16)
+                        .collect(),
+                    cpal::StreamData::Input {
+                        buffer: cpal::UnknownTypeInputBuffer::F32(buffer),
+                    } => buffer
+                        .iter()
+                        .map(|&sample| (sample.max(-1.0).min(1.0) * 32767.0) as i16)
+                        .collect(),
+                    _ => return,
+                };
+
+                let mono = audio::downmix(&samples, channels);
+                let pcm = audio::from_samples(&audio::resample(&mono, device_rate, sample_rate));
+                // Nobody is listening once the Microphone is gone
+                let _ = tx.send(pcm);
+            });
+        });
+
+        Ok(Microphone {
+            event_loop,
+            stream_id,
+            receiver,
+            stop,
+            worker: Some(worker),
+        })
+    }
+
+    /// Receiver of recorded audio, in chunks of whatever size the device
+    /// delivers
+    pub fn receiver(&self) -> &Receiver<Vec<u8>> {
+        &self.receiver
+    }
+}
+
+impl Drop for Microphone {
+    fn drop(&mut self) {
+        // The thread exits on the next buffer the stream delivers, so the
+        // stream is only destroyed once it's gone
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        self.event_loop.destroy_stream(self.stream_id.clone());
+    }
+}
+
+impl<'a> RecognitionSession<'a> {
+    /// Start the session if needed and feed it audio from the default
+    /// microphone until `until` returns true, then `stop()`
+    ///
+    /// Audio is sent in chunks of `ContinuousOptions::chunk_size` bytes as
+    /// soon as it's recorded.
+    pub fn from_microphone<F>(&mut self, mut until: F) -> Result<()>
+    where
+        F: FnMut() -> bool,
+    {
+        let microphone = Microphone::start(SAMPLE_RATE)?;
+        if !self.is_started() {
+            self.start()?;
+        }
+
+        let chunk_size = self.chunk_size();
+        let mut pending = Vec::new();
+        while !until() {
+            match microphone
+                .receiver()
+                .recv_timeout(Duration::from_millis(100))
+            {
+                Ok(pcm) => pending.extend_from_slice(&pcm),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => bail!("microphone stopped"),
+            }
+            if pending.len() >= chunk_size {
+                let rest = pending.split_off(pending.len() / chunk_size * chunk_size);
+                self.push_audio(&pending)?;
+                pending = rest;
+            }
+        }
+        if !pending.is_empty() {
+            self.push_audio(&pending)?;
+        }
+        self.stop()
+    }
+}
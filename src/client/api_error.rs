@@ -0,0 +1,117 @@
+//! Error descriptions returned by the services
+//!
+//! Requests rejected with a 4xx or 5xx status usually carry a JSON body
+//! explaining why, e.g. `{"error":{"code":"BadArgument","message":"..."}}`.
+//! `check_response` attaches it as the cause of the status error.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//!
+//! let speech = Speech::new("your_subscription_key").unwrap();
+//! let mode = Mode::Interactive(Language::EnglishUnitedStates);
+//! if let Err(err) = speech.recognize(vec![0; 32000], mode, Format::Simple) {
+//!     if let Some(api_error) = err.api_error() {
+//!         println!("{}: {}", api_error.code.as_ref().map_or("-", |c| c.as_str()), api_error.message);
+//!     }
+//! }
+//! ```
+
+// serde_json
+use serde_json::{self, Value};
+
+// std
+use std::error;
+use std::fmt;
+
+// internal
+use errors::*;
+
+/// Longest message kept from a body that isn't JSON
+const MAX_TEXT_LEN: usize = 512;
+
+/// Error description found in the body of a failed response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    /// Service error code, e.g. "BadArgument", if the body had one
+    pub code: Option<String>,
+    pub message: String,
+}
+
+impl ApiError {
+    /// Extract the error description from a response body
+    ///
+    /// Understands `{"error": {"code", "message"}}` bodies, flat
+    /// `{"code", "message"}` ones in any letter case and plain text. Returns
+    /// `None` for empty bodies.
+    pub fn from_body(body: &[u8]) -> Option<Self> {
+        if body.iter().all(|byte| byte.is_ascii_whitespace()) {
+            return None;
+        }
+
+        match serde_json::from_slice::<Value>(body) {
+            Ok(value) => {
+                let object = match value.get("error") {
+                    Some(error) if error.is_object() => error,
+                    _ => &value,
+                };
+                let code = field(object, &["code", "Code", "statusCode"]);
+                let message = field(object, &["message", "Message", "error_description"]);
+                if code.is_none() && message.is_none() {
+                    return None;
+                }
+                Some(ApiError {
+                    code,
+                    message: message.unwrap_or_default(),
+                })
+            }
+            Err(_) => {
+                let text = String::from_utf8_lossy(body);
+                let text = text.trim();
+                let message = match text.char_indices().nth(MAX_TEXT_LEN) {
+                    Some((index, _)) => format!("{}...", &text[..index]),
+                    None => text.to_string(),
+                };
+                Some(ApiError {
+                    code: None,
+                    message,
+                })
+            }
+        }
+    }
+}
+
+/// First of `names` present in `object`, as a string
+fn field(object: &Value, names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| object.get(*name))
+        .filter_map(|value| match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }).next()
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.code {
+            Some(ref code) => write!(f, "{}: {}", code, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl error::Error for ApiError {}
+
+impl Error {
+    /// Error description sent by the service, if this error comes from a
+    /// failed response that had one
+    pub fn api_error(&self) -> Option<&ApiError> {
+        self.1
+            .next_error
+            .as_ref()
+            .and_then(|cause| cause.downcast_ref::<ApiError>())
+    }
+}
@@ -0,0 +1,39 @@
+//! Cancelling requests in flight
+
+// futures
+use futures::sync::oneshot;
+
+// std
+use std::sync::{Arc, Mutex};
+
+/// Handle aborting the requests a client has in flight
+///
+/// Clones share the same state, so a clone can be moved to another thread
+/// and cancel a blocking `recognize` there. Aborted requests fail with
+/// `ErrorKind::Cancelled`; requests made afterwards aren't affected.
+#[derive(Debug, Clone, Default)]
+pub struct RequestHandle {
+    waiters: Arc<Mutex<Vec<oneshot::Sender<()>>>>,
+}
+
+impl RequestHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort every request in flight
+    pub fn cancel(&self) {
+        for waiter in self.waiters.lock().unwrap().drain(..) {
+            let _ = waiter.send(());
+        }
+    }
+
+    /// Receiver resolving when `cancel` is called
+    pub(crate) fn register(&self) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        let mut waiters = self.waiters.lock().unwrap();
+        waiters.retain(|waiter| !waiter.is_canceled());
+        waiters.push(tx);
+        rx
+    }
+}
@@ -0,0 +1,527 @@
+//! HTTP transport used by the API clients
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::client::HyperTransport;
+//! use bing_rs::speech::Speech;
+//! use std::time::Duration;
+//!
+//! let transport = HyperTransport::builder()
+//!     .timeout(Duration::from_secs(30))
+//!     .build()
+//!     .unwrap();
+//! let speech = Speech::with_transport(&"your_subscription_key", transport);
+//! ```
+
+// tokio / futures
+use futures::sync::{mpsc, oneshot};
+use futures::{future, Future, Stream};
+use tokio_core::reactor::{Core, Handle, Timeout};
+
+// hyper
+use hyper::client::Client;
+use hyper::StatusCode;
+use hyper::header::RETRY_AFTER;
+use hyper::http::request::Builder;
+use hyper::{Body, HeaderMap, Method, Request, Uri};
+#[cfg(feature = "rustls")]
+use hyper_rustls::HttpsConnector;
+#[cfg(feature = "rust-native-tls")]
+use hyper_tls;
+#[cfg(feature = "rust-native-tls")]
+type HttpsConnector<T> = hyper_tls::HttpsConnector<T>;
+#[cfg(feature = "rust-native-tls")]
+use native_tls;
+#[cfg(feature = "rustls")]
+use tls_rustls;
+#[cfg(feature = "rustls")]
+use webpki_roots;
+
+// serde / url
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json;
+use url::{form_urlencoded, Url};
+
+// std
+use std::sync::{self, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// internal
+mod api_error;
+mod cancel;
+mod proxy;
+pub use self::api_error::ApiError;
+pub use self::cancel::RequestHandle;
+pub use self::proxy::{Proxy, TunnelConnector};
+use errors::*;
+
+/// hyper client used for HTTPS requests
+pub type HttpsClient = Client<HttpsConnector<TunnelConnector>>;
+
+/// Build a hyper client for HTTPS requests, through the proxy configured in
+/// the environment if any
+pub(crate) fn https_client() -> Result<HttpsClient> {
+    let connector = https_connector(&[], Proxy::from_env())?;
+    Ok(Client::builder().build(connector))
+}
+
+/// Build the TLS connector trusting the default roots plus
+/// `root_certificates`
+#[cfg(feature = "rustls")]
+fn https_connector(
+    root_certificates: &[Vec<u8>],
+    proxy: Option<Proxy>,
+) -> Result<HttpsConnector<TunnelConnector>> {
+    let mut config = tls_rustls::ClientConfig::new();
+    config
+        .root_store
+        .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+    for der in root_certificates {
+        if let Err(err) = config
+            .root_store
+            .add(&tls_rustls::Certificate(der.clone()))
+        {
+            bail!("invalid root certificate: {:?}", err);
+        }
+    }
+    Ok(HttpsConnector::from((TunnelConnector::new(proxy), config)))
+}
+
+/// Build the TLS connector trusting the default roots plus
+/// `root_certificates`
+#[cfg(feature = "rust-native-tls")]
+fn https_connector(
+    root_certificates: &[Vec<u8>],
+    proxy: Option<Proxy>,
+) -> Result<HttpsConnector<TunnelConnector>> {
+    let mut tls = native_tls::TlsConnector::builder();
+    for der in root_certificates {
+        tls.add_root_certificate(native_tls::Certificate::from_der(der)?);
+    }
+    Ok(HttpsConnector::from((TunnelConnector::new(proxy), tls.build()?)))
+}
+
+/// Builds a `HyperTransport`
+///
+/// Created with `HyperTransport::builder()`.
+#[derive(Default)]
+pub struct TransportBuilder {
+    timeout: Option<Duration>,
+    root_certificates: Vec<Vec<u8>>,
+    proxy: Option<Proxy>,
+    no_proxy: bool,
+    client: Option<HttpsClient>,
+}
+
+impl TransportBuilder {
+    /// Fail requests that haven't completed after `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Trust the DER-encoded certificate `der` in addition to the default
+    /// roots, e.g. for a TLS-intercepting corporate gateway
+    pub fn add_root_certificate(mut self, der: &[u8]) -> Self {
+        self.root_certificates.push(der.to_vec());
+        self
+    }
+
+    /// Tunnel requests through `proxy`
+    ///
+    /// Without this, the proxy set in the HTTPS_PROXY or HTTP_PROXY
+    /// environment variables is used.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self.no_proxy = false;
+        self
+    }
+
+    /// Connect directly, even if a proxy is set in the environment
+    pub fn no_proxy(mut self) -> Self {
+        self.proxy = None;
+        self.no_proxy = true;
+        self
+    }
+
+    /// Send requests through `client` instead of building one
+    ///
+    /// Root certificates and proxies set on the builder are ignored.
+    pub fn client(mut self, client: HttpsClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Build the hyper client the transport would use
+    pub fn build_client(&self) -> Result<HttpsClient> {
+        if let Some(ref client) = self.client {
+            return Ok(client.clone());
+        }
+        let proxy = if self.no_proxy {
+            None
+        } else {
+            self.proxy.clone().or_else(Proxy::from_env)
+        };
+        let connector = https_connector(&self.root_certificates, proxy)?;
+        Ok(Client::builder().build(connector))
+    }
+
+    pub fn build(self) -> Result<HyperTransport> {
+        let client = self.build_client()?;
+        Ok(HyperTransport {
+            jobs: Mutex::new(spawn_driver(client)?),
+            timeout: self.timeout,
+        })
+    }
+}
+
+/// Sends HTTP requests on behalf of a client
+///
+/// The default implementation is `HyperTransport`. Implement this to route
+/// requests elsewhere, e.g. `testing::MockTransport` for offline tests.
+/// Transports are shared by every thread using the client, so they must be
+/// `Send` and `Sync`.
+pub trait Transport: Send + Sync {
+    /// Send a request and collect the full response
+    fn send(&self, request: Request<Body>) -> Result<(HeaderMap, StatusCode, Vec<u8>)>;
+
+    /// Send a request honouring `options`
+    ///
+    /// The default implementation ignores the timeout and can't abort the
+    /// request once it's sent.
+    fn send_with(
+        &self,
+        request: Request<Body>,
+        _options: &SendOptions,
+    ) -> Result<(HeaderMap, StatusCode, Vec<u8>)> {
+        self.send(request)
+    }
+}
+
+/// Per-request settings passed to `Transport::send_with`
+#[derive(Debug, Clone, Default)]
+pub struct SendOptions {
+    /// Fail with `ErrorKind::Timeout` if the response takes longer,
+    /// overriding the transport's own timeout
+    pub timeout: Option<Duration>,
+    /// Handle that can abort the request
+    pub cancel: Option<RequestHandle>,
+}
+
+/// Transport backed by a hyper client driven by a background thread
+///
+/// The thread owns the tokio Core and runs the requests handed to it, so
+/// the transport can be shared between threads and requests from several
+/// threads reuse the same kept-alive connections. It exits once the
+/// transport is dropped.
+///
+/// Requests are sent once; `Speech` retries according to its
+/// `speech::RetryPolicy`.
+pub struct HyperTransport {
+    jobs: Mutex<mpsc::UnboundedSender<Job>>,
+    timeout: Option<Duration>,
+}
+
+type Response = (HeaderMap, StatusCode, Vec<u8>);
+
+/// Request handed to the driver thread
+struct Job {
+    request: Request<Body>,
+    timeout: Option<Duration>,
+    cancelled: Option<oneshot::Receiver<()>>,
+    reply: oneshot::Sender<Result<Response>>,
+}
+
+/// Start the thread sending the requests received on the returned channel
+fn spawn_driver(client: HttpsClient) -> Result<mpsc::UnboundedSender<Job>> {
+    let (jobs, receiver) = mpsc::unbounded::<Job>();
+    let (ready, started) = sync::mpsc::channel();
+    thread::Builder::new()
+        .name("bing-rs-transport".to_string())
+        .spawn(move || {
+            let mut core = match Core::new() {
+                Ok(core) => core,
+                Err(err) => {
+                    let _ = ready.send(Err(err));
+                    return;
+                }
+            };
+            let _ = ready.send(Ok(()));
+            let handle = core.handle();
+            let work = receiver.for_each(|job| {
+                handle.spawn(run(&handle, &client, job));
+                Ok(())
+            });
+            let _ = core.run(work);
+        })?;
+    match started.recv() {
+        Ok(result) => result?,
+        Err(_) => bail!("transport thread exited on startup"),
+    }
+    Ok(jobs)
+}
+
+/// Send one request and collect the response, honouring the timeout and
+/// cancellation, then reply with the result
+fn run(handle: &Handle, client: &HttpsClient, job: Job) -> Box<Future<Item = (), Error = ()>> {
+    let Job {
+        request,
+        timeout,
+        cancelled,
+        reply,
+    } = job;
+    let mut work: ResponseFuture = Box::new(
+        client
+            .request(request)
+            .and_then(|res| {
+                let header = res.headers().clone();
+                let status = res.status();
+                res.into_body()
+                    .concat2()
+                    .map(move |chunks| (header, status, chunks.to_vec()))
+            }).map_err(Error::from),
+    );
+
+    if let Some(timeout) = timeout {
+        let timer: ResponseFuture = match Timeout::new(timeout, handle) {
+            Ok(timer) => Box::new(
+                timer.then(move |_| -> Result<Response> { bail!(ErrorKind::Timeout(timeout)) }),
+            ),
+            Err(err) => Box::new(future::err(err.into())),
+        };
+        work = Box::new(
+            work.select(timer)
+                .map(|(response, _)| response)
+                .map_err(|(err, _)| err),
+        );
+    }
+    if let Some(cancelled) = cancelled {
+        let cancelled = cancelled.then(|result| -> ResponseFuture {
+            match result {
+                Ok(()) => Box::new(future::err(ErrorKind::Cancelled.into())),
+                // The handle is gone, so the request can't be cancelled
+                Err(_) => Box::new(future::empty()),
+            }
+        });
+        work = Box::new(
+            work.select(cancelled)
+                .map(|(response, _)| response)
+                .map_err(|(err, _)| err),
+        );
+    }
+    Box::new(work.then(move |result| {
+        // The caller may have given up waiting
+        let _ = reply.send(result);
+        Ok(())
+    }))
+}
+
+impl HyperTransport {
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    /// Start configuring a transport
+    pub fn builder() -> TransportBuilder {
+        TransportBuilder::default()
+    }
+
+    /// Creates a transport sending requests through an already configured
+    /// hyper client
+    pub fn with_client(client: HttpsClient) -> Result<Self> {
+        Self::builder().client(client).build()
+    }
+
+    /// Hand one request to the driver thread and wait for the response
+    fn run(&self, request: Request<Body>, options: &SendOptions) -> Result<Response> {
+        let (reply, response) = oneshot::channel();
+        let job = Job {
+            request,
+            timeout: options.timeout.or(self.timeout),
+            cancelled: options.cancel.as_ref().map(|cancel| cancel.register()),
+            reply,
+        };
+        if self.jobs.lock().unwrap().unbounded_send(job).is_err() {
+            bail!("transport thread stopped");
+        }
+        match response.wait() {
+            Ok(result) => result,
+            Err(_) => bail!("transport thread stopped"),
+        }
+    }
+}
+
+type ResponseFuture = Box<Future<Item = Response, Error = Error>>;
+
+impl Transport for HyperTransport {
+    fn send(&self, request: Request<Body>) -> Result<Response> {
+        self.send_with(request, &SendOptions::default())
+    }
+
+    fn send_with(&self, request: Request<Body>, options: &SendOptions) -> Result<Response> {
+        self.run(request, options)
+    }
+}
+
+/// Map a non-2xx response status to an error
+///
+/// 401 and 403 become `Unauthorized`, 429 `RateLimited` with the
+/// `Retry-After` delay if the service sent one, 5xx `ServiceUnavailable`
+/// and anything else `HttpStatus`.
+pub fn check_status(header: &HeaderMap, status: StatusCode) -> Result<()> {
+    if status.is_success() {
+        return Ok(());
+    }
+
+    let code = status.as_u16();
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => bail!(ErrorKind::Unauthorized(code)),
+        StatusCode::TOO_MANY_REQUESTS => {
+            let retry_after = header
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse().ok())
+                .map(Duration::from_secs);
+            bail!(ErrorKind::RateLimited(retry_after))
+        }
+        _ if status.is_server_error() => bail!(ErrorKind::ServiceUnavailable(code)),
+        _ => bail!(ErrorKind::HttpStatus(code)),
+    }
+}
+
+/// Like `check_status`, with the error description found in `body`, if
+/// any, attached as the cause of the error
+///
+/// See `Error::api_error`.
+pub fn check_response(header: &HeaderMap, status: StatusCode, body: &[u8]) -> Result<()> {
+    check_status(header, status).map_err(|err| match ApiError::from_body(body) {
+        Some(api_error) => {
+            let Error(kind, _) = err;
+            Error::with_chain(api_error, kind)
+        }
+        None => err,
+    })
+}
+
+/// Core shared by the JSON APIs authenticated with a subscription key
+///
+/// Used by `search::Search`, `spellcheck::SpellCheck` and friends.
+pub(crate) struct ApiClient {
+    transport: Box<Transport>,
+    subscription_key: String,
+    region: Option<String>,
+    endpoint: String,
+}
+
+impl ApiClient {
+    pub(crate) fn new<T>(subscription_key: &str, endpoint: &str, transport: T) -> Self
+    where
+        T: Transport + 'static,
+    {
+        ApiClient {
+            transport: Box::new(transport),
+            subscription_key: subscription_key.to_string(),
+            region: None,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub(crate) fn set_subscription_key(&mut self, key: &str) {
+        self.subscription_key = String::from(key);
+    }
+
+    pub(crate) fn set_endpoint(&mut self, endpoint: &str) {
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+    }
+
+    /// Region of the resource, sent with every request for services that
+    /// need it to accept regional and multi-service keys
+    pub(crate) fn set_region(&mut self, region: Option<&str>) {
+        self.region = region.map(String::from);
+    }
+
+    /// Send a GET request to `path` and deserialize the JSON response
+    pub(crate) fn get<T>(&self, path: &str, params: &[(String, String)]) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let request = self
+            .request(Method::GET, path, params)?
+            .body(Body::empty())
+            .map_err(|err| ErrorKind::InvalidRequest(err.to_string()))?;
+        self.send(request)
+    }
+
+    /// Send `form` URL-encoded in a POST request to `path` and deserialize
+    /// the JSON response
+    pub(crate) fn post_form<T>(
+        &self,
+        path: &str,
+        params: &[(String, String)],
+        form: &[(String, String)],
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let body = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(form)
+            .finish();
+        let request = self
+            .request(Method::POST, path, params)?
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(Body::from(body))
+            .map_err(|err| ErrorKind::InvalidRequest(err.to_string()))?;
+        self.send(request)
+    }
+
+    /// Send `body` serialized as JSON in a POST request to `path` and
+    /// deserialize the JSON response
+    pub(crate) fn post_json<B, T>(
+        &self,
+        path: &str,
+        params: &[(String, String)],
+        body: &B,
+    ) -> Result<T>
+    where
+        B: Serialize,
+        T: DeserializeOwned,
+    {
+        let body = serde_json::to_vec(body)?;
+        let request = self
+            .request(Method::POST, path, params)?
+            .header("Content-Type", "application/json")
+            .body(Body::from(body))
+            .map_err(|err| ErrorKind::InvalidRequest(err.to_string()))?;
+        self.send(request)
+    }
+
+    /// Start a request to `path` carrying the authentication headers
+    fn request(&self, method: Method, path: &str, params: &[(String, String)]) -> Result<Builder> {
+        let mut builder = Request::builder();
+        builder
+            .method(method)
+            .uri(self.uri(path, params)?)
+            .header("Ocp-Apim-Subscription-Key", self.subscription_key.as_str());
+        if let Some(ref region) = self.region {
+            builder.header("Ocp-Apim-Subscription-Region", region.as_str());
+        }
+        Ok(builder)
+    }
+
+    fn uri(&self, path: &str, params: &[(String, String)]) -> Result<Uri> {
+        let url = Url::parse_with_params(&format!("{}/{}", self.endpoint, path), params)?;
+        Ok(url.as_str().parse()?)
+    }
+
+    fn send<T>(&self, request: Request<Body>) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let (header, status, body) = self.transport.send(request)?;
+        check_response(&header, status, &body)?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
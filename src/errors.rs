@@ -1,12 +1,68 @@
 error_chain!{
     foreign_links {
-        CellBorrowMut(::std::cell::BorrowMutError);
         Hyper(::hyper::Error);
+        InvalidUri(::hyper::http::uri::InvalidUri);
         HyperTls(::native_tls::Error) #[cfg(feature = "rust-native-tls")];
         Io(::std::io::Error);
         SerdeJson(::serde_json::Error);
         FromUtf(::std::string::FromUtf8Error);
         Url(::url::ParseError);
         Ws(::ws::Error);
+        Opus(::opus::Error) #[cfg(feature = "ogg-opus")];
+        Symphonia(::symphonia::core::errors::Error) #[cfg(feature = "codec")];
+    }
+
+    errors {
+        BudgetExceeded(reason: String) {
+            description("budget exceeded")
+            display("budget exceeded: {}", reason)
+        }
+        Cancelled {
+            description("request cancelled")
+            display("request cancelled")
+        }
+        HttpStatus(status: u16) {
+            description("request failed")
+            display("request failed with HTTP status {}", status)
+        }
+        InvalidRequest(reason: String) {
+            description("invalid request")
+            display("invalid request: {}", reason)
+        }
+        Protocol(reason: String) {
+            description("malformed server message")
+            display("malformed server message: {}", reason)
+        }
+        RateLimited(retry_after: Option<::std::time::Duration>) {
+            description("rate limited")
+            display("rate limited{}", match *retry_after {
+                Some(ref retry_after) => format!(", retry after {}s", retry_after.as_secs()),
+                None => String::new(),
+            })
+        }
+        ServiceUnavailable(status: u16) {
+            description("service unavailable")
+            display("service unavailable (HTTP status {})", status)
+        }
+        Timeout(timeout: ::std::time::Duration) {
+            description("request timed out")
+            display("request timed out after {:?}", timeout)
+        }
+        Unauthorized(status: u16) {
+            description("unauthorized")
+            display("unauthorized (HTTP status {}), check the subscription key or token", status)
+        }
+        UnknownVariant(type_name: &'static str, value: String) {
+            description("unknown variant")
+            display("unknown {}: {:?}", type_name, value)
+        }
+        UnsupportedLanguage(mode: &'static str, language: String) {
+            description("language not supported in this mode")
+            display("{} is not supported in {} mode", language, mode)
+        }
+        UnsupportedAudio(reason: String) {
+            description("unsupported audio")
+            display("unsupported audio: {}", reason)
+        }
     }
 }
@@ -1,13 +1,30 @@
 //! # Bing for Rust
 //!
 //! `bing-rs` is a library for using Bing API via Rust.
+//!
+//! - `speech`: speech recognition and synthesis
+//! - `search`: web and image search
+//! - `autosuggest`: query completion
+//! - `spellcheck`: spelling and grammar checking
+//! - `translate`: text translation
 
 #[macro_use]
 extern crate error_chain;
 
+// Text matching
+extern crate aho_corasick;
+
+// Encoding
+extern crate base64;
+extern crate bytes;
+
+// Threads
+extern crate crossbeam_utils;
+
 // Tokio/Futures Crates
 extern crate futures;
 extern crate tokio_core;
+extern crate tokio_io;
 
 // Hyper Crates
 extern crate hyper;
@@ -17,6 +34,10 @@ extern crate hyper_rustls;
 extern crate hyper_tls;
 #[cfg(feature = "native-tls")]
 extern crate native_tls;
+#[cfg(feature = "rustls")]
+extern crate tls_rustls;
+#[cfg(feature = "rustls")]
+extern crate webpki_roots;
 
 // WebSocket Crates
 extern crate ws;
@@ -34,6 +55,10 @@ extern crate url;
 #[macro_use]
 extern crate log;
 
+// Statics
+#[macro_use]
+extern crate lazy_static;
+
 // Chrono
 extern crate chrono;
 
@@ -43,5 +68,35 @@ extern crate uuid;
 // C
 extern crate libc;
 
+// Codecs
+#[cfg(feature = "ogg-opus")]
+extern crate ogg;
+#[cfg(feature = "ogg-opus")]
+extern crate opus;
+#[cfg(feature = "codec")]
+extern crate symphonia;
+
+// Audio I/O
+#[cfg(any(feature = "capture", feature = "playback"))]
+extern crate cpal;
+
+pub mod audio;
+pub mod autosuggest;
+pub mod budget;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod client;
 pub mod errors;
+pub mod metrics;
+#[cfg(feature = "playback")]
+pub mod playback;
+mod redact;
+pub mod search;
 pub mod speech;
+pub mod spellcheck;
+pub mod stats;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod trace;
+pub mod transcript;
+pub mod translate;
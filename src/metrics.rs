@@ -0,0 +1,74 @@
+//! Metrics hooks
+//!
+//! The crate doesn't pick a metrics backend. Instead, implement `MetricsSink`
+//! to bridge the events below to Prometheus, statsd or anything else, and
+//! register it with `Speech::set_metrics_sink` / `Websocket::set_metrics_sink`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use errors::*;
+
+/// Kind of operation a metrics event belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    Token,
+    Recognize,
+    Synthesize,
+    /// Voice list fetched by `Speech::list_voices`
+    Voices,
+    Websocket,
+}
+
+/// Coarse classification of failures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// Connection, TLS or I/O failure
+    Network,
+    /// The service answered with a non-success HTTP status
+    Status(u16),
+    /// The response couldn't be parsed
+    Parse,
+    Other,
+}
+
+/// Receiver of client metrics events
+///
+/// All methods have empty default implementations so sinks only need to
+/// implement the events they care about.
+pub trait MetricsSink: Send + Sync {
+    fn request_started(&self, _kind: RequestKind) {}
+    /// Called right after `request_started` when a correlation ID is set
+    fn request_correlated(&self, _kind: RequestKind, _correlation_id: &str) {}
+    fn request_completed(&self, _kind: RequestKind, _status: Option<u16>, _elapsed: Duration) {}
+    fn bytes_sent(&self, _kind: RequestKind, _bytes: usize) {}
+    fn error(&self, _kind: RequestKind, _class: ErrorClass) {}
+    fn reconnect(&self) {}
+}
+
+/// Metrics sink that discards every event
+pub struct NoopMetrics;
+
+impl MetricsSink for NoopMetrics {}
+
+/// Default sink used until one is registered
+pub fn noop() -> Arc<MetricsSink> {
+    Arc::new(NoopMetrics)
+}
+
+impl<'a> From<&'a Error> for ErrorClass {
+    fn from(err: &Error) -> Self {
+        match *err.kind() {
+            ErrorKind::Hyper(_)
+            | ErrorKind::Io(_)
+            | ErrorKind::Ws(_)
+            | ErrorKind::Timeout(_) => ErrorClass::Network,
+            ErrorKind::SerdeJson(_) | ErrorKind::FromUtf(_) => ErrorClass::Parse,
+            ErrorKind::HttpStatus(status)
+            | ErrorKind::ServiceUnavailable(status)
+            | ErrorKind::Unauthorized(status) => ErrorClass::Status(status),
+            ErrorKind::RateLimited(_) => ErrorClass::Status(429),
+            _ => ErrorClass::Other,
+        }
+    }
+}
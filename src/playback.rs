@@ -0,0 +1,150 @@
+//! Speaker playback
+//!
+//! Enabled with the `playback` feature. Plays synthesized PCM on the default
+//! output device, converting to whatever format the device prefers.
+
+// std
+use std::panic;
+use std::thread;
+
+// cpal
+use cpal;
+
+// internal
+use audio::{self, wav};
+use errors::*;
+use speech::OutputFormat;
+
+/// Play 16-bit mono little-endian PCM and block until it has finished
+///
+/// # Examples
+///
+/// ```no_run
+/// use bing_rs::playback;
+/// use bing_rs::speech::*;
+///
+/// let mut speech = Speech::new("your_subscription_key").unwrap();
+/// speech.fetch_token().unwrap();
+/// if let Ok((_, _, Some(pcm))) = speech.synthesize("Hello", voice::en_us::JESSA_RUS) {
+///     playback::play_pcm16(&pcm, 16000).unwrap();
+/// }
+/// ```
+pub fn play_pcm16(pcm: &[u8], sample_rate: u32) -> Result<()> {
+    let device = match cpal::default_output_device() {
+        Some(device) => device,
+        None => bail!("no output device available"),
+    };
+    let format = match device.default_output_format() {
+        Ok(format) => format,
+        Err(err) => bail!("failed to query output format: {:?}", err),
+    };
+
+    let samples = audio::resample(&audio::to_samples(pcm), sample_rate, format.sample_rate.0);
+    let channels = format.channels as usize;
+    let event_loop = cpal::EventLoop::new();
+    let stream_id = match event_loop.build_output_stream(&device, &format) {
+        Ok(stream_id) => stream_id,
+        Err(err) => bail!("failed to open output stream: {:?}", err),
+    };
+    event_loop.play_stream(stream_id);
+
+    let player = thread::spawn(move || {
+        let mut samples = samples.into_iter();
+        let mut finished = false;
+        event_loop.run(move |_, data| {
+            // The device asking for more means the last buffer has played.
+            // `run` never returns, so unwind out of it to end the thread and
+            // drop the event loop along with its stream.
+            if finished {
+                panic::resume_unwind(Box::new(()));
+            }
+
+            // Write each mono sample to every channel of a frame, padding
+            // with silence once the audio has run out
+            let mut next = || match samples.next() {
+                Some(sample) => sample,
+                None => {
+                    finished = true;
+                    0
+                }
+            };
+            match data {
+                cpal::StreamData::Output {
+                    buffer: cpal::UnknownTypeOutputBuffer::I16(mut buffer),
+                } => {
+                    for frame in buffer.chunks_mut(channels) {
+                        let sample = next();
+                        for out in frame.iter_mut() {
+                            *out = sample;
+                        }
+                    }
+                }
+                cpal::StreamData::Output {
+                    buffer: cpal::UnknownTypeOutputBuffer::U16(mut buffer),
+                } => {
+                    for frame in buffer.chunks_mut(channels) {
+                        let sample = (i32::from(next()) + 32768) as u16;
+                        for out in frame.iter_mut() {
+                            *out = sample;
+                        }
+                    }
+                }
+                cpal::StreamData::Output {
+                    buffer: cpal::UnknownTypeOutputBuffer::F32(mut buffer),
+                } => {
+                    for frame in buffer.chunks_mut(channels) {
+                        let sample = f32::from(next()) / 32768.0;
+                        for out in frame.iter_mut() {
+                            *out = sample;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        });
+    });
+
+    // The thread always ends by unwinding
+    let _ = player.join();
+    Ok(())
+}
+
+/// Play audio synthesized in `format` and block until it has finished
+///
+/// Only the raw and RIFF 16-bit PCM formats can be played; anything else
+/// fails with `ErrorKind::UnsupportedAudio`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use bing_rs::playback;
+/// use bing_rs::speech::*;
+///
+/// let mut speech = Speech::new("your_subscription_key").unwrap();
+/// speech.fetch_token().unwrap();
+/// let format = OutputFormat::Riff24Khz16BitMonoPcm;
+/// if let Ok((_, _, Some(audio))) =
+///     speech.synthesize_with_format("Hello", voice::en_us::JESSA_RUS, format)
+/// {
+///     playback::play(&audio, format).unwrap();
+/// }
+/// ```
+pub fn play(audio: &[u8], format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Raw16Khz16BitMonoPcm | OutputFormat::Raw24Khz16BitMonoPcm => {
+            play_pcm16(audio, format.sample_rate())
+        }
+        OutputFormat::Riff16Khz16BitMonoPcm | OutputFormat::Riff24Khz16BitMonoPcm => {
+            let pcm = if wav::has_header(audio) && audio.len() >= wav::HEADER_LEN {
+                &audio[wav::HEADER_LEN..]
+            } else {
+                audio
+            };
+            play_pcm16(pcm, format.sample_rate())
+        }
+        _ => bail!(ErrorKind::UnsupportedAudio(format!(
+            "can't play {}, request a 16-bit PCM format",
+            format
+        ))),
+    }
+}
@@ -0,0 +1,59 @@
+//! Helpers for logging requests without leaking credentials
+
+use hyper::HeaderMap;
+
+/// Headers whose values must never end up in logs
+const SENSITIVE_HEADERS: &[&str] = &["authorization", "ocp-apim-subscription-key"];
+
+/// Maximum number of body bytes included in debug logs
+pub const MAX_BODY_LOG_LEN: usize = 512;
+
+/// Format headers for logging with credentials replaced by `[REDACTED]`
+pub fn headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_sensitive(name.as_str()) {
+                "[REDACTED]"
+            } else {
+                value.to_str().unwrap_or("[binary]")
+            };
+            format!("{}: {}", name, value)
+        }).collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Whether the header carries credentials
+pub fn is_sensitive(name: &str) -> bool {
+    SENSITIVE_HEADERS
+        .iter()
+        .any(|sensitive| sensitive.eq_ignore_ascii_case(name))
+}
+
+/// Format a body for logging, truncated to at most `MAX_BODY_LOG_LEN` bytes
+/// without splitting a character
+///
+/// Text bodies are logged as-is, anything else only by length. Bearer tokens
+/// returned by the token endpoint are always redacted.
+pub fn body(body: &[u8], is_token: bool) -> String {
+    if is_token {
+        return format!("[REDACTED {} bytes]", body.len());
+    }
+
+    let text = match ::std::str::from_utf8(body) {
+        Ok(text) => text,
+        Err(_) => return format!("[{} bytes of binary data]", body.len()),
+    };
+    if text.len() <= MAX_BODY_LOG_LEN {
+        return text.to_string();
+    }
+
+    // Cut before the first character that doesn't fit
+    let end = text
+        .char_indices()
+        .map(|(index, c)| index + c.len_utf8())
+        .take_while(|end| *end <= MAX_BODY_LOG_LEN)
+        .last()
+        .unwrap_or(0);
+    format!("{}... ({} bytes)", &text[..end], body.len())
+}
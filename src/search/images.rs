@@ -0,0 +1,237 @@
+//! Image Search results
+
+use super::*;
+
+/// Image size filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageSize {
+    Small,
+    Medium,
+    Large,
+    Wallpaper,
+}
+
+/// Image color filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageColor {
+    ColorOnly,
+    Monochrome,
+    Black,
+    Blue,
+    Brown,
+    Gray,
+    Green,
+    Orange,
+    Pink,
+    Purple,
+    Red,
+    Teal,
+    White,
+    Yellow,
+}
+
+/// Image license filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageLicense {
+    /// Any image with a license
+    Any,
+    Public,
+    Share,
+    ShareCommercially,
+    Modify,
+    ModifyCommercially,
+}
+
+/// Image aspect ratio filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageAspect {
+    Square,
+    Wide,
+    Tall,
+}
+
+/// Implement `Display` with the query parameter value of every variant
+macro_rules! filter_values {
+    ($ty:ident { $($variant:ident => $value:expr,)* }) => {
+        impl Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let s = match self {
+                    $($ty::$variant => $value,)*
+                };
+                write!(f, "{}", s)
+            }
+        }
+    };
+}
+
+filter_values!(ImageSize {
+    Small => "Small",
+    Medium => "Medium",
+    Large => "Large",
+    Wallpaper => "Wallpaper",
+});
+
+filter_values!(ImageColor {
+    ColorOnly => "ColorOnly",
+    Monochrome => "Monochrome",
+    Black => "Black",
+    Blue => "Blue",
+    Brown => "Brown",
+    Gray => "Gray",
+    Green => "Green",
+    Orange => "Orange",
+    Pink => "Pink",
+    Purple => "Purple",
+    Red => "Red",
+    Teal => "Teal",
+    White => "White",
+    Yellow => "Yellow",
+});
+
+filter_values!(ImageLicense {
+    Any => "Any",
+    Public => "Public",
+    Share => "Share",
+    ShareCommercially => "ShareCommercially",
+    Modify => "Modify",
+    ModifyCommercially => "ModifyCommercially",
+});
+
+filter_values!(ImageAspect {
+    Square => "Square",
+    Wide => "Wide",
+    Tall => "Tall",
+});
+
+/// Options for `Search::images`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageOptions {
+    pub common: CommonOptions,
+    pub size: Option<ImageSize>,
+    pub color: Option<ImageColor>,
+    pub license: Option<ImageLicense>,
+    pub aspect: Option<ImageAspect>,
+}
+
+impl ImageOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn market(mut self, market: &str) -> Self {
+        self.common.market = Some(market.to_string());
+        self
+    }
+
+    pub fn safe_search(mut self, safe_search: SafeSearch) -> Self {
+        self.common.safe_search = Some(safe_search);
+        self
+    }
+
+    pub fn count(mut self, count: u32) -> Self {
+        self.common.count = Some(count);
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.common.offset = Some(offset);
+        self
+    }
+
+    pub fn size(mut self, size: ImageSize) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn color(mut self, color: ImageColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn license(mut self, license: ImageLicense) -> Self {
+        self.license = Some(license);
+        self
+    }
+
+    pub fn aspect(mut self, aspect: ImageAspect) -> Self {
+        self.aspect = Some(aspect);
+        self
+    }
+
+    pub(crate) fn params(&self) -> Vec<(String, String)> {
+        let mut params = self.common.params();
+        if let Some(size) = self.size {
+            params.push(("size".to_string(), size.to_string()));
+        }
+        if let Some(color) = self.color {
+            params.push(("color".to_string(), color.to_string()));
+        }
+        if let Some(license) = self.license {
+            params.push(("license".to_string(), license.to_string()));
+        }
+        if let Some(aspect) = self.aspect {
+            params.push(("aspect".to_string(), aspect.to_string()));
+        }
+        params
+    }
+}
+
+/// Response of `Search::images`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageResponse {
+    #[serde(default)]
+    pub query_context: Option<QueryContext>,
+    #[serde(default)]
+    pub web_search_url: Option<String>,
+    #[serde(default)]
+    pub total_estimated_matches: Option<u64>,
+    /// Offset to pass to get the next page of results
+    #[serde(default)]
+    pub next_offset: Option<u32>,
+    #[serde(default)]
+    pub value: Vec<Image>,
+}
+
+/// Single image result
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub image_id: Option<String>,
+    /// Full-size image
+    pub content_url: String,
+    pub thumbnail_url: String,
+    #[serde(default)]
+    pub thumbnail: Option<Thumbnail>,
+    /// Page the image was found on
+    #[serde(default)]
+    pub host_page_url: Option<String>,
+    #[serde(default)]
+    pub web_search_url: Option<String>,
+    /// e.g. "jpeg"
+    #[serde(default)]
+    pub encoding_format: Option<String>,
+    /// e.g. "120033 B"
+    #[serde(default)]
+    pub content_size: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    /// Hex color, e.g. "C06F2A"
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    /// Token for requesting image insights
+    #[serde(default)]
+    pub image_insights_token: Option<String>,
+}
+
+/// Size of an image thumbnail
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+}
@@ -0,0 +1,153 @@
+//! Bing Search v7 client
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::search::*;
+//!
+//! let search = Search::new("your_subscription_key").unwrap();
+//! let options = WebOptions::new().market("en-US").count(10);
+//! let response = search.web("rust websocket", &options).unwrap();
+//! for page in response.pages() {
+//!     println!("{}: {}", page.name, page.url);
+//! }
+//! ```
+
+// internal
+pub mod images;
+pub mod web;
+pub use self::images::*;
+pub use self::web::*;
+use client::{ApiClient, HyperTransport, Transport};
+use errors::*;
+
+// std
+use std::fmt::{self, Display};
+
+/// Base URL of the Bing Search v7 APIs
+pub const DEFAULT_ENDPOINT: &str = "https://api.cognitive.microsoft.com/bing/v7.0";
+
+/// Adult content filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SafeSearch {
+    Off,
+    Moderate,
+    Strict,
+}
+
+impl Display for SafeSearch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SafeSearch::Off => write!(f, "Off"),
+            SafeSearch::Moderate => write!(f, "Moderate"),
+            SafeSearch::Strict => write!(f, "Strict"),
+        }
+    }
+}
+
+/// Bing Search client
+pub struct Search {
+    client: ApiClient,
+}
+
+impl Search {
+    /// Creates a new Bing Search handle
+    pub fn new<T>(subscription_key: &T) -> Result<Self>
+    where
+        T: ToString,
+    {
+        let transport = HyperTransport::new()?;
+        Ok(Self::with_transport(subscription_key, transport))
+    }
+
+    /// Creates a new Bing Search handle sending requests through `transport`
+    pub fn with_transport<T, U>(subscription_key: &T, transport: U) -> Self
+    where
+        T: ToString,
+        U: Transport + 'static,
+    {
+        Search {
+            client: ApiClient::new(&subscription_key.to_string(), DEFAULT_ENDPOINT, transport),
+        }
+    }
+
+    /// Sets Bing Search subscription key
+    pub fn set_subscription_key(&mut self, key: &str) {
+        self.client.set_subscription_key(key);
+    }
+
+    /// Override the base URL, e.g. for a custom Azure resource
+    pub fn set_endpoint(&mut self, endpoint: &str) {
+        self.client.set_endpoint(endpoint);
+    }
+
+    /// Search the web for `query`
+    pub fn web(&self, query: &str, options: &WebOptions) -> Result<WebResponse> {
+        let mut params = vec![("q".to_string(), query.to_string())];
+        params.extend(options.params());
+        self.client.get("search", &params)
+    }
+
+    /// Search for images matching `query`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use bing_rs::search::*;
+    ///
+    /// let search = Search::new("your_subscription_key").unwrap();
+    /// let options = ImageOptions::new()
+    ///     .size(ImageSize::Large)
+    ///     .license(ImageLicense::Public);
+    /// for image in search.images("sailboats", &options).unwrap().value {
+    ///     println!("{}", image.content_url);
+    /// }
+    /// ```
+    pub fn images(&self, query: &str, options: &ImageOptions) -> Result<ImageResponse> {
+        let mut params = vec![("q".to_string(), query.to_string())];
+        params.extend(options.params());
+        self.client.get("images/search", &params)
+    }
+}
+
+/// Paging and filtering parameters shared by every search
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommonOptions {
+    /// Market the results come from, e.g. "en-US"
+    pub market: Option<String>,
+    pub safe_search: Option<SafeSearch>,
+    /// Number of results to return
+    pub count: Option<u32>,
+    /// Number of results to skip
+    pub offset: Option<u32>,
+}
+
+impl CommonOptions {
+    fn params(&self) -> Vec<(String, String)> {
+        let mut params = Vec::new();
+        if let Some(ref market) = self.market {
+            params.push(("mkt".to_string(), market.clone()));
+        }
+        if let Some(safe_search) = self.safe_search {
+            params.push(("safeSearch".to_string(), safe_search.to_string()));
+        }
+        if let Some(count) = self.count {
+            params.push(("count".to_string(), count.to_string()));
+        }
+        if let Some(offset) = self.offset {
+            params.push(("offset".to_string(), offset.to_string()));
+        }
+        params
+    }
+}
+
+/// Query the search engine understood
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryContext {
+    pub original_query: String,
+    #[serde(default)]
+    pub altered_query: Option<String>,
+    #[serde(default)]
+    pub adult_intent: Option<bool>,
+}
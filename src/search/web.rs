@@ -0,0 +1,132 @@
+//! Web Search results
+
+use super::*;
+
+/// Options for `Search::web`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WebOptions {
+    pub common: CommonOptions,
+    /// Answer types to include, e.g. "WebPages,RelatedSearches"
+    pub response_filter: Option<String>,
+}
+
+impl WebOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn market(mut self, market: &str) -> Self {
+        self.common.market = Some(market.to_string());
+        self
+    }
+
+    pub fn safe_search(mut self, safe_search: SafeSearch) -> Self {
+        self.common.safe_search = Some(safe_search);
+        self
+    }
+
+    pub fn count(mut self, count: u32) -> Self {
+        self.common.count = Some(count);
+        self
+    }
+
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.common.offset = Some(offset);
+        self
+    }
+
+    pub fn response_filter(mut self, response_filter: &str) -> Self {
+        self.response_filter = Some(response_filter.to_string());
+        self
+    }
+
+    pub(crate) fn params(&self) -> Vec<(String, String)> {
+        let mut params = self.common.params();
+        if let Some(ref response_filter) = self.response_filter {
+            params.push(("responseFilter".to_string(), response_filter.clone()));
+        }
+        params
+    }
+}
+
+/// Response of `Search::web`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebResponse {
+    #[serde(default)]
+    pub query_context: Option<QueryContext>,
+    #[serde(default)]
+    pub web_pages: Option<WebPages>,
+    #[serde(default)]
+    pub related_searches: Option<RelatedSearches>,
+}
+
+impl WebResponse {
+    /// Web pages found, if any
+    pub fn pages(&self) -> &[WebPage] {
+        match self.web_pages {
+            Some(ref web_pages) => &web_pages.value,
+            None => &[],
+        }
+    }
+
+    /// Related queries, if any
+    pub fn related(&self) -> &[RelatedSearch] {
+        match self.related_searches {
+            Some(ref related_searches) => &related_searches.value,
+            None => &[],
+        }
+    }
+}
+
+/// Web pages answer
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebPages {
+    #[serde(default)]
+    pub web_search_url: Option<String>,
+    #[serde(default)]
+    pub total_estimated_matches: Option<u64>,
+    #[serde(default)]
+    pub value: Vec<WebPage>,
+}
+
+/// Single web page result
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WebPage {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub display_url: Option<String>,
+    #[serde(default)]
+    pub snippet: Option<String>,
+    #[serde(default)]
+    pub date_last_crawled: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub is_family_friendly: Option<bool>,
+}
+
+/// Related searches answer
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedSearches {
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub value: Vec<RelatedSearch>,
+}
+
+/// Single related query
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RelatedSearch {
+    pub text: String,
+    #[serde(default)]
+    pub display_text: Option<String>,
+    #[serde(default)]
+    pub web_search_url: Option<String>,
+}
@@ -0,0 +1,180 @@
+//! Voice assistant pipeline
+//!
+//! `Assistant` ties continuous recognition to text-to-speech: every final
+//! phrase is handed to a responder, and the responder's answer is
+//! synthesized and delivered as audio, or played with the `playback`
+//! feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.fetch_token().unwrap();
+//! let mode = Mode::Interactive(Language::EnglishUnitedStates);
+//! let options = ContinuousOptions::new(mode, Format::Simple);
+//! let mut assistant = speech.assistant(options, voice::en_us::JESSA_RUS, |phrase| {
+//!     phrase.text().map(|text| format!("You said {}", text))
+//! });
+//! let replies = assistant.take_replies().unwrap();
+//!
+//! assistant.start().unwrap();
+//! assistant.push_audio(&[0; 3200]).unwrap();
+//! assistant.stop().unwrap();
+//!
+//! for reply in replies.try_iter() {
+//!     println!("{}: {} bytes of audio", reply.text, reply.audio.len());
+//! }
+//! ```
+
+// std
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+// internal
+use super::*;
+
+/// Synthesized answer to a recognized phrase
+#[derive(Debug, Clone)]
+pub struct Reply {
+    /// Phrase the responder answered
+    pub phrase: Phrase,
+    /// Responder's answer
+    pub text: String,
+    /// `text` synthesized as 16 kHz 16-bit mono PCM
+    pub audio: Vec<u8>,
+}
+
+/// Continuous recognition answering every final phrase with speech
+///
+/// Created with `Speech::assistant`. Session events are handled whenever
+/// audio is pushed and on `poll()`, on the caller's thread, so the
+/// responder doesn't need to be `Send`.
+pub struct Assistant<'a> {
+    speech: &'a Speech,
+    session: RecognitionSession<'a>,
+    events: Receiver<SessionEvent>,
+    font: &'static voice::Font,
+    responder: Box<FnMut(Phrase) -> Option<String> + 'a>,
+    replies_tx: Sender<Reply>,
+    replies_rx: Option<Receiver<Reply>>,
+    play: bool,
+}
+
+impl<'a> Assistant<'a> {
+    fn new<F>(
+        speech: &'a Speech,
+        options: ContinuousOptions,
+        font: &'static voice::Font,
+        responder: F,
+    ) -> Self
+    where
+        F: FnMut(Phrase) -> Option<String> + 'a,
+    {
+        let mut session = speech.recognition_session(options);
+        let events = session.take_events().unwrap();
+        let (replies_tx, replies_rx) = channel();
+        Assistant {
+            speech,
+            session,
+            events,
+            font,
+            responder: Box::new(responder),
+            replies_tx,
+            replies_rx: Some(replies_rx),
+            play: false,
+        }
+    }
+
+    /// Receiver of the synthesized replies
+    ///
+    /// Only the first call returns the receiver.
+    pub fn take_replies(&mut self) -> Option<Receiver<Reply>> {
+        self.replies_rx.take()
+    }
+
+    /// Play replies through the default output device as well, blocking
+    /// until each has finished
+    #[cfg(feature = "playback")]
+    pub fn set_playback(&mut self, play: bool) {
+        self.play = play;
+    }
+
+    /// Connect and start listening
+    pub fn start(&mut self) -> Result<()> {
+        self.session.start()
+    }
+
+    /// Send 16 kHz 16-bit mono PCM audio, then answer the phrases
+    /// recognized so far
+    pub fn push_audio(&mut self, audio: &[u8]) -> Result<()> {
+        self.session.push_audio(audio)?;
+        self.poll()
+    }
+
+    /// Answer the phrases recognized so far
+    pub fn poll(&mut self) -> Result<()> {
+        while let Ok(event) = self.events.try_recv() {
+            if let SessionEvent::Phrase(phrase) = event {
+                self.respond(phrase)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop listening and answer the final phrase
+    pub fn stop(&mut self) -> Result<()> {
+        self.session.stop()?;
+        self.poll()
+    }
+
+    fn respond(&mut self, phrase: Phrase) -> Result<()> {
+        if !phrase.is_success() {
+            return Ok(());
+        }
+        let text = match (self.responder)(phrase.clone()) {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+
+        let audio = match self.speech.synthesize(&text, self.font)? {
+            (_, _, Some(audio)) => audio,
+            (_, status, None) => bail!("no audio synthesized ({})", status),
+        };
+        if self.play {
+            self.play_reply(&audio)?;
+        }
+        let _ = self.replies_tx.send(Reply {
+            phrase,
+            text,
+            audio,
+        });
+        Ok(())
+    }
+
+    #[cfg(feature = "playback")]
+    fn play_reply(&self, audio: &[u8]) -> Result<()> {
+        ::playback::play_pcm16(audio, audio::SAMPLE_RATE)
+    }
+
+    #[cfg(not(feature = "playback"))]
+    fn play_reply(&self, _audio: &[u8]) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl Speech {
+    /// Start building a voice assistant answering phrases with `responder`
+    /// in the voice of `font`
+    pub fn assistant<'a, F>(
+        &'a self,
+        options: ContinuousOptions,
+        font: &'static voice::Font,
+        responder: F,
+    ) -> Assistant<'a>
+    where
+        F: FnMut(Phrase) -> Option<String> + 'a,
+    {
+        Assistant::new(self, options, font, responder)
+    }
+}
@@ -0,0 +1,289 @@
+//! Non-blocking variants of the REST calls
+//!
+//! The `*_async` methods return futures instead of blocking on an internal
+//! tokio Core, so they can be driven by the caller's own runtime and run
+//! concurrently. Requests go straight through hyper: they bypass any
+//! transport set with `Speech::set_transport`, and neither the timeout nor
+//! the retry policy apply to them.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! extern crate bing_rs;
+//! extern crate futures;
+//! extern crate tokio_core;
+//!
+//! use bing_rs::speech::*;
+//! use futures::Future;
+//! use tokio_core::reactor::Core;
+//!
+//! # fn main() {
+//! let mut core = Core::new().unwrap();
+//! let speech = Speech::new("your_subscription_key").unwrap();
+//! let mode = Mode::Interactive(Language::EnglishUnitedStates);
+//!
+//! core.run(speech.fetch_token_async()).unwrap();
+//!
+//! let first = speech.recognize_async(vec![0; 32000], mode, Format::Simple);
+//! let second = speech.recognize_async(vec![0; 32000], mode, Format::Simple);
+//! let ((_, _, a), (_, _, b)) = core.run(first.join(second)).unwrap();
+//! # }
+//! ```
+
+use futures::future;
+
+use super::*;
+use client;
+
+/// Future returned by the non-blocking `Speech` methods
+pub type SpeechFuture<T> = Box<Future<Item = T, Error = Error> + Send>;
+
+impl Speech {
+    /// Non-blocking `fetch_token`
+    ///
+    /// The issued token is stored in `self.token` when the future resolves.
+    pub fn fetch_token_async(&self) -> SpeechFuture<(HeaderMap, StatusCode, Option<String>)> {
+        let request = match self.token_request() {
+            Ok(request) => request,
+            Err(err) => return Box::new(future::err(err)),
+        };
+
+        let token = self.token.clone();
+        Box::new(
+            self.execute_async(RequestKind::Token, request, 0)
+                .and_then(move |(header, status, body)| {
+                    check_response(&header, status, &body)?;
+                    if body.is_empty() {
+                        return Ok((header, status, None));
+                    }
+                    let body = String::from_utf8(body)?;
+                    *token.lock().unwrap() = body.clone();
+                    Ok((header, status, Some(body)))
+                }),
+        )
+    }
+
+    /// Non-blocking `recognize`
+    ///
+    /// Recognitions started this way run concurrently when their futures
+    /// are polled together, e.g. with `Future::join` or `join_all`. Fetch a
+    /// token first or set a token provider, whose stale token is then
+    /// refreshed with `fetch_token_async` before the request is sent.
+    pub fn recognize_async(
+        &self,
+        audio: Vec<u8>,
+        mode: Mode,
+        format: Format,
+    ) -> SpeechFuture<(HeaderMap, StatusCode, Option<Phrase>)> {
+        let audio_len = audio.len();
+        if let Some(ref budget) = self.budget {
+            if let Err(err) = budget.charge_audio(audio::duration(&audio)) {
+                return Box::new(future::err(err));
+            }
+        }
+        let request = match self.recognize_request(audio.into(), mode, format, self.params) {
+            Ok(request) => request,
+            Err(err) => return Box::new(future::err(err)),
+        };
+
+        let min_confidence = self.min_confidence;
+        Box::new(
+            self.execute_async(RequestKind::Recognize, request, audio_len)
+                .and_then(move |(header, status, body)| {
+                    check_response(&header, status, &body)?;
+                    Ok((header, status, phrase_from_body(&body, min_confidence)?))
+                }),
+        )
+    }
+
+    /// Non-blocking `synthesize`
+    pub fn synthesize_async(
+        &self,
+        text: &str,
+        font: &voice::Font,
+    ) -> SpeechFuture<(HeaderMap, StatusCode, Option<Vec<u8>>)> {
+        self.synthesize_async_with_format(text, font, OutputFormat::default())
+    }
+
+    /// Non-blocking `synthesize_with_format`
+    pub fn synthesize_async_with_format(
+        &self,
+        text: &str,
+        font: &voice::Font,
+        format: OutputFormat,
+    ) -> SpeechFuture<(HeaderMap, StatusCode, Option<Vec<u8>>)> {
+        if let Some(ref budget) = self.budget {
+            if let Err(err) = budget.charge_characters(text.chars().count() as u64) {
+                return Box::new(future::err(err));
+            }
+        }
+        let data = synthesis_ssml(text, font);
+        let data_len = data.len();
+        let request = match self.synthesize_request(data, format) {
+            Ok(request) => request,
+            Err(err) => return Box::new(future::err(err)),
+        };
+
+        Box::new(
+            self.execute_async(RequestKind::Synthesize, request, data_len)
+                .and_then(|(header, status, body)| {
+                    check_response(&header, status, &body)?;
+                    if body.is_empty() {
+                        Ok((header, status, None))
+                    } else {
+                        Ok((header, status, Some(body)))
+                    }
+                }),
+        )
+    }
+
+    /// Future resolving once the token provider, if any, has a fresh token
+    ///
+    /// A stale token is replaced through `fetch_token_async` rather than
+    /// the provider, which would block.
+    fn ensure_token_async(&self) -> SpeechFuture<()> {
+        let provider = match self.token_provider {
+            Some(ref provider) if provider.is_stale() => provider.clone(),
+            _ => return Box::new(future::ok(())),
+        };
+        Box::new(
+            self.fetch_token_async()
+                .and_then(move |(_, status, token)| -> Result<()> {
+                    match token {
+                        Some(token) => {
+                            provider.set_token(&token);
+                            Ok(())
+                        }
+                        None => bail!("no token issued ({})", status),
+                    }
+                }),
+        )
+    }
+
+    /// Send a request on the shared hyper client and report metrics when it
+    /// completes
+    ///
+    /// Requests other than token requests wait for `ensure_token_async` and
+    /// are authorized with the token it leaves.
+    fn execute_async(
+        &self,
+        kind: RequestKind,
+        mut request: Request<Body>,
+        body_len: usize,
+    ) -> SpeechFuture<(HeaderMap, StatusCode, Vec<u8>)> {
+        let client = {
+            let mut async_client = self.async_client.lock().unwrap();
+            if async_client.is_none() {
+                match client::https_client() {
+                    Ok(client) => *async_client = Some(client),
+                    Err(err) => return Box::new(future::err(err)),
+                }
+            }
+            async_client.as_ref().unwrap().clone()
+        };
+        let request_id = request_id(&mut request);
+        self.trace
+            .on_request(&request_id, request.method(), request.uri());
+        self.metrics.request_started(kind);
+        if let Some(ref correlation_id) = self.correlation_id {
+            self.metrics.request_correlated(kind, correlation_id);
+        }
+        self.metrics.bytes_sent(kind, body_len);
+        let debug_logging = self.debug_logging;
+        if debug_logging {
+            debug!(
+                "[{}] {:?} request: {} {} [{}] ({} bytes)",
+                self.correlation_id().unwrap_or("-"),
+                kind,
+                request.method(),
+                request.uri(),
+                redact::headers(request.headers()),
+                body_len
+            );
+        }
+
+        let metrics = self.metrics.clone();
+        let trace = self.trace.clone();
+        let stats = self.stats.clone();
+        let correlation_id = self.correlation_id.clone();
+        let token = self.token.clone();
+        let ready: SpeechFuture<()> = if kind == RequestKind::Token {
+            Box::new(future::ok(()))
+        } else {
+            self.ensure_token_async()
+        };
+        let start = Instant::now();
+
+        let work = ready
+            .and_then(move |()| {
+                if kind != RequestKind::Token {
+                    let bearer = format!("Bearer {}", token.lock().unwrap());
+                    if let Ok(value) = bearer.parse::<HeaderValue>() {
+                        request.headers_mut().insert("Authorization", value);
+                    }
+                }
+                client
+                    .request(request)
+                    .and_then(|res| {
+                        let header = res.headers().clone();
+                        let status = res.status();
+                        res.into_body()
+                            .concat2()
+                            .map(move |chunks| (header, status, chunks.to_vec()))
+                    }).map_err(Error::from)
+            }).then(move |result| -> Result<(HeaderMap, StatusCode, Vec<u8>)> {
+                if debug_logging {
+                    let correlation_id = correlation_id.as_ref().map_or("-", |id| id.as_str());
+                    match result {
+                        Ok((ref header, status, ref body)) => debug!(
+                            "[{}] {:?} response: {} [{}] {}",
+                            correlation_id,
+                            kind,
+                            status,
+                            redact::headers(header),
+                            redact::body(body, kind == RequestKind::Token)
+                        ),
+                        Err(ref err) => debug!("[{}] {:?} failed: {}", correlation_id, kind, err),
+                    };
+                }
+                match result {
+                    Ok((ref header, status, _)) => {
+                        let metric = match kind {
+                            RequestKind::Token => Some(Metric::TokenFetch),
+                            RequestKind::Recognize => Some(Metric::Recognition),
+                            _ => None,
+                        };
+                        if let Some(metric) = metric {
+                            stats.record(metric, start.elapsed());
+                        }
+                        if let Err(err) = client::check_status(header, status) {
+                            metrics.error(kind, ErrorClass::from(&err));
+                        }
+                        metrics.request_completed(kind, Some(status.as_u16()), start.elapsed());
+                    }
+                    Err(ref err) => {
+                        metrics.error(kind, ErrorClass::from(err));
+                        metrics.request_completed(kind, None, start.elapsed());
+                    }
+                };
+                trace.on_response(
+                    &request_id,
+                    result.as_ref().ok().map(|&(_, status, _)| status.as_u16()),
+                    start.elapsed(),
+                );
+
+                let (mut header, status, body) = result?;
+                if let Ok(value) = request_id.parse::<HeaderValue>() {
+                    header.insert(REQUEST_ID_HEADER, value);
+                }
+                if let Some(correlation_id) = correlation_id {
+                    if let Ok(value) = correlation_id.parse::<HeaderValue>() {
+                        header.insert(CORRELATION_ID_HEADER, value);
+                    }
+                }
+                Ok((header, status, body))
+            });
+
+        Box::new(work)
+    }
+}
@@ -0,0 +1,132 @@
+//! Concurrent recognition of many short clips
+//!
+//! `BatchRecognizer` sends up to `concurrency` REST recognitions at a time
+//! from worker threads sharing the client's transport, so connections are
+//! kept alive and reused between clips instead of every request waiting for
+//! the previous one.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.fetch_token().unwrap();
+//! let mode = Mode::Interactive(Language::EnglishUnitedStates);
+//! let mut batch = speech.batch_recognizer(mode, Format::Simple);
+//! batch.set_concurrency(8);
+//!
+//! let clips = vec![vec![0; 32000], vec![0; 32000]];
+//! batch
+//!     .run(clips, |index, result| match result {
+//!         Ok(Some(phrase)) => println!("{}: {}", index, phrase.text().unwrap_or("")),
+//!         Ok(None) => println!("{}: no phrase", index),
+//!         Err(err) => println!("{}: {}", index, err),
+//!     })
+//!     .unwrap();
+//! ```
+
+// std
+use std::sync::mpsc::channel;
+
+// crossbeam
+use crossbeam_utils::thread as scoped;
+
+use super::*;
+
+/// Concurrency used unless `set_concurrency` is called
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Recognizes a list of audio buffers with a bounded number of concurrent
+/// requests
+///
+/// Created with `Speech::batch_recognizer`. Every clip is sent like
+/// `Speech::recognize`, so the budget, retry policy, timeout and statistics
+/// of the client apply.
+pub struct BatchRecognizer<'a> {
+    speech: &'a Speech,
+    mode: Mode,
+    format: Format,
+    concurrency: usize,
+}
+
+impl<'a> BatchRecognizer<'a> {
+    fn new(speech: &'a Speech, mode: Mode, format: Format) -> Self {
+        BatchRecognizer {
+            speech,
+            mode,
+            format,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Send at most `concurrency` requests at a time
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.concurrency = concurrency.max(1);
+    }
+
+    /// Recognize every clip, calling `callback` with the clip's index and
+    /// result as soon as each one completes
+    ///
+    /// Results arrive in completion order, on the calling thread. A failed
+    /// clip doesn't stop the others; only a failure to fetch the token is
+    /// returned.
+    pub fn run<F>(&self, clips: Vec<Vec<u8>>, mut callback: F) -> Result<()>
+    where
+        F: FnMut(usize, Result<Option<Phrase>>),
+    {
+        self.speech.ensure_token()?;
+
+        let speech = self.speech;
+        let (mode, format) = (self.mode, self.format);
+        let workers = self.concurrency.min(clips.len());
+        let clips = Mutex::new(clips.into_iter().enumerate());
+        let (results_tx, results_rx) = channel();
+        let finished = scoped::scope(|scope| {
+            for _ in 0..workers {
+                let clips = &clips;
+                let results_tx = results_tx.clone();
+                scope.spawn(move |_| loop {
+                    let next = clips.lock().unwrap().next();
+                    let (index, audio) = match next {
+                        Some(clip) => clip,
+                        None => break,
+                    };
+                    let result = speech
+                        .recognize(audio, mode, format)
+                        .map(|(_, _, phrase)| phrase);
+                    if results_tx.send((index, result)).is_err() {
+                        break;
+                    }
+                });
+            }
+            drop(results_tx);
+
+            for (index, result) in results_rx {
+                callback(index, result);
+            }
+        });
+        if finished.is_err() {
+            bail!("a batch recognition thread panicked");
+        }
+        Ok(())
+    }
+
+    /// Recognize every clip and return the results in the order of `clips`
+    pub fn recognize_all(&self, clips: Vec<Vec<u8>>) -> Result<Vec<Result<Option<Phrase>>>> {
+        let mut results: Vec<Option<Result<Option<Phrase>>>> =
+            (0..clips.len()).map(|_| None).collect();
+        self.run(clips, |index, result| results[index] = Some(result))?;
+        Ok(results
+            .into_iter()
+            .map(|result| result.unwrap_or(Ok(None)))
+            .collect())
+    }
+}
+
+impl Speech {
+    /// Recognizer for many clips in `mode` and `format`, sent concurrently
+    pub fn batch_recognizer(&self, mode: Mode, format: Format) -> BatchRecognizer {
+        BatchRecognizer::new(self, mode, format)
+    }
+}
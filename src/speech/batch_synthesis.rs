@@ -0,0 +1,87 @@
+//! Synthesis of several texts in a row
+//!
+//! The texts are sent one after the other with the same token and over the
+//! transport's kept-alive connection, so prompts made of many sentences,
+//! e.g. for IVR menus, don't pay for a new connection per sentence.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.fetch_token().unwrap();
+//! let items = [
+//!     ("Welcome to the help line.", voice::en_us::JESSA_RUS),
+//!     ("Please hold.", voice::en_us::BENJAMIN_RUS),
+//! ];
+//! let audio = speech
+//!     .synthesize_batch(&items, Duration::from_millis(500))
+//!     .unwrap();
+//! ```
+
+use super::*;
+use audio::Spec;
+
+impl Speech {
+    /// Synthesize every `(text, font)` item into one raw 16kHz 16-bit mono
+    /// PCM buffer, with `silence` between consecutive items
+    pub fn synthesize_batch(
+        &self,
+        items: &[(&str, &voice::Font)],
+        silence: Duration,
+    ) -> Result<Vec<u8>> {
+        self.synthesize_batch_with_format(items, OutputFormat::default(), silence)
+    }
+
+    /// Like `synthesize_batch` with audio encoded as `format`
+    ///
+    /// Only raw PCM formats can be joined. Others fail with
+    /// `ErrorKind::InvalidRequest`, use `synthesize_each` for them.
+    pub fn synthesize_batch_with_format(
+        &self,
+        items: &[(&str, &voice::Font)],
+        format: OutputFormat,
+        silence: Duration,
+    ) -> Result<Vec<u8>> {
+        if !format.is_raw_pcm() {
+            bail!(ErrorKind::InvalidRequest(format!(
+                "can't join audio in {}; use a raw PCM format",
+                format.name()
+            )));
+        }
+
+        let gap = vec![0; Spec::new(format.sample_rate(), 1, 16).bytes_for(silence)];
+        let mut joined = Vec::new();
+        for (index, audio) in self.synthesize_each(items, format)?.into_iter().enumerate() {
+            if index > 0 {
+                joined.extend_from_slice(&gap);
+            }
+            joined.extend(audio);
+        }
+        Ok(joined)
+    }
+
+    /// Synthesize every `(text, font)` item into its own buffer encoded as
+    /// `format`, in the order of `items`
+    ///
+    /// Stops at the first failed item.
+    pub fn synthesize_each(
+        &self,
+        items: &[(&str, &voice::Font)],
+        format: OutputFormat,
+    ) -> Result<Vec<Vec<u8>>> {
+        self.ensure_token()?;
+
+        let mut buffers = Vec::with_capacity(items.len());
+        for (index, &(text, font)) in items.iter().enumerate() {
+            let (_, status, audio) = self.synthesize_with_format(text, font, format)?;
+            match audio {
+                Some(audio) => buffers.push(audio),
+                None => bail!("no audio returned for item {} ({})", index, status),
+            }
+        }
+        Ok(buffers)
+    }
+}
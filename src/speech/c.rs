@@ -1,16 +1,37 @@
-use std::ffi::CString;
+//! C API
+//!
+//! # Ownership
+//!
+//! Strings and buffers passed in by the caller are only borrowed for the
+//! duration of the call and copied if they need to outlive it. Strings,
+//! arrays and audio buffers handed out by the library are allocated by Rust
+//! and must be released with the matching `bing_speech_*_free` function,
+//! never with `free()`:
+//!
+//! - `bing_speech_phrase_free` for phrases filled in by
+//!   `bing_speech_recognize` or passed to `on_speech_phrase`
+//! - `bing_speech_hypothesis_free` for hypotheses passed to
+//!   `on_speech_hypothesis`
+//! - `bing_speech_buffer_free` for synthesized audio
+//! - `bing_speech_string_free` for the token returned by
+//!   `bing_speech_fetch_token`
+
+use std::ffi::{CStr, CString};
 use std::marker::Send;
 use std::mem;
 use std::os::raw::{c_char, c_double, c_int, c_void};
 use std::ptr;
+use std::slice;
 use std::sync::{Arc, Mutex};
 
+use audio::frame::{FrameReader, DEFAULT_FRAME_SIZE};
 use speech::websocket::*;
 use speech::*;
 
 #[no_mangle]
 pub struct BingSpeech {
     handle: Speech,
+    refresher: Option<TokenRefresher>,
 }
 
 #[no_mangle]
@@ -29,6 +50,18 @@ pub struct BingSpeechWebsocketHandler {
     on_speech_phrase: fn(BingSpeechPhrase),
 }
 
+#[no_mangle]
+#[repr(C)]
+pub struct BingSpeechWord {
+    pub word: *mut c_char,
+    pub offset: c_double,
+    pub duration: c_double,
+    /// `offset` in milliseconds
+    pub offset_ms: c_double,
+    /// `duration` in milliseconds
+    pub duration_ms: c_double,
+}
+
 #[no_mangle]
 #[repr(C)]
 pub struct BingSpeechResult {
@@ -37,6 +70,8 @@ pub struct BingSpeechResult {
     pub itn: *mut c_char,
     pub masked_itn: *mut c_char,
     pub display: *mut c_char,
+    pub words: *mut BingSpeechWord,
+    pub words_count: c_int,
 }
 
 #[no_mangle]
@@ -48,6 +83,12 @@ pub struct BingSpeechPhrase {
     duration: c_double,
     nbest: *mut BingSpeechResult,
     nbest_count: c_int,
+    /// `offset` in milliseconds
+    offset_ms: c_double,
+    /// `duration` in milliseconds
+    duration_ms: c_double,
+    /// Speaker identified by conversation transcription, or null
+    speaker_id: *mut c_char,
 }
 
 #[no_mangle]
@@ -56,6 +97,10 @@ pub struct BingSpeechHypothesis {
     text: *mut c_char,
     offset: c_double,
     duration: c_double,
+    /// `offset` in milliseconds
+    offset_ms: c_double,
+    /// `duration` in milliseconds
+    duration_ms: c_double,
 }
 
 unsafe impl Send for BingSpeechWebsocketHandler {}
@@ -69,17 +114,172 @@ pub struct BingSpeechHandler {
 fn nbest_to_c(nbest: &[DetailedPhraseItem]) -> Vec<BingSpeechResult> {
     nbest
         .iter()
-        .map(|result| BingSpeechResult {
-            confidence: result.confidence,
-            lexical: to_c_string(&result.lexical),
-            itn: to_c_string(&result.itn),
-            masked_itn: to_c_string(&result.masked_itn),
-            display: to_c_string(&result.display),
+        .map(|result| {
+            let (words, words_count) = into_c_array(words_to_c(&result.words));
+            BingSpeechResult {
+                confidence: result.confidence,
+                lexical: to_c_string(&result.lexical),
+                itn: to_c_string(&result.itn),
+                masked_itn: to_c_string(&result.masked_itn),
+                display: to_c_string(&result.display),
+                words,
+                words_count,
+            }
+        }).collect()
+}
+
+fn words_to_c(words: &[WordTiming]) -> Vec<BingSpeechWord> {
+    words
+        .iter()
+        .map(|word| BingSpeechWord {
+            word: to_c_string(&word.word),
+            offset: word.offset,
+            duration: word.duration,
+            offset_ms: ticks::to_millis(word.offset),
+            duration_ms: ticks::to_millis(word.duration),
         }).collect()
 }
 
+fn phrase_to_c(phrase: Phrase) -> BingSpeechPhrase {
+    let speaker_id = match phrase.speaker_id() {
+        Some(speaker_id) => to_c_string(speaker_id),
+        None => ptr::null_mut(),
+    };
+    match phrase {
+        Phrase::Simple(simple) => BingSpeechPhrase {
+            recognition_status: to_c_string(&simple.recognition_status),
+            display_text: to_c_string(&simple.display_text),
+            offset: simple.offset,
+            duration: simple.duration,
+            offset_ms: ticks::to_millis(simple.offset),
+            duration_ms: ticks::to_millis(simple.duration),
+            nbest: ptr::null_mut(),
+            nbest_count: 0,
+            speaker_id,
+        },
+        Phrase::Detailed(detailed) => {
+            let (nbest, nbest_count) = into_c_array(nbest_to_c(&detailed.nbest));
+            BingSpeechPhrase {
+                recognition_status: to_c_string(&detailed.recognition_status),
+                display_text: ptr::null_mut(),
+                offset: detailed.offset,
+                duration: detailed.duration,
+                offset_ms: ticks::to_millis(detailed.offset),
+                duration_ms: ticks::to_millis(detailed.duration),
+                nbest,
+                nbest_count,
+                speaker_id,
+            }
+        }
+        Phrase::LowConfidence(detailed) => {
+            let (nbest, nbest_count) = into_c_array(nbest_to_c(&detailed.nbest));
+            BingSpeechPhrase {
+                recognition_status: to_c_string("LowConfidence"),
+                display_text: ptr::null_mut(),
+                offset: detailed.offset,
+                duration: detailed.duration,
+                offset_ms: ticks::to_millis(detailed.offset),
+                duration_ms: ticks::to_millis(detailed.duration),
+                nbest,
+                nbest_count,
+                speaker_id,
+            }
+        }
+        Phrase::Silence(silence) => BingSpeechPhrase {
+            recognition_status: to_c_string(&silence.recognition_status),
+            display_text: ptr::null_mut(),
+            offset: silence.offset,
+            duration: silence.duration,
+            offset_ms: ticks::to_millis(silence.offset),
+            duration_ms: ticks::to_millis(silence.duration),
+            nbest: ptr::null_mut(),
+            nbest_count: 0,
+            speaker_id,
+        },
+        Phrase::Unknown => BingSpeechPhrase {
+            recognition_status: to_c_string("Unknown"),
+            display_text: ptr::null_mut(),
+            offset: 0.0,
+            duration: 0.0,
+            offset_ms: 0.0,
+            duration_ms: 0.0,
+            nbest: ptr::null_mut(),
+            nbest_count: 0,
+            speaker_id,
+        },
+    }
+}
+
+/// Copy `s` into a string owned by Rust, freed with `free_c_string`
+///
+/// Interior NUL bytes are dropped rather than failing.
 fn to_c_string(s: &str) -> *mut c_char {
-    CString::new(s).unwrap().into_raw()
+    let bytes: Vec<u8> = s.bytes().filter(|&b| b != 0).collect();
+    CString::new(bytes).unwrap().into_raw()
+}
+
+/// Copy the caller's NUL-terminated string, or `None` if it's null or not
+/// UTF-8
+unsafe fn from_c_string(s: *const c_char) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok().map(String::from)
+}
+
+unsafe fn free_c_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Hand `items` over to C as a pointer and count, freed with `from_c_array`
+fn into_c_array<T>(items: Vec<T>) -> (*mut T, c_int) {
+    if items.is_empty() {
+        return (ptr::null_mut(), 0);
+    }
+    let len = items.len() as c_int;
+    (Box::into_raw(items.into_boxed_slice()) as *mut T, len)
+}
+
+/// Take back an array handed out by `into_c_array`
+unsafe fn from_c_array<T>(items: *mut T, len: c_int) -> Vec<T> {
+    if items.is_null() || len <= 0 {
+        return Vec::new();
+    }
+    Box::from_raw(slice::from_raw_parts_mut(items, len as usize)).into_vec()
+}
+
+/// Copy the caller's buffer, treating null as empty
+unsafe fn copy_c_buffer(buffer: *const u8, len: usize) -> Vec<u8> {
+    if buffer.is_null() || len == 0 {
+        return Vec::new();
+    }
+    slice::from_raw_parts(buffer, len).to_vec()
+}
+
+/// Hand synthesized audio over to C, or null on failure
+///
+/// Returns 0 if there was audio and 1 otherwise.
+unsafe fn write_c_buffer(
+    result: Result<(HeaderMap, StatusCode, Option<Vec<u8>>)>,
+    c_output: *mut *mut c_void,
+    c_output_len: *mut c_int,
+) -> c_int {
+    let (data, len, ret) = match result {
+        Ok((_, _, Some(data))) => {
+            let (data, len) = into_c_array(data);
+            (data, len, 0)
+        }
+        Ok(_) => (ptr::null_mut(), 0, 1),
+        Err(err) => {
+            error!("{}", err);
+            (ptr::null_mut(), 0, 1)
+        }
+    };
+    *c_output = data as *mut c_void;
+    *c_output_len = len;
+    ret
 }
 
 impl Handler for BingSpeechHandler {
@@ -115,6 +315,8 @@ impl Handler for BingSpeechHandler {
             text: to_c_string(&hypothesis.text),
             offset: hypothesis.offset,
             duration: hypothesis.duration,
+            offset_ms: ticks::to_millis(hypothesis.offset),
+            duration_ms: ticks::to_millis(hypothesis.duration),
         });
     }
 
@@ -122,86 +324,184 @@ impl Handler for BingSpeechHandler {
         let handler = self.c_handler.lock().unwrap();
         let f: extern "C" fn(BingSpeechPhrase) =
             unsafe { mem::transmute(handler.on_speech_phrase) };
-        let phrase = match phrase {
-            Phrase::Simple(simple) => BingSpeechPhrase {
-                recognition_status: to_c_string(&simple.recognition_status),
-                display_text: to_c_string(&simple.display_text),
-                offset: simple.offset,
-                duration: simple.duration,
-                nbest: ptr::null_mut(),
-                nbest_count: 0,
-            },
-            Phrase::Detailed(detailed) => {
-                let nbest_count = detailed.nbest.len() as i32;
-                let mut nbest = nbest_to_c(&detailed.nbest);
-                let phrase = BingSpeechPhrase {
-                    recognition_status: to_c_string(&detailed.recognition_status),
-                    display_text: ptr::null_mut(),
-                    offset: detailed.offset,
-                    duration: detailed.duration,
-                    nbest: nbest.as_mut_ptr(),
-                    nbest_count,
-                };
-                mem::forget(nbest);
-                phrase
-            }
-            Phrase::Silence(silence) => BingSpeechPhrase {
-                recognition_status: to_c_string(&silence.recognition_status),
-                display_text: ptr::null_mut(),
-                offset: silence.offset,
-                duration: silence.duration,
-                nbest: ptr::null_mut(),
-                nbest_count: 0,
-            },
-            Phrase::Unknown => BingSpeechPhrase {
-                recognition_status: to_c_string("Unknown"),
-                display_text: ptr::null_mut(),
-                offset: 0.0,
-                duration: 0.0,
-                nbest: ptr::null_mut(),
-                nbest_count: 0,
-            },
-        };
-        f(phrase);
+        f(phrase_to_c(phrase));
+    }
+}
+
+/// Create a handle, or return null on failure
+///
+/// `subscription_key` is copied.
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_new(subscription_key: *const c_char) -> *mut BingSpeech {
+    let subscription_key = match from_c_string(subscription_key) {
+        Some(subscription_key) => subscription_key,
+        None => return ptr::null_mut(),
+    };
+    match Speech::new(&subscription_key) {
+        Ok(handle) => Box::into_raw(Box::new(BingSpeech {
+            handle,
+            refresher: None,
+        })),
+        Err(err) => {
+            error!("{}", err);
+            ptr::null_mut()
+        }
     }
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bing_speech_new(subscription_key: *mut c_char) -> *mut BingSpeech {
-    let subscription_key = CString::from_raw(subscription_key).into_string().unwrap();
-    let bing_speech = Box::new(BingSpeech {
-        handle: Speech::new(&subscription_key).unwrap(),
-    });
+pub unsafe extern "C" fn bing_speech_free(bing_speech: *mut BingSpeech) {
+    if !bing_speech.is_null() {
+        drop(Box::from_raw(bing_speech));
+    }
+}
 
-    mem::forget(subscription_key);
-    Box::into_raw(bing_speech)
+/// Release a string returned by the library
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_string_free(s: *mut c_char) {
+    free_c_string(s);
 }
 
+/// Release an audio buffer returned by the library
 #[no_mangle]
-pub unsafe extern "C" fn bing_speech_free(bing_speech: *mut BingSpeech) {
-    Box::from_raw(bing_speech);
+pub unsafe extern "C" fn bing_speech_buffer_free(buffer: *mut c_void, len: c_int) {
+    drop(from_c_array(buffer as *mut u8, len));
+}
+
+/// Release the strings and words of `result`; the struct itself is left
+/// to the caller
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_result_free(result: *mut BingSpeechResult) {
+    if result.is_null() {
+        return;
+    }
+    let result = &mut *result;
+    free_c_string(result.lexical);
+    free_c_string(result.itn);
+    free_c_string(result.masked_itn);
+    free_c_string(result.display);
+    for word in from_c_array(result.words, result.words_count) {
+        free_c_string(word.word);
+    }
+    result.lexical = ptr::null_mut();
+    result.itn = ptr::null_mut();
+    result.masked_itn = ptr::null_mut();
+    result.display = ptr::null_mut();
+    result.words = ptr::null_mut();
+    result.words_count = 0;
+}
+
+/// Release the strings and N-best results of `phrase`; the struct itself
+/// is left to the caller
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_phrase_free(phrase: *mut BingSpeechPhrase) {
+    if phrase.is_null() {
+        return;
+    }
+    let phrase = &mut *phrase;
+    free_c_string(phrase.recognition_status);
+    free_c_string(phrase.display_text);
+    free_c_string(phrase.speaker_id);
+    for mut result in from_c_array(phrase.nbest, phrase.nbest_count) {
+        bing_speech_result_free(&mut result);
+    }
+    phrase.recognition_status = ptr::null_mut();
+    phrase.display_text = ptr::null_mut();
+    phrase.speaker_id = ptr::null_mut();
+    phrase.nbest = ptr::null_mut();
+    phrase.nbest_count = 0;
+}
+
+/// Release the text of `hypothesis`; the struct itself is left to the
+/// caller
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_hypothesis_free(hypothesis: *mut BingSpeechHypothesis) {
+    if hypothesis.is_null() {
+        return;
+    }
+    free_c_string((*hypothesis).text);
+    (*hypothesis).text = ptr::null_mut();
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn bing_speech_set_custom_speech(
     bing_speech: *mut BingSpeech,
     is_custom_speech: c_int,
+) {
+    (*bing_speech).handle.is_custom_speech = is_custom_speech > 0;
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_set_endpoint_id(
+    bing_speech: *mut BingSpeech,
+    endpoint_id: *const c_char,
+) {
+    if let Some(endpoint_id) = from_c_string(endpoint_id) {
+        (*bing_speech).handle.endpoint_id = endpoint_id;
+    }
+}
+
+/// Send custom speech recognitions to the host of `c_url`, returning 0 on
+/// success
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_set_custom_speech_endpoint(
+    bing_speech: *mut BingSpeech,
+    c_url: *const c_char,
+) -> c_int {
+    let url = match from_c_string(c_url) {
+        Some(url) => url,
+        None => return 1,
+    };
+    match (*bing_speech).handle.set_custom_speech_endpoint(&url) {
+        Ok(()) => 0,
+        Err(err) => {
+            error!("{}", err);
+            1
+        }
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_set_profanity(
+    bing_speech: *mut BingSpeech,
+    c_profanity: c_int,
 ) {
     (*bing_speech)
         .handle
-        .set_custom_speech(is_custom_speech > 0);
+        .set_profanity(profanity_from_c(c_profanity));
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn bing_speech_set_endpoint_id(
+pub unsafe extern "C" fn bing_speech_set_word_level_timestamps(
+    bing_speech: *mut BingSpeech,
+    c_enabled: c_int,
+) {
+    (*bing_speech)
+        .handle
+        .set_word_level_timestamps(c_enabled > 0);
+}
+
+/// Drop detailed alternatives below `c_min_confidence`, or stop filtering
+/// with a negative value
+///
+/// Results without remaining alternatives have the recognition status
+/// "LowConfidence".
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_set_min_confidence(
     bing_speech: *mut BingSpeech,
-    endpoint_id: *mut c_char,
+    c_min_confidence: c_double,
 ) {
-    let endpoint_id = CString::from_raw(endpoint_id).into_string().unwrap();
-    (*bing_speech).handle.set_endpoint_id(&endpoint_id);
-    mem::forget(endpoint_id);
+    let min_confidence = if c_min_confidence < 0.0 {
+        None
+    } else {
+        Some(c_min_confidence)
+    };
+    (*bing_speech).handle.set_min_confidence(min_confidence);
 }
 
+/// Fetch a token, returning it or null on failure
+///
+/// Release the token with `bing_speech_string_free`.
 #[no_mangle]
 pub unsafe extern "C" fn bing_speech_fetch_token(bing_speech: *mut BingSpeech) -> *mut c_char {
     let result = (*bing_speech).handle.fetch_token();
@@ -211,11 +511,16 @@ pub unsafe extern "C" fn bing_speech_fetch_token(bing_speech: *mut BingSpeech) -
     }
 }
 
+/// Refresh the token in the background until `bing_speech_free`
 #[no_mangle]
 pub unsafe extern "C" fn bing_speech_auto_fetch_token(bing_speech: *mut BingSpeech) {
-    (*bing_speech).handle.auto_fetch_token();
+    let refresher = (*bing_speech).handle.auto_fetch_token();
+    (*bing_speech).refresher = Some(refresher);
 }
 
+/// Recognize `c_audio`, filling in `c_phrase` and returning 0 on success
+///
+/// The audio is copied. Release the phrase with `bing_speech_phrase_free`.
 #[no_mangle]
 pub unsafe extern "C" fn bing_speech_recognize(
     bing_speech: *mut BingSpeech,
@@ -226,11 +531,6 @@ pub unsafe extern "C" fn bing_speech_recognize(
     c_format: c_int,
     c_phrase: *mut BingSpeechPhrase,
 ) -> c_int {
-    let audio: Vec<u8> = Vec::from_raw_parts(
-        c_audio as *mut u8,
-        c_audio_len as usize,
-        c_audio_len as usize,
-    );
     let (mode, ok) = mode_from_c(c_mode, c_language);
     if ok != 0 {
         return ok;
@@ -242,72 +542,82 @@ pub unsafe extern "C" fn bing_speech_recognize(
         Format::Simple
     };
 
-    let audio_1 = audio.clone();
-    mem::forget(audio);
-
-    if let Ok((_, _, Some(phrase))) = (*bing_speech).handle.recognize(audio_1, &mode, &format) {
-        match phrase {
-            Phrase::Simple(simple) => {
-                (*c_phrase).recognition_status = to_c_string(&simple.recognition_status);
-                (*c_phrase).display_text = to_c_string(&simple.display_text);
-                (*c_phrase).offset = simple.offset;
-                (*c_phrase).duration = simple.duration;
-                (*c_phrase).nbest = ptr::null_mut();
-                (*c_phrase).nbest_count = 0;
-            }
-            Phrase::Detailed(detailed) => {
-                let mut nbest = nbest_to_c(&detailed.nbest);
-                let nbest_count = detailed.nbest.len() as i32;
-                (*c_phrase).recognition_status = to_c_string(&detailed.recognition_status);
-                (*c_phrase).display_text = ptr::null_mut();
-                (*c_phrase).offset = detailed.offset;
-                (*c_phrase).duration = detailed.duration;
-                (*c_phrase).nbest = nbest.as_mut_ptr();
-                (*c_phrase).nbest_count = nbest_count;
-                mem::forget(nbest);
-            }
-            Phrase::Silence(silence) => {
-                (*c_phrase).recognition_status = to_c_string(&silence.recognition_status);
-                (*c_phrase).display_text = ptr::null_mut();
-                (*c_phrase).offset = silence.offset;
-                (*c_phrase).duration = silence.duration;
-                (*c_phrase).nbest = ptr::null_mut();
-                (*c_phrase).nbest_count = 0;
-            }
-            Phrase::Unknown => {
-                (*c_phrase).recognition_status = to_c_string("Unknown");
-                (*c_phrase).display_text = ptr::null_mut();
-                (*c_phrase).offset = 0.0;
-                (*c_phrase).duration = 0.0;
-                (*c_phrase).nbest = ptr::null_mut();
-                (*c_phrase).nbest_count = 0;
-            }
-        };
-        0
-    } else {
-        1
+    let audio = copy_c_buffer(c_audio as *const u8, c_audio_len.max(0) as usize);
+    match (*bing_speech).handle.recognize(audio, mode, format) {
+        Ok((_, _, Some(phrase))) => {
+            ptr::write(c_phrase, phrase_to_c(phrase));
+            0
+        }
+        Ok(_) => 1,
+        Err(err) => {
+            error!("{}", err);
+            1
+        }
     }
 }
 
+/// Synthesize `c_text`, setting `c_output` to the audio or null on failure
+///
+/// The text is copied. Release the audio with `bing_speech_buffer_free`.
 #[no_mangle]
 pub unsafe extern "C" fn bing_speech_synthesize(
     bing_speech: *mut BingSpeech,
-    c_text: *mut c_char,
+    c_text: *const c_char,
     c_font: c_int,
     c_output: *mut *mut c_void,
     c_output_len: *mut c_int,
 ) {
-    let text = CString::from_raw(c_text).into_string().unwrap();
-    if let Ok((_, _, Some(mut data))) = (*bing_speech).handle.synthesize(&text, font_from_c(c_font))
-    {
-        *c_output_len = data.len() as i32;
-        *c_output = data.as_mut_ptr() as *mut c_void;
-        mem::forget(data);
-    } else {
-        *c_output_len = 0;
-        *c_output = ptr::null_mut();
-    }
-    mem::forget(text);
+    let result = match from_c_string(c_text) {
+        Some(text) => (*bing_speech).handle.synthesize(&text, font_from_c(c_font)),
+        None => Err(ErrorKind::InvalidRequest("text is null or not UTF-8".to_string()).into()),
+    };
+    write_c_buffer(result, c_output, c_output_len);
+}
+
+/// Like `bing_speech_synthesize`, returning audio in one of the
+/// `OUTPUT_FORMAT_*` formats
+///
+/// Returns 0 on success and 1 if the text isn't UTF-8 or synthesis failed,
+/// leaving no audio.
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_synthesize_with_format(
+    bing_speech: *mut BingSpeech,
+    c_text: *const c_char,
+    c_font: c_int,
+    c_output_format: c_int,
+    c_output: *mut *mut c_void,
+    c_output_len: *mut c_int,
+) -> c_int {
+    let result = match from_c_string(c_text) {
+        Some(text) => (*bing_speech).handle.synthesize_with_format(
+            &text,
+            font_from_c(c_font),
+            output_format_from_c(c_output_format),
+        ),
+        None => Err(ErrorKind::InvalidRequest("text is null or not UTF-8".to_string()).into()),
+    };
+    write_c_buffer(result, c_output, c_output_len)
+}
+
+/// Synthesize the SSML document `c_ssml` in one of the `OUTPUT_FORMAT_*`
+/// formats
+///
+/// The document is copied. Release the audio with `bing_speech_buffer_free`.
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_synthesize_ssml(
+    bing_speech: *mut BingSpeech,
+    c_ssml: *const c_char,
+    c_output_format: c_int,
+    c_output: *mut *mut c_void,
+    c_output_len: *mut c_int,
+) {
+    let result = match from_c_string(c_ssml) {
+        Some(ssml) => (*bing_speech)
+            .handle
+            .synthesize_ssml_with_format(&ssml, output_format_from_c(c_output_format)),
+        None => Err(ErrorKind::InvalidRequest("SSML is null or not UTF-8".to_string()).into()),
+    };
+    write_c_buffer(result, c_output, c_output_len);
 }
 
 #[no_mangle]
@@ -325,7 +635,7 @@ pub unsafe extern "C" fn bing_speech_websocket_connect(
     c_language: c_int,
     c_format: c_int,
     c_is_custom_speech: c_int,
-    c_endpoint_id: *mut c_char,
+    c_endpoint_id: *const c_char,
     handler: BingSpeechWebsocketHandler,
 ) -> c_int {
     let (mode, ok) = mode_from_c(c_mode, c_language);
@@ -335,23 +645,29 @@ pub unsafe extern "C" fn bing_speech_websocket_connect(
     let format = format_from_c(c_format);
     let is_custom_speech = c_is_custom_speech > 0;
     let endpoint_id = if is_custom_speech {
-        CString::from_raw(c_endpoint_id).into_string().unwrap()
+        from_c_string(c_endpoint_id).unwrap_or_default()
     } else {
         "".to_string()
     };
+    // Connect to the deployment set with
+    // bing_speech_set_custom_speech_endpoint, if any
+    if let Some(ref endpoints) = (*c_speech).handle.custom_speech_endpoints {
+        (*c_websocket)
+            .handle
+            .set_custom_speech_endpoints(Some(endpoints.clone()));
+    }
 
     // Connect to Websocket
     let result = (*c_websocket).handle.connect(
         (*c_speech).handle.token.clone(),
-        &mode,
-        &format,
+        mode,
+        format,
         is_custom_speech,
         &endpoint_id,
         Arc::new(Mutex::new(BingSpeechHandler {
             c_handler: Arc::new(Mutex::new(handler)),
         })),
     );
-    mem::forget(endpoint_id);
 
     match result {
         Ok(_) => 0,
@@ -377,284 +693,318 @@ pub unsafe extern "C" fn bing_speech_websocket_disconnect(
     }
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_websocket_set_profanity(
+    c_websocket: *mut BingSpeechWebsocket,
+    c_profanity: c_int,
+) {
+    (*c_websocket)
+        .handle
+        .set_profanity(profanity_from_c(c_profanity));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_websocket_set_word_level_timestamps(
+    c_websocket: *mut BingSpeechWebsocket,
+    c_enabled: c_int,
+) {
+    (*c_websocket)
+        .handle
+        .set_word_level_timestamps(c_enabled > 0);
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn bing_speech_websocket_free(c_websocket: *mut BingSpeechWebsocket) {
-    Box::from_raw(c_websocket);
+    if !c_websocket.is_null() {
+        drop(Box::from_raw(c_websocket));
+    }
 }
 
+/// Send `audio`, returning 0 on success
+///
+/// The audio is only read during the call.
 #[no_mangle]
 pub unsafe extern "C" fn bing_speech_websocket_audio(
     handle: *mut BingSpeechWebsocket,
     audio: *const u8,
     audio_size: usize,
 ) -> i32 {
-    const BUFFER_SIZE: usize = 4096;
-
-    let audio: Vec<u8> = Vec::from_raw_parts(audio as *mut u8, audio_size, audio_size);
-    let mut i = 0;
-
-    while i < audio_size {
-        let j = if audio_size - i < BUFFER_SIZE {
-            audio_size
-        } else {
-            i + BUFFER_SIZE
-        };
+    let audio: &[u8] = if audio.is_null() || audio_size == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(audio, audio_size)
+    };
 
+    for frame in FrameReader::new(audio, DEFAULT_FRAME_SIZE) {
         // Send audio data to Bing Speech
-        let result = (*handle).handle.audio(&audio[i..j].to_vec());
+        let result = match frame {
+            Ok(frame) => (*handle).handle.audio(&frame),
+            Err(err) => Err(err.into()),
+        };
         if let Err(err) = result {
             error!("{}", err);
             return 2;
         }
-
-        i = j;
     }
 
-    mem::forget(audio);
-
     0
 }
 
+/// Signal the end of the audio stream so the final phrase is returned
+/// without trailing silence
+#[no_mangle]
+pub unsafe extern "C" fn bing_speech_websocket_end_audio(
+    c_websocket: *mut BingSpeechWebsocket,
+) -> c_int {
+    match (*c_websocket).handle.end_audio() {
+        Ok(_) => 0,
+        Err(err) => {
+            error!("{}", err);
+            1
+        }
+    }
+}
+
 fn mode_from_c(c_mode: c_int, c_language: c_int) -> (Mode, c_int) {
     match c_mode {
         0 => match c_language {
             0 => (
-                Mode::Interactive(InteractiveDictationLanguage::ArabicEgypt),
+                Mode::Interactive(Language::ArabicEgypt),
                 0,
             ),
             1 => (
-                Mode::Interactive(InteractiveDictationLanguage::CatalanSpain),
+                Mode::Interactive(Language::CatalanSpain),
                 0,
             ),
             2 => (
-                Mode::Interactive(InteractiveDictationLanguage::ChineseChina),
+                Mode::Interactive(Language::ChineseChina),
                 0,
             ),
             3 => (
-                Mode::Interactive(InteractiveDictationLanguage::ChineseHongKong),
+                Mode::Interactive(Language::ChineseHongKong),
                 0,
             ),
             4 => (
-                Mode::Interactive(InteractiveDictationLanguage::ChineseTaiwan),
+                Mode::Interactive(Language::ChineseTaiwan),
                 0,
             ),
             5 => (
-                Mode::Interactive(InteractiveDictationLanguage::DanishDenmark),
+                Mode::Interactive(Language::DanishDenmark),
                 0,
             ),
             6 => (
-                Mode::Interactive(InteractiveDictationLanguage::DutchNetherlands),
+                Mode::Interactive(Language::DutchNetherlands),
                 0,
             ),
             7 => (
-                Mode::Interactive(InteractiveDictationLanguage::EnglishAustralia),
+                Mode::Interactive(Language::EnglishAustralia),
                 0,
             ),
             8 => (
-                Mode::Interactive(InteractiveDictationLanguage::EnglishCanada),
+                Mode::Interactive(Language::EnglishCanada),
                 0,
             ),
             9 => (
-                Mode::Interactive(InteractiveDictationLanguage::EnglishIndia),
+                Mode::Interactive(Language::EnglishIndia),
                 0,
             ),
             10 => (
-                Mode::Interactive(InteractiveDictationLanguage::EnglishNewZealand),
+                Mode::Interactive(Language::EnglishNewZealand),
                 0,
             ),
             11 => (
-                Mode::Interactive(InteractiveDictationLanguage::EnglishUnitedKingdom),
+                Mode::Interactive(Language::EnglishUnitedKingdom),
                 0,
             ),
             12 => (
-                Mode::Interactive(InteractiveDictationLanguage::EnglishUnitedStates),
+                Mode::Interactive(Language::EnglishUnitedStates),
                 0,
             ),
             13 => (
-                Mode::Interactive(InteractiveDictationLanguage::FinnishFinland),
+                Mode::Interactive(Language::FinnishFinland),
                 0,
             ),
             14 => (
-                Mode::Interactive(InteractiveDictationLanguage::FrenchCanada),
+                Mode::Interactive(Language::FrenchCanada),
                 0,
             ),
             15 => (
-                Mode::Interactive(InteractiveDictationLanguage::FrenchFrance),
+                Mode::Interactive(Language::FrenchFrance),
                 0,
             ),
             16 => (
-                Mode::Interactive(InteractiveDictationLanguage::GermanGermany),
+                Mode::Interactive(Language::GermanGermany),
                 0,
             ),
             17 => (
-                Mode::Interactive(InteractiveDictationLanguage::HindiIndia),
+                Mode::Interactive(Language::HindiIndia),
                 0,
             ),
             18 => (
-                Mode::Interactive(InteractiveDictationLanguage::ItalianItaly),
+                Mode::Interactive(Language::ItalianItaly),
                 0,
             ),
             19 => (
-                Mode::Interactive(InteractiveDictationLanguage::JapaneseJapan),
+                Mode::Interactive(Language::JapaneseJapan),
                 0,
             ),
             20 => (
-                Mode::Interactive(InteractiveDictationLanguage::KoreanKorea),
+                Mode::Interactive(Language::KoreanKorea),
                 0,
             ),
             21 => (
-                Mode::Interactive(InteractiveDictationLanguage::NorwegianNorway),
+                Mode::Interactive(Language::NorwegianNorway),
                 0,
             ),
             22 => (
-                Mode::Interactive(InteractiveDictationLanguage::PolishPoland),
+                Mode::Interactive(Language::PolishPoland),
                 0,
             ),
             23 => (
-                Mode::Interactive(InteractiveDictationLanguage::PortugueseBrazil),
+                Mode::Interactive(Language::PortugueseBrazil),
                 0,
             ),
             24 => (
-                Mode::Interactive(InteractiveDictationLanguage::PortuguesePortugal),
+                Mode::Interactive(Language::PortuguesePortugal),
                 0,
             ),
             25 => (
-                Mode::Interactive(InteractiveDictationLanguage::RussianRussia),
+                Mode::Interactive(Language::RussianRussia),
                 0,
             ),
             26 => (
-                Mode::Interactive(InteractiveDictationLanguage::SpanishMexico),
+                Mode::Interactive(Language::SpanishMexico),
                 0,
             ),
             27 => (
-                Mode::Interactive(InteractiveDictationLanguage::SpanishSpain),
+                Mode::Interactive(Language::SpanishSpain),
                 0,
             ),
             28 => (
-                Mode::Interactive(InteractiveDictationLanguage::SwedishSweden),
+                Mode::Interactive(Language::SwedishSweden),
                 0,
             ),
             _ => (
-                Mode::Interactive(InteractiveDictationLanguage::EnglishUnitedStates),
+                Mode::Interactive(Language::EnglishUnitedStates),
                 1,
             ),
         },
         1 => match c_language {
             0 => (
-                Mode::Dictation(InteractiveDictationLanguage::ArabicEgypt),
+                Mode::Dictation(Language::ArabicEgypt),
                 0,
             ),
             1 => (
-                Mode::Dictation(InteractiveDictationLanguage::CatalanSpain),
+                Mode::Dictation(Language::CatalanSpain),
                 0,
             ),
             2 => (
-                Mode::Dictation(InteractiveDictationLanguage::ChineseChina),
+                Mode::Dictation(Language::ChineseChina),
                 0,
             ),
             3 => (
-                Mode::Dictation(InteractiveDictationLanguage::ChineseHongKong),
+                Mode::Dictation(Language::ChineseHongKong),
                 0,
             ),
             4 => (
-                Mode::Dictation(InteractiveDictationLanguage::ChineseTaiwan),
+                Mode::Dictation(Language::ChineseTaiwan),
                 0,
             ),
             5 => (
-                Mode::Dictation(InteractiveDictationLanguage::DanishDenmark),
+                Mode::Dictation(Language::DanishDenmark),
                 0,
             ),
             6 => (
-                Mode::Dictation(InteractiveDictationLanguage::DutchNetherlands),
+                Mode::Dictation(Language::DutchNetherlands),
                 0,
             ),
             7 => (
-                Mode::Dictation(InteractiveDictationLanguage::EnglishAustralia),
+                Mode::Dictation(Language::EnglishAustralia),
                 0,
             ),
             8 => (
-                Mode::Dictation(InteractiveDictationLanguage::EnglishCanada),
+                Mode::Dictation(Language::EnglishCanada),
                 0,
             ),
             9 => (
-                Mode::Dictation(InteractiveDictationLanguage::EnglishIndia),
+                Mode::Dictation(Language::EnglishIndia),
                 0,
             ),
             10 => (
-                Mode::Dictation(InteractiveDictationLanguage::EnglishNewZealand),
+                Mode::Dictation(Language::EnglishNewZealand),
                 0,
             ),
             11 => (
-                Mode::Dictation(InteractiveDictationLanguage::EnglishUnitedKingdom),
+                Mode::Dictation(Language::EnglishUnitedKingdom),
                 0,
             ),
             12 => (
-                Mode::Dictation(InteractiveDictationLanguage::EnglishUnitedStates),
+                Mode::Dictation(Language::EnglishUnitedStates),
                 0,
             ),
             13 => (
-                Mode::Dictation(InteractiveDictationLanguage::FinnishFinland),
+                Mode::Dictation(Language::FinnishFinland),
                 0,
             ),
             14 => (
-                Mode::Dictation(InteractiveDictationLanguage::FrenchCanada),
+                Mode::Dictation(Language::FrenchCanada),
                 0,
             ),
             15 => (
-                Mode::Dictation(InteractiveDictationLanguage::FrenchFrance),
+                Mode::Dictation(Language::FrenchFrance),
                 0,
             ),
             16 => (
-                Mode::Dictation(InteractiveDictationLanguage::GermanGermany),
+                Mode::Dictation(Language::GermanGermany),
                 0,
             ),
-            17 => (Mode::Dictation(InteractiveDictationLanguage::HindiIndia), 0),
+            17 => (Mode::Dictation(Language::HindiIndia), 0),
             18 => (
-                Mode::Dictation(InteractiveDictationLanguage::ItalianItaly),
+                Mode::Dictation(Language::ItalianItaly),
                 0,
             ),
             19 => (
-                Mode::Dictation(InteractiveDictationLanguage::JapaneseJapan),
+                Mode::Dictation(Language::JapaneseJapan),
                 0,
             ),
             20 => (
-                Mode::Dictation(InteractiveDictationLanguage::KoreanKorea),
+                Mode::Dictation(Language::KoreanKorea),
                 0,
             ),
             21 => (
-                Mode::Dictation(InteractiveDictationLanguage::NorwegianNorway),
+                Mode::Dictation(Language::NorwegianNorway),
                 0,
             ),
             22 => (
-                Mode::Dictation(InteractiveDictationLanguage::PolishPoland),
+                Mode::Dictation(Language::PolishPoland),
                 0,
             ),
             23 => (
-                Mode::Dictation(InteractiveDictationLanguage::PortugueseBrazil),
+                Mode::Dictation(Language::PortugueseBrazil),
                 0,
             ),
             24 => (
-                Mode::Dictation(InteractiveDictationLanguage::PortuguesePortugal),
+                Mode::Dictation(Language::PortuguesePortugal),
                 0,
             ),
             25 => (
-                Mode::Dictation(InteractiveDictationLanguage::RussianRussia),
+                Mode::Dictation(Language::RussianRussia),
                 0,
             ),
             26 => (
-                Mode::Dictation(InteractiveDictationLanguage::SpanishMexico),
+                Mode::Dictation(Language::SpanishMexico),
                 0,
             ),
             27 => (
-                Mode::Dictation(InteractiveDictationLanguage::SpanishSpain),
+                Mode::Dictation(Language::SpanishSpain),
                 0,
             ),
             28 => (
-                Mode::Dictation(InteractiveDictationLanguage::SwedishSweden),
+                Mode::Dictation(Language::SwedishSweden),
                 0,
             ),
             _ => (
-                Mode::Dictation(InteractiveDictationLanguage::EnglishUnitedStates),
+                Mode::Dictation(Language::EnglishUnitedStates),
                 1,
             ),
         },
@@ -681,7 +1031,7 @@ fn mode_from_c(c_mode: c_int, c_language: c_int) -> (Mode, c_int) {
             ),
         },
         _ => (
-            Mode::Interactive(InteractiveDictationLanguage::EnglishUnitedStates),
+            Mode::Interactive(Language::EnglishUnitedStates),
             0,
         ),
     }
@@ -694,13 +1044,48 @@ fn format_from_c(c_format: c_int) -> Format {
     }
 }
 
+fn profanity_from_c(c_profanity: c_int) -> Option<Profanity> {
+    match c_profanity {
+        0 => Some(Profanity::Raw),
+        1 => Some(Profanity::Masked),
+        2 => Some(Profanity::Removed),
+        _ => None,
+    }
+}
+
+fn output_format_from_c(c_output_format: c_int) -> OutputFormat {
+    match c_output_format {
+        0 => OutputFormat::Raw8Khz8BitMonoMulaw,
+        1 => OutputFormat::Riff8Khz8BitMonoMulaw,
+        2 => OutputFormat::Raw16Khz16BitMonoPcm,
+        3 => OutputFormat::Riff16Khz16BitMonoPcm,
+        4 => OutputFormat::Raw24Khz16BitMonoPcm,
+        5 => OutputFormat::Riff24Khz16BitMonoPcm,
+        6 => OutputFormat::Raw16Khz16BitMonoTruesilk,
+        7 => OutputFormat::Raw24Khz16BitMonoTruesilk,
+        8 => OutputFormat::Riff16Khz16KbpsMonoSiren,
+        9 => OutputFormat::Audio16Khz16KbpsMonoSiren,
+        10 => OutputFormat::Audio16Khz32KbitrateMonoMp3,
+        11 => OutputFormat::Audio16Khz64KbitrateMonoMp3,
+        12 => OutputFormat::Audio16Khz128KbitrateMonoMp3,
+        13 => OutputFormat::Audio24Khz48KbitrateMonoMp3,
+        14 => OutputFormat::Audio24Khz96KbitrateMonoMp3,
+        15 => OutputFormat::Audio24Khz160KbitrateMonoMp3,
+        16 => OutputFormat::Ogg16Khz16BitMonoOpus,
+        17 => OutputFormat::Ogg24Khz16BitMonoOpus,
+        18 => OutputFormat::Webm16Khz16BitMonoOpus,
+        19 => OutputFormat::Webm24Khz16BitMonoOpus,
+        _ => OutputFormat::default(),
+    }
+}
+
 fn font_from_c(c_font: c_int) -> &'static voice::Font {
     match c_font {
         0 => voice::ar_eg::HODA,
         1 => voice::ar_sa::NAAYF,
         2 => voice::bg_bg::IVAN,
         3 => voice::ca_es::HERENA_RUS,
-        4 => voice::ca_cz::JAKUB,
+        4 => voice::cs_cz::JAKUB,
         5 => voice::da_dk::HELLE_RUS,
         6 => voice::de_at::MICHAEL,
         7 => voice::de_ch::KARSTEN,
@@ -846,6 +1231,56 @@ pub static FORMAT_SIMPLE: i32 = 0;
 #[no_mangle]
 pub static FORMAT_DETAILED: i32 = 1;
 
+#[no_mangle]
+pub static OUTPUT_FORMAT_RAW_8KHZ_8BIT_MONO_MULAW: i32 = 0;
+#[no_mangle]
+pub static OUTPUT_FORMAT_RIFF_8KHZ_8BIT_MONO_MULAW: i32 = 1;
+#[no_mangle]
+pub static OUTPUT_FORMAT_RAW_16KHZ_16BIT_MONO_PCM: i32 = 2;
+#[no_mangle]
+pub static OUTPUT_FORMAT_RIFF_16KHZ_16BIT_MONO_PCM: i32 = 3;
+#[no_mangle]
+pub static OUTPUT_FORMAT_RAW_24KHZ_16BIT_MONO_PCM: i32 = 4;
+#[no_mangle]
+pub static OUTPUT_FORMAT_RIFF_24KHZ_16BIT_MONO_PCM: i32 = 5;
+#[no_mangle]
+pub static OUTPUT_FORMAT_RAW_16KHZ_16BIT_MONO_TRUESILK: i32 = 6;
+#[no_mangle]
+pub static OUTPUT_FORMAT_RAW_24KHZ_16BIT_MONO_TRUESILK: i32 = 7;
+#[no_mangle]
+pub static OUTPUT_FORMAT_RIFF_16KHZ_16KBPS_MONO_SIREN: i32 = 8;
+#[no_mangle]
+pub static OUTPUT_FORMAT_AUDIO_16KHZ_16KBPS_MONO_SIREN: i32 = 9;
+#[no_mangle]
+pub static OUTPUT_FORMAT_AUDIO_16KHZ_32KBITRATE_MONO_MP3: i32 = 10;
+#[no_mangle]
+pub static OUTPUT_FORMAT_AUDIO_16KHZ_64KBITRATE_MONO_MP3: i32 = 11;
+#[no_mangle]
+pub static OUTPUT_FORMAT_AUDIO_16KHZ_128KBITRATE_MONO_MP3: i32 = 12;
+#[no_mangle]
+pub static OUTPUT_FORMAT_AUDIO_24KHZ_48KBITRATE_MONO_MP3: i32 = 13;
+#[no_mangle]
+pub static OUTPUT_FORMAT_AUDIO_24KHZ_96KBITRATE_MONO_MP3: i32 = 14;
+#[no_mangle]
+pub static OUTPUT_FORMAT_AUDIO_24KHZ_160KBITRATE_MONO_MP3: i32 = 15;
+#[no_mangle]
+pub static OUTPUT_FORMAT_OGG_16KHZ_16BIT_MONO_OPUS: i32 = 16;
+#[no_mangle]
+pub static OUTPUT_FORMAT_OGG_24KHZ_16BIT_MONO_OPUS: i32 = 17;
+#[no_mangle]
+pub static OUTPUT_FORMAT_WEBM_16KHZ_16BIT_MONO_OPUS: i32 = 18;
+#[no_mangle]
+pub static OUTPUT_FORMAT_WEBM_24KHZ_16BIT_MONO_OPUS: i32 = 19;
+
+#[no_mangle]
+pub static PROFANITY_DEFAULT: i32 = -1;
+#[no_mangle]
+pub static PROFANITY_RAW: i32 = 0;
+#[no_mangle]
+pub static PROFANITY_MASKED: i32 = 1;
+#[no_mangle]
+pub static PROFANITY_REMOVED: i32 = 2;
+
 #[no_mangle]
 pub static VOICE_FONT_AR_EG_HODA: i32 = 0;
 #[no_mangle]
@@ -0,0 +1,280 @@
+//! High-level continuous recognition
+//!
+//! `Speech::recognize_continuous` streams audio from any `Read` through the
+//! websocket API and takes care of the connection, the `speech.config`
+//! message, pacing, reconnection and waiting for the final phrase.
+
+// std
+use std::io::{ErrorKind as IoErrorKind, Read};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// internal
+use super::intent::IntentResult;
+use super::websocket::{Handler, Websocket};
+use super::*;
+use audio::{FrameReader, Spec};
+
+/// Options for `Speech::recognize_continuous`
+#[derive(Debug, Clone)]
+pub struct ContinuousOptions {
+    pub mode: Mode,
+    pub format: Format,
+    /// Bytes of audio sent per websocket message
+    pub chunk_size: usize,
+    /// Send audio no faster than real time, as a live source would
+    pub pacing: bool,
+    /// Reconnect this many times after the connection drops
+    pub max_reconnects: u32,
+    /// How long to wait for the connection to be established
+    pub connect_timeout: Duration,
+    /// How long to wait for the final phrase after the audio ended
+    pub final_phrase_timeout: Duration,
+}
+
+impl ContinuousOptions {
+    pub fn new(mode: Mode, format: Format) -> Self {
+        ContinuousOptions {
+            mode,
+            format,
+            chunk_size: audio::frame::DEFAULT_FRAME_SIZE,
+            pacing: true,
+            max_reconnects: 3,
+            connect_timeout: Duration::from_secs(10),
+            final_phrase_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Forwards callbacks and signals the end of each turn
+struct TurnTracker {
+    inner: Arc<Mutex<Handler + Send + Sync>>,
+    turn_end: Mutex<Sender<()>>,
+}
+
+impl Handler for TurnTracker {
+    fn on_turn_start(&mut self) {
+        self.inner.lock().unwrap().on_turn_start();
+    }
+
+    fn on_turn_end(&mut self) {
+        self.inner.lock().unwrap().on_turn_end();
+        let _ = self.turn_end.lock().unwrap().send(());
+    }
+
+    fn on_speech_start(&mut self) {
+        self.inner.lock().unwrap().on_speech_start();
+    }
+
+    fn on_speech_end(&mut self) {
+        self.inner.lock().unwrap().on_speech_end();
+    }
+
+    fn on_local_speech_start(&mut self) {
+        self.inner.lock().unwrap().on_local_speech_start();
+    }
+
+    fn on_local_speech_end(&mut self) {
+        self.inner.lock().unwrap().on_local_speech_end();
+    }
+
+    fn on_speech_hypothesis(&mut self, hypothesis: Hypothesis) {
+        self.inner.lock().unwrap().on_speech_hypothesis(hypothesis);
+    }
+
+    fn on_speech_phrase(&mut self, phrase: Phrase) {
+        self.inner.lock().unwrap().on_speech_phrase(phrase);
+    }
+
+    fn on_speaker_change(&mut self, speaker_id: &str) {
+        self.inner.lock().unwrap().on_speaker_change(speaker_id);
+    }
+
+    fn on_intent(&mut self, result: IntentResult) {
+        self.inner.lock().unwrap().on_intent(result);
+    }
+
+    fn on_raw_message(&mut self, path: &str, headers: &[(&str, &str)], body: &str) {
+        self.inner.lock().unwrap().on_raw_message(path, headers, body);
+    }
+
+    fn on_connect(&mut self, connection_id: &str) {
+        self.inner.lock().unwrap().on_connect(connection_id);
+    }
+
+    fn on_disconnect(&mut self, code: u16, reason: &str) {
+        self.inner.lock().unwrap().on_disconnect(code, reason);
+    }
+
+    fn on_error(&mut self, err: &Error) {
+        self.inner.lock().unwrap().on_error(err);
+    }
+
+    fn on_reconnect(&mut self, attempt: u32) {
+        self.inner.lock().unwrap().on_reconnect(attempt);
+    }
+}
+
+impl Speech {
+    /// Recognize a continuous audio stream, invoking `handler` as results
+    /// arrive
+    ///
+    /// `audio` must yield 16 kHz 16-bit mono PCM. The call blocks until the
+    /// stream ends and its final phrase has been delivered, or the connection
+    /// couldn't be recovered. A token must have been fetched beforehand;
+    /// combine with `auto_fetch_token` for long streams.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use std::sync::{Arc, Mutex};
+    /// use bing_rs::speech::*;
+    /// use bing_rs::speech::websocket::Handler;
+    ///
+    /// struct Printer;
+    /// impl Handler for Printer {
+    ///     fn on_speech_phrase(&mut self, phrase: Phrase) {
+    ///         println!("{}", phrase.text().unwrap_or(""));
+    ///     }
+    /// }
+    ///
+    /// let mut speech = Speech::new("your_subscription_key").unwrap();
+    /// speech.fetch_token().unwrap();
+    /// let mode = Mode::Dictation(Language::EnglishUnitedStates);
+    /// let options = ContinuousOptions::new(mode, Format::Simple);
+    /// let audio = File::open("assets/audio.wav").unwrap();
+    /// speech
+    ///     .recognize_continuous(audio, &options, Arc::new(Mutex::new(Printer)))
+    ///     .unwrap();
+    /// ```
+    pub fn recognize_continuous<R>(
+        &self,
+        audio: R,
+        options: &ContinuousOptions,
+        handler: Arc<Mutex<Handler + Send + Sync>>,
+    ) -> Result<()>
+    where
+        R: Read,
+    {
+        let (turn_end_tx, turn_end_rx) = channel();
+        let tracker: Arc<Mutex<Handler + Send + Sync>> = Arc::new(Mutex::new(TurnTracker {
+            inner: handler,
+            turn_end: Mutex::new(turn_end_tx),
+        }));
+
+        let mut ws = self.start_continuous(options, tracker.clone())?;
+
+        let chunk_duration = Spec::default().duration(options.chunk_size);
+        let mut reconnects = 0;
+        let start = Instant::now();
+        let mut sent = Duration::from_secs(0);
+        for chunk in FrameReader::new(audio, options.chunk_size) {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(ref err) if err.kind() == IoErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+
+            while let Err(err) = ws.audio(&chunk) {
+                let mut err: Error = err.into();
+                // Failed reconnects count against the limit like lost
+                // connections do
+                loop {
+                    if reconnects >= options.max_reconnects {
+                        return Err(err);
+                    }
+                    reconnects += 1;
+                    warn!(
+                        "Connection lost ({}), reconnecting ({}/{})",
+                        err, reconnects, options.max_reconnects
+                    );
+                    self.metrics.reconnect();
+
+                    match self.start_continuous(options, tracker.clone()) {
+                        Ok(reconnected) => {
+                            ws = reconnected;
+                            break;
+                        }
+                        Err(reconnect_err) => err = reconnect_err,
+                    }
+                }
+            }
+
+            if options.pacing {
+                sent += chunk_duration;
+                let elapsed = start.elapsed();
+                if sent > elapsed {
+                    thread::sleep(sent - elapsed);
+                }
+            }
+        }
+        // Only the turn ended by the end of audio counts
+        while turn_end_rx.try_recv().is_ok() {}
+        ws.end_audio()?;
+
+        if turn_end_rx
+            .recv_timeout(options.final_phrase_timeout)
+            .is_err()
+        {
+            warn!("Timed out waiting for the final phrase");
+        }
+        ws.disconnect()
+    }
+
+    /// Websocket sharing this client's endpoints, metrics and statistics
+    pub(crate) fn continuous_websocket(&self) -> Websocket {
+        let mut ws = Websocket::new();
+        ws.set_endpoints(Some(self.endpoints()));
+        ws.set_metrics_sink(self.metrics.clone());
+        ws.set_stats_tracker(self.stats.clone());
+        ws.set_correlation_id(self.correlation_id.clone());
+        ws.set_budget(self.budget.clone());
+        ws.set_recognition_params(self.params);
+        if let Some(ref proxy) = self.proxy {
+            ws.set_proxy(Some(proxy.clone()));
+        }
+        ws
+    }
+
+    /// Connect a new websocket and send the speech configuration
+    fn start_continuous(
+        &self,
+        options: &ContinuousOptions,
+        handler: Arc<Mutex<Handler + Send + Sync>>,
+    ) -> Result<Websocket> {
+        let mut ws = self.continuous_websocket();
+        self.open_continuous(&ws, options, handler)?;
+        ws.config(&default_speech_config())?;
+        Ok(ws)
+    }
+
+    /// Connect `ws` and wait until audio can be sent
+    pub(crate) fn open_continuous(
+        &self,
+        ws: &Websocket,
+        options: &ContinuousOptions,
+        handler: Arc<Mutex<Handler + Send + Sync>>,
+    ) -> Result<()> {
+        self.ensure_token()?;
+        ws.connect(
+            self.token.clone(),
+            options.mode,
+            options.format,
+            self.is_custom_speech,
+            &self.endpoint_id,
+            handler,
+        )?;
+
+        let start = Instant::now();
+        while !ws.is_connected() {
+            if start.elapsed() > options.connect_timeout {
+                bail!("timed out connecting to the websocket endpoint");
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        Ok(())
+    }
+}
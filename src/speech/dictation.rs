@@ -0,0 +1,206 @@
+//! Dictation of audio longer than a single connection
+//!
+//! The service closes websocket connections after about ten minutes.
+//! `LongDictation` splits a stream of any length into parts sent on their
+//! own connection, preferably during a pause so words aren't cut in half,
+//! waits for each part's `turn.end` and stitches the phrases into one
+//! `Transcript` whose offsets count from the start of the whole stream.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.fetch_token().unwrap();
+//! let mode = Mode::Dictation(Language::EnglishUnitedStates);
+//! let mut options = ContinuousOptions::new(mode, Format::Simple);
+//! options.pacing = false;
+//!
+//! let dictation = speech.long_dictation(options);
+//! let transcript = dictation.transcribe(File::open("lecture.raw").unwrap()).unwrap();
+//! println!("{}", transcript.text());
+//! ```
+
+// std
+use std::io::{ErrorKind as IoErrorKind, Read};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::{Arc, Mutex};
+
+// internal
+use super::websocket::{Handler, ReconnectPolicy};
+use super::*;
+use audio::{gain, FrameReader, Spec};
+use transcript::Transcript;
+
+/// Longest audio sent on one connection by default
+pub const DEFAULT_MAX_PART: Duration = Duration::from_secs(9 * 60);
+
+/// How long before `max_part` a pause may end the part by default
+pub const DEFAULT_SPLIT_WINDOW: Duration = Duration::from_secs(30);
+
+/// Collects the phrases of one part and signals its end
+struct PartHandler {
+    transcript: Arc<Mutex<Transcript>>,
+    turn_end: Mutex<Sender<()>>,
+}
+
+impl Handler for PartHandler {
+    fn on_turn_end(&mut self) {
+        let _ = self.turn_end.lock().unwrap().send(());
+    }
+
+    fn on_speech_phrase(&mut self, phrase: Phrase) {
+        self.transcript.lock().unwrap().push(&phrase);
+    }
+}
+
+/// Transcribes audio streams of any length in dictation mode
+///
+/// Created with `Speech::long_dictation`. Use a `Mode::Dictation` mode; the
+/// other modes end turns after a single utterance.
+pub struct LongDictation<'a> {
+    speech: &'a Speech,
+    options: ContinuousOptions,
+    max_part: Duration,
+    split_window: Duration,
+    silence_threshold: f32,
+}
+
+impl<'a> LongDictation<'a> {
+    fn new(speech: &'a Speech, options: ContinuousOptions) -> Self {
+        LongDictation {
+            speech,
+            options,
+            max_part: DEFAULT_MAX_PART,
+            split_window: DEFAULT_SPLIT_WINDOW,
+            silence_threshold: 0.01,
+        }
+    }
+
+    /// Send at most `max_part` of audio on one connection
+    pub fn set_max_part(&mut self, max_part: Duration) {
+        self.max_part = max_part;
+    }
+
+    /// End a part at the first pause within `split_window` of `max_part`
+    ///
+    /// Audio with an RMS level under `silence_threshold` (0.0 - 1.0 of full
+    /// scale) counts as a pause. Without a pause the part ends at exactly
+    /// `max_part`.
+    pub fn set_split(&mut self, split_window: Duration, silence_threshold: f32) {
+        self.split_window = split_window;
+        self.silence_threshold = silence_threshold;
+    }
+
+    /// Recognize 16 kHz 16-bit mono PCM `audio` until it ends
+    ///
+    /// Phrases are returned in order, with offsets from the start of
+    /// `audio`.
+    pub fn transcribe<R>(&self, audio: R) -> Result<Transcript>
+    where
+        R: Read,
+    {
+        let spec = Spec::default();
+        let chunk_size = self.options.chunk_size.max(2);
+        let transcript = Arc::new(Mutex::new(Transcript::new()));
+        let mut frames = FrameReader::new(audio, chunk_size);
+
+        let start = Instant::now();
+        let mut offset = Duration::from_secs(0);
+        let mut finished = false;
+        while !finished {
+            let (turn_end_tx, turn_end_rx) = channel();
+            let mut ws = None;
+            let mut part = Duration::from_secs(0);
+
+            loop {
+                let chunk = match frames.next() {
+                    Some(Ok(chunk)) => chunk,
+                    Some(Err(ref err)) if err.kind() == IoErrorKind::Interrupted => continue,
+                    Some(Err(err)) => return Err(err.into()),
+                    None => {
+                        finished = true;
+                        break;
+                    }
+                };
+
+                // Connect once there's audio, so a stream ending on a part
+                // boundary doesn't open an empty connection
+                if ws.is_none() {
+                    transcript.lock().unwrap().start_stream_at(offset);
+                    let handler: Arc<Mutex<Handler + Send + Sync>> =
+                        Arc::new(Mutex::new(PartHandler {
+                            transcript: transcript.clone(),
+                            turn_end: Mutex::new(turn_end_tx.clone()),
+                        }));
+                    ws = Some(self.open_part(handler)?);
+                }
+                if let Some(ref mut ws) = ws {
+                    ws.push_audio(&chunk)?;
+                }
+
+                part += spec.duration(chunk.len());
+                if self.options.pacing {
+                    let sent = offset + part;
+                    let elapsed = start.elapsed();
+                    if sent > elapsed {
+                        thread::sleep(sent - elapsed);
+                    }
+                }
+                if self.should_split(part, &chunk) {
+                    break;
+                }
+            }
+
+            if let Some(mut ws) = ws {
+                info!("Ending dictation part at {:?}", offset + part);
+                // Only the turn ended by this part's end of audio counts
+                while turn_end_rx.try_recv().is_ok() {}
+                ws.end_audio()?;
+                if turn_end_rx
+                    .recv_timeout(self.options.final_phrase_timeout)
+                    .is_err()
+                {
+                    warn!("Timed out waiting for the final phrase");
+                }
+                ws.disconnect()?;
+            }
+            offset += part;
+        }
+
+        let transcript = transcript.lock().unwrap().clone();
+        Ok(transcript)
+    }
+
+    /// Whether the part should end after `chunk`, `part` into it
+    fn should_split(&self, part: Duration, chunk: &[u8]) -> bool {
+        if part >= self.max_part {
+            return true;
+        }
+        part + self.split_window >= self.max_part && gain::rms(chunk) < self.silence_threshold
+    }
+
+    /// Connect a websocket for the next part and send "speech.config"
+    fn open_part(&self, handler: Arc<Mutex<Handler + Send + Sync>>) -> Result<Websocket> {
+        let mut ws = self.speech.continuous_websocket();
+        if self.options.max_reconnects > 0 {
+            let mut policy = ReconnectPolicy::new(self.options.max_reconnects);
+            policy.connect_timeout = self.options.connect_timeout;
+            ws.set_reconnect(Some(policy));
+        }
+        self.speech.open_continuous(&ws, &self.options, handler)?;
+        ws.config(&default_speech_config())?;
+        Ok(ws)
+    }
+}
+
+impl Speech {
+    /// Transcriber for dictation longer than one connection allows
+    ///
+    /// `ContinuousOptions::pacing` should be turned off for recorded audio.
+    pub fn long_dictation(&self, options: ContinuousOptions) -> LongDictation {
+        LongDictation::new(self, options)
+    }
+}
@@ -0,0 +1,168 @@
+//! Dry-run request validation
+//!
+//! The `dry_run_*` methods build and validate the same requests as
+//! `fetch_token`, `recognize` and `synthesize` and return a description of
+//! them instead of sending anything. Use them in tests and pre-flight checks
+//! to catch configuration problems without a network connection.
+//!
+//! # Examples
+//!
+//! ```
+//! use bing_rs::speech::*;
+//!
+//! let speech = Speech::new("your_subscription_key").unwrap();
+//! let request = speech.dry_run_fetch_token().unwrap();
+//! println!("{}", request);
+//! ```
+
+use super::*;
+
+/// Description of a request that would have been sent
+///
+/// Credentials are redacted from the headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestDescription {
+    pub kind: RequestKind,
+    pub method: String,
+    pub uri: String,
+    pub headers: Vec<(String, String)>,
+    pub body_len: usize,
+    /// Body of text requests such as SSML; `None` for audio
+    pub body: Option<String>,
+}
+
+impl RequestDescription {
+    fn new(
+        kind: RequestKind,
+        request: &Request<Body>,
+        body_len: usize,
+        body: Option<String>,
+    ) -> Self {
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = if redact::is_sensitive(name.as_str()) {
+                    "[REDACTED]"
+                } else {
+                    value.to_str().unwrap_or("[binary]")
+                };
+                (name.as_str().to_string(), value.to_string())
+            }).collect();
+
+        RequestDescription {
+            kind,
+            method: request.method().to_string(),
+            uri: request.uri().to_string(),
+            headers,
+            body_len,
+            body,
+        }
+    }
+}
+
+impl Display for RequestDescription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} {}", self.method, self.uri)?;
+        for &(ref name, ref value) in &self.headers {
+            writeln!(f, "{}: {}", name, value)?;
+        }
+        match self.body {
+            Some(ref body) => write!(f, "\n{}", body),
+            None => write!(f, "\n[{} bytes]", self.body_len),
+        }
+    }
+}
+
+impl Speech {
+    /// Build and validate the request `fetch_token` would send
+    pub fn dry_run_fetch_token(&self) -> Result<RequestDescription> {
+        if self.subscription_key.is_empty() {
+            return Err(invalid("subscription key is empty"));
+        }
+
+        let request = self.token_request()?;
+        Ok(RequestDescription::new(RequestKind::Token, &request, 0, None))
+    }
+
+    /// Build and validate the request `recognize` would send
+    pub fn dry_run_recognize(
+        &self,
+        audio: &[u8],
+        mode: Mode,
+        format: Format,
+    ) -> Result<RequestDescription> {
+        self.check_token()?;
+        if self.is_custom_speech && self.endpoint_id.is_empty() {
+            return Err(invalid("custom speech is enabled but no endpoint ID is set"));
+        }
+        if audio.is_empty() {
+            return Err(invalid("audio is empty"));
+        }
+        if audio.len() % 2 != 0 {
+            return Err(invalid("audio length is odd; expected 16-bit PCM samples"));
+        }
+
+        let request = self.recognize_request(audio.to_vec().into(), mode, format, self.params)?;
+        Ok(RequestDescription::new(
+            RequestKind::Recognize,
+            &request,
+            audio.len(),
+            None,
+        ))
+    }
+
+    /// Build and validate the request `synthesize` would send
+    pub fn dry_run_synthesize(
+        &self,
+        text: &str,
+        font: &voice::Font,
+    ) -> Result<RequestDescription> {
+        self.check_token()?;
+        if text.trim().is_empty() {
+            return Err(invalid("text is empty"));
+        }
+        if text.contains('<') || text.contains('>') {
+            return Err(invalid("text contains markup; escape '<' and '>'"));
+        }
+        if has_bare_ampersand(text) {
+            return Err(invalid("text contains an unescaped '&'"));
+        }
+
+        let data = synthesis_ssml(text, font);
+        let data_len = data.len();
+        let request = self.synthesize_request(data.clone(), OutputFormat::default())?;
+        Ok(RequestDescription::new(
+            RequestKind::Synthesize,
+            &request,
+            data_len,
+            Some(data),
+        ))
+    }
+
+    fn check_token(&self) -> Result<()> {
+        if self.token.lock().unwrap().is_empty() {
+            return Err(invalid("no token; call fetch_token first"));
+        }
+        Ok(())
+    }
+}
+
+fn invalid(reason: &str) -> Error {
+    ErrorKind::InvalidRequest(reason.to_string()).into()
+}
+
+/// Whether `text` contains an `&` that doesn't start an XML entity
+fn has_bare_ampersand(text: &str) -> bool {
+    text.match_indices('&').any(|(i, _)| {
+        let rest = &text[i + 1..];
+        match rest.find(';') {
+            Some(end) => {
+                let entity = &rest[..end];
+                !(["amp", "lt", "gt", "quot", "apos"].contains(&entity)
+                    || (entity.starts_with('#') && entity.len() > 1))
+            }
+            None => true,
+        }
+    })
+}
@@ -0,0 +1,210 @@
+use url::Url;
+
+use super::{Format, Mode, Profanity, Region};
+use errors::*;
+
+/// Voice list of the West US deployment, used unless a region is set
+const DEFAULT_VOICES_URL: &str =
+    "https://westus.tts.speech.microsoft.com/cognitiveservices/voices/list";
+
+/// Service URLs used by `Speech` and `Websocket`
+///
+/// Defaults to the public Bing Speech endpoints, or the West US Custom
+/// Speech endpoints when custom speech is enabled, unless another region or
+/// host is set with `SpeechBuilder::region` /
+/// `Speech::set_custom_speech_endpoint`. Override them with
+/// `Speech::set_endpoints` / `Websocket::set_endpoints`, e.g. to point at
+/// `testing::MockServer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// Token issuing URL
+    pub token: String,
+    /// Base URL for REST recognition, without the mode
+    pub recognition: String,
+    /// Base URL for websocket recognition, without the mode
+    pub websocket: String,
+    /// Text-to-speech URL
+    pub synthesis: String,
+    /// URL listing the text-to-speech voices
+    pub voices: String,
+}
+
+impl Endpoints {
+    /// Public Bing Speech endpoints
+    pub fn bing() -> Self {
+        Endpoints {
+            token: "https://api.cognitive.microsoft.com/sts/v1.0/issueToken".to_string(),
+            recognition: "https://speech.platform.bing.com/speech/recognition".to_string(),
+            websocket: "wss://speech.platform.bing.com/speech/recognition".to_string(),
+            synthesis: "https://speech.platform.bing.com/synthesize".to_string(),
+            voices: DEFAULT_VOICES_URL.to_string(),
+        }
+    }
+
+    /// Bing Custom Speech endpoints in West US
+    pub fn custom_speech() -> Self {
+        Self::custom_speech_region(Region::WestUS)
+    }
+
+    /// Bing Custom Speech endpoints of a deployment in `region`
+    pub fn custom_speech_region(region: Region) -> Self {
+        Endpoints {
+            token: format!(
+                "https://{}.api.cognitive.microsoft.com/sts/v1.0/issueToken",
+                region
+            ),
+            recognition: format!(
+                "https://{}.stt.speech.microsoft.com/speech/recognition",
+                region
+            ),
+            websocket: format!(
+                "wss://{}.stt.speech.microsoft.com/speech/recognition",
+                region
+            ),
+            synthesis: "https://speech.platform.bing.com/synthesize".to_string(),
+            voices: DEFAULT_VOICES_URL.to_string(),
+        }
+    }
+
+    /// Send REST and websocket recognitions to the host of `url`, e.g.
+    /// "https://westeurope.stt.speech.microsoft.com", keeping the token and
+    /// synthesis endpoints
+    ///
+    /// The websocket URL uses "wss" for "https" hosts and "ws" for "http"
+    /// ones.
+    pub fn with_recognition_host(mut self, url: &str) -> Result<Self> {
+        let url = Url::parse(url)?;
+        let websocket_scheme = match url.scheme() {
+            "https" => "wss",
+            "http" => "ws",
+            scheme => bail!(ErrorKind::InvalidRequest(format!(
+                "unsupported recognition endpoint scheme {:?}",
+                scheme
+            ))),
+        };
+        let host = match url.host_str() {
+            Some(host) => host,
+            None => bail!(ErrorKind::InvalidRequest(format!(
+                "recognition endpoint {} has no host",
+                url
+            ))),
+        };
+        let authority = match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+
+        self.recognition = format!("{}://{}/speech/recognition", url.scheme(), authority);
+        self.websocket = format!("{}://{}/speech/recognition", websocket_scheme, authority);
+        Ok(self)
+    }
+
+    /// Regional speech service endpoints
+    pub fn region(region: Region) -> Self {
+        Endpoints {
+            token: format!(
+                "https://{}.api.cognitive.microsoft.com/sts/v1.0/issueToken",
+                region
+            ),
+            recognition: format!(
+                "https://{}.stt.speech.microsoft.com/speech/recognition",
+                region
+            ),
+            websocket: format!(
+                "wss://{}.stt.speech.microsoft.com/speech/recognition",
+                region
+            ),
+            synthesis: format!(
+                "https://{}.tts.speech.microsoft.com/cognitiveservices/v1",
+                region
+            ),
+            voices: format!(
+                "https://{}.tts.speech.microsoft.com/cognitiveservices/voices/list",
+                region
+            ),
+        }
+    }
+
+    /// Default endpoints depending on whether custom speech is enabled
+    pub fn default_for(is_custom_speech: bool) -> Self {
+        if is_custom_speech {
+            Self::custom_speech()
+        } else {
+            Self::bing()
+        }
+    }
+
+    /// Full REST recognition URL
+    pub fn recognition_url(
+        &self,
+        mode: Mode,
+        format: Format,
+        endpoint_id: Option<&str>,
+        params: RecognitionParams,
+    ) -> String {
+        recognition_url(&self.recognition, mode, format, endpoint_id, params)
+    }
+
+    /// Full websocket recognition URL
+    pub fn websocket_url(
+        &self,
+        mode: Mode,
+        format: Format,
+        endpoint_id: Option<&str>,
+        params: RecognitionParams,
+    ) -> String {
+        recognition_url(&self.websocket, mode, format, endpoint_id, params)
+    }
+}
+
+/// Optional query parameters of recognition URLs
+///
+/// Parameters left at their default aren't sent, so the service defaults
+/// apply.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RecognitionParams {
+    /// How profanity is handled, masked by the service if `None`
+    pub profanity: Option<Profanity>,
+    /// Include per-word offsets and durations in detailed results
+    pub word_level_timestamps: bool,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self::bing()
+    }
+}
+
+fn recognition_url(
+    base: &str,
+    mode: Mode,
+    format: Format,
+    endpoint_id: Option<&str>,
+    params: RecognitionParams,
+) -> String {
+    let language = match mode {
+        Mode::Interactive(language) | Mode::Dictation(language) => language.to_string(),
+        Mode::Conversation(language) => language.to_string(),
+        Mode::Detect(candidates) => format!(
+            "{}&lidEnabled=true&lidLanguages={}",
+            mode.language(),
+            candidates
+        ),
+    };
+    let cid = match endpoint_id {
+        Some(endpoint_id) => format!("cid={}&", endpoint_id),
+        None => String::new(),
+    };
+    let mut extra = String::new();
+    if let Some(profanity) = params.profanity {
+        extra.push_str(&format!("&profanity={}", profanity));
+    }
+    if params.word_level_timestamps {
+        extra.push_str("&wordLevelTimestamps=true");
+    }
+
+    format!(
+        "{}/{}/cognitiveservices/v1?{}language={}&format={}{}",
+        base, mode, cid, language, format, extra
+    )
+}
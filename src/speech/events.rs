@@ -0,0 +1,207 @@
+//! Structured session event export
+//!
+//! An `EventLog` attached to a `Websocket` writes one JSON object per line
+//! for every session event: connection changes, messages sent and received
+//! (with their `Path`, `X-RequestId` and body) and errors. Unlike a
+//! `record::Recorder`, which keeps raw frames for replay, the event log is
+//! meant to be read by people and log tooling as an audit trail.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::events::EventLog;
+//! use bing_rs::speech::websocket::Websocket;
+//!
+//! let mut ws = Websocket::new();
+//! ws.set_event_log(Some(EventLog::create("session-events.jsonl").unwrap()));
+//! ```
+
+// std
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+// chrono / serde
+use chrono::prelude::*;
+use serde_json;
+
+// internal
+use super::record::Direction;
+use errors::*;
+
+/// Kind of session event
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum EventKind {
+    Connected,
+    Disconnected,
+    Message,
+    Error,
+}
+
+/// Single line of a session event log
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionEvent {
+    pub kind: EventKind,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Raw text body of the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Size of the audio carried by a binary message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_bytes: Option<usize>,
+    /// Close reason or error description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl SessionEvent {
+    fn new(kind: EventKind) -> Self {
+        SessionEvent {
+            kind,
+            timestamp: Local::now().to_rfc3339(),
+            correlation_id: None,
+            direction: None,
+            path: None,
+            request_id: None,
+            body: None,
+            audio_bytes: None,
+            detail: None,
+        }
+    }
+}
+
+/// Writes session events as JSON Lines
+///
+/// Clones share the same underlying writer.
+#[derive(Clone)]
+pub struct EventLog {
+    writer: Arc<Mutex<Box<Write + Send>>>,
+    correlation_id: Arc<Mutex<Option<String>>>,
+}
+
+impl EventLog {
+    /// Log into a new file at `path`
+    pub fn create<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    /// Log into an arbitrary writer
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        EventLog {
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+            correlation_id: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Stamp `correlation_id` onto every following event
+    pub fn set_correlation_id(&self, correlation_id: Option<String>) {
+        *self.correlation_id.lock().unwrap() = correlation_id;
+    }
+
+    /// Log that the websocket connection was opened
+    pub fn connected(&self) {
+        self.write(SessionEvent::new(EventKind::Connected));
+    }
+
+    /// Log that the websocket connection was closed
+    pub fn disconnected(&self, reason: &str) {
+        let mut event = SessionEvent::new(EventKind::Disconnected);
+        if !reason.is_empty() {
+            event.detail = Some(reason.to_string());
+        }
+        self.write(event);
+    }
+
+    /// Log a connection or protocol error
+    pub fn error(&self, detail: &str) {
+        let mut event = SessionEvent::new(EventKind::Error);
+        event.detail = Some(detail.to_string());
+        self.write(event);
+    }
+
+    /// Log a text message, splitting it into headers and body
+    pub fn text(&self, direction: Direction, text: &str) {
+        let mut sections = text.splitn(2, "\r\n\r\n");
+        let header = sections.next().unwrap_or("");
+        let body = sections.next().unwrap_or("");
+
+        let mut event = SessionEvent::new(EventKind::Message);
+        event.direction = Some(direction);
+        event.path = header_value(header, "Path");
+        event.request_id = header_value(header, "X-RequestId");
+        if !body.is_empty() {
+            event.body = Some(body.to_string());
+        }
+        self.write(event);
+    }
+
+    /// Log a binary audio message
+    ///
+    /// `data` is the full message: a 2-byte header length, the header text
+    /// and the audio.
+    pub fn binary(&self, direction: Direction, data: &[u8]) {
+        let mut event = SessionEvent::new(EventKind::Message);
+        event.direction = Some(direction);
+        if data.len() >= 2 {
+            let header_len = ((data[0] as usize) << 8) | data[1] as usize;
+            let header_end = (2 + header_len).min(data.len());
+            let header = String::from_utf8_lossy(&data[2..header_end]);
+            event.path = header_value(&header, "Path");
+            event.request_id = header_value(&header, "X-RequestId");
+            event.audio_bytes = Some(data.len() - header_end);
+        }
+        self.write(event);
+    }
+
+    /// Flush buffered events to the underlying writer
+    pub fn flush(&self) -> Result<()> {
+        Ok(self.writer.lock().unwrap().flush()?)
+    }
+
+    fn write(&self, mut event: SessionEvent) {
+        event.correlation_id = self.correlation_id.lock().unwrap().clone();
+        let line = match serde_json::to_string(&event) {
+            Ok(line) => line,
+            Err(err) => {
+                error!("Failed to serialize session event: {}", err);
+                return;
+            }
+        };
+
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(err) = writeln!(writer, "{}", line) {
+            error!("Failed to log session event: {}", err);
+        }
+    }
+}
+
+fn header_value(header: &str, name: &str) -> Option<String> {
+    header
+        .split("\r\n")
+        .filter_map(|line| {
+            let mut kv = line.splitn(2, ':');
+            match (kv.next(), kv.next()) {
+                (Some(key), Some(value)) if key.trim().eq_ignore_ascii_case(name) => {
+                    Some(value.trim().to_string())
+                }
+                _ => None,
+            }
+        }).next()
+}
@@ -0,0 +1,165 @@
+//! Wake-word gating of a recognition session
+//!
+//! `GatedSession` keeps a `RecognitionSession` connected but only sends
+//! audio while awake, e.g. between a local wake-word detection and the end
+//! of the command. While asleep it keeps the last moments of audio, so the
+//! words spoken right as the wake event fires aren't clipped.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.fetch_token().unwrap();
+//! let mode = Mode::Interactive(Language::EnglishUnitedStates);
+//! let mut session = GatedSession::new(
+//!     speech.recognition_session(ContinuousOptions::new(mode, Format::Simple)),
+//! );
+//! session.set_preroll(Duration::from_millis(500));
+//! session.start().unwrap();
+//!
+//! // Kept while asleep, then sent first on wake
+//! session.push_audio(&[0; 3200]).unwrap();
+//! session.set_awake(true).unwrap();
+//! session.push_audio(&[0; 3200]).unwrap();
+//! session.set_awake(false).unwrap();
+//! session.stop().unwrap();
+//! ```
+
+use super::*;
+use audio::preroll::PreRoll;
+
+/// `RecognitionSession` that only sends audio while awake
+///
+/// Starts asleep.
+pub struct GatedSession<'a> {
+    session: RecognitionSession<'a>,
+    awake: bool,
+    /// Whether audio was sent since waking, i.e. a turn is open
+    sent: bool,
+    preroll: Duration,
+    /// Latest audio pushed while asleep, if a preroll is set
+    buffer: Option<PreRoll>,
+}
+
+impl<'a> GatedSession<'a> {
+    pub fn new(session: RecognitionSession<'a>) -> Self {
+        GatedSession {
+            session,
+            awake: false,
+            sent: false,
+            preroll: Duration::from_secs(0),
+            buffer: None,
+        }
+    }
+
+    /// Keep the last `preroll` of audio pushed while asleep and send it
+    /// first on wake (none by default)
+    ///
+    /// The buffer is sized for the input layout when the session starts.
+    /// Changing it clears the buffered audio.
+    pub fn set_preroll(&mut self, preroll: Duration) {
+        self.preroll = preroll;
+        self.resize_buffer();
+    }
+
+    pub fn is_awake(&self) -> bool {
+        self.awake
+    }
+
+    /// Resume or suspend sending audio
+    ///
+    /// Waking sends the buffered audio. Falling asleep ends the current
+    /// turn so its final phrase comes back right away, unless no audio was
+    /// sent since waking.
+    pub fn set_awake(&mut self, awake: bool) -> Result<()> {
+        if awake == self.awake {
+            return Ok(());
+        }
+        self.awake = awake;
+        let sent = self.sent;
+        self.sent = false;
+
+        if awake {
+            let buffered = match self.buffer {
+                Some(ref buffer) => {
+                    let audio = buffer.snapshot();
+                    buffer.clear();
+                    audio
+                }
+                None => return Ok(()),
+            };
+            if !buffered.is_empty() {
+                self.session.push_audio(&buffered)?;
+                self.sent = true;
+            }
+            Ok(())
+        } else if sent && self.session.is_started() {
+            self.session.end_turn()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Send `audio` while awake, or keep its end while asleep
+    ///
+    /// Audio is in the layout set with
+    /// `RecognitionSession::set_input_format`.
+    pub fn push_audio(&mut self, audio: &[u8]) -> Result<()> {
+        if self.awake {
+            self.session.push_audio(audio)?;
+            self.sent |= !audio.is_empty();
+            return Ok(());
+        }
+        if let Some(ref buffer) = self.buffer {
+            buffer.push(audio);
+        }
+        Ok(())
+    }
+
+    /// Drop the audio buffered while asleep
+    pub fn clear_buffer(&mut self) {
+        if let Some(ref buffer) = self.buffer {
+            buffer.clear();
+        }
+    }
+
+    /// Connect, see `RecognitionSession::start`
+    pub fn start(&mut self) -> Result<()> {
+        self.resize_buffer();
+        self.session.start()
+    }
+
+    /// Disconnect, see `RecognitionSession::stop`
+    ///
+    /// Buffered audio is dropped.
+    pub fn stop(&mut self) -> Result<()> {
+        self.clear_buffer();
+        self.sent = false;
+        self.session.stop()
+    }
+
+    pub fn session(&self) -> &RecognitionSession<'a> {
+        &self.session
+    }
+
+    pub fn session_mut(&mut self) -> &mut RecognitionSession<'a> {
+        &mut self.session
+    }
+
+    pub fn into_inner(self) -> RecognitionSession<'a> {
+        self.session
+    }
+
+    /// Size the buffer for `preroll` of audio in the input layout
+    fn resize_buffer(&mut self) {
+        let samples = self.session.input_spec().bytes_for(self.preroll) / 2;
+        self.buffer = if samples == 0 {
+            None
+        } else {
+            Some(PreRoll::with_capacity(samples))
+        };
+    }
+}
@@ -0,0 +1,202 @@
+//! Startup / readiness checks
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! let report = speech.health_check();
+//! if !report.is_healthy() {
+//!     println!("{}", report);
+//! }
+//! ```
+
+use super::*;
+
+/// Outcome of a single health check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Failed(String),
+    /// Not run because an earlier check failed
+    Skipped,
+}
+
+/// Result and latency of a single health check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub status: CheckStatus,
+    pub latency: Duration,
+}
+
+impl Check {
+    fn ok(latency: Duration) -> Self {
+        Check {
+            status: CheckStatus::Ok,
+            latency,
+        }
+    }
+
+    fn failed<T: ToString>(reason: T, latency: Duration) -> Self {
+        Check {
+            status: CheckStatus::Failed(reason.to_string()),
+            latency,
+        }
+    }
+
+    fn skipped() -> Self {
+        Check {
+            status: CheckStatus::Skipped,
+            latency: Duration::from_secs(0),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == CheckStatus::Ok
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.status {
+            CheckStatus::Ok => write!(
+                f,
+                "ok ({} ms)",
+                self.latency.as_secs() * 1000 + u64::from(self.latency.subsec_millis())
+            ),
+            CheckStatus::Failed(ref reason) => write!(f, "failed: {}", reason),
+            CheckStatus::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// Structured report returned by `Speech::health_check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// The subscription key was accepted by the token service
+    pub subscription_key: Check,
+    /// A token was issued
+    pub token: Check,
+    /// The recognition endpoint answered
+    pub recognition: Check,
+    /// The text-to-speech endpoint answered
+    pub synthesis: Check,
+}
+
+impl HealthReport {
+    /// Whether every check passed
+    pub fn is_healthy(&self) -> bool {
+        self.subscription_key.is_ok()
+            && self.token.is_ok()
+            && self.recognition.is_ok()
+            && self.synthesis.is_ok()
+    }
+}
+
+impl Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "subscription key: {}", self.subscription_key)?;
+        writeln!(f, "token: {}", self.token)?;
+        writeln!(f, "recognition: {}", self.recognition)?;
+        write!(f, "synthesis: {}", self.synthesis)
+    }
+}
+
+impl Speech {
+    /// Validate the subscription key, token issuance and reachability of the
+    /// configured recognition and text-to-speech endpoints
+    ///
+    /// A successful check leaves the freshly issued token in `self.token`.
+    pub fn health_check(&mut self) -> HealthReport {
+        let start = Instant::now();
+        let (subscription_key, token) = match self.fetch_token() {
+            Ok((_, _, Some(_))) => (Check::ok(start.elapsed()), Check::ok(start.elapsed())),
+            Ok((_, status, None)) => (
+                Check::ok(start.elapsed()),
+                Check::failed(format!("no token issued ({})", status), start.elapsed()),
+            ),
+            Err(err) => match *err.kind() {
+                ErrorKind::Unauthorized(status) => (
+                    Check::failed(format!("rejected with {}", status), start.elapsed()),
+                    Check::skipped(),
+                ),
+                ErrorKind::HttpStatus(_)
+                | ErrorKind::RateLimited(_)
+                | ErrorKind::ServiceUnavailable(_) => (
+                    Check::ok(start.elapsed()),
+                    Check::failed(err.to_string(), start.elapsed()),
+                ),
+                _ => (
+                    Check::failed(err.to_string(), start.elapsed()),
+                    Check::skipped(),
+                ),
+            },
+        };
+
+        if !token.is_ok() {
+            return HealthReport {
+                subscription_key,
+                token,
+                recognition: Check::skipped(),
+                synthesis: Check::skipped(),
+            };
+        }
+
+        let endpoints = self.endpoints();
+        let endpoint_id = if self.is_custom_speech {
+            Some(self.endpoint_id.as_str())
+        } else {
+            None
+        };
+        let recognition_url = endpoints.recognition_url(
+            Mode::Interactive(Language::EnglishUnitedStates),
+            Format::Simple,
+            endpoint_id,
+            self.params,
+        );
+        let recognition = self.probe(&recognition_url);
+        let synthesis = self.probe(&endpoints.synthesis);
+
+        HealthReport {
+            subscription_key,
+            token,
+            recognition,
+            synthesis,
+        }
+    }
+
+    /// Send an empty authorized request and treat any non-5xx answer as reachable
+    ///
+    /// Probes go straight to the transport so they don't show up in the
+    /// client's stats and metrics.
+    fn probe(&self, url: &str) -> Check {
+        let start = Instant::now();
+        let uri: Uri = match url.parse() {
+            Ok(uri) => uri,
+            Err(err) => return Check::failed(err, start.elapsed()),
+        };
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header(
+                "Authorization",
+                format!("Bearer {}", self.token.lock().unwrap().clone()).as_str(),
+            ).header("Content-Length", "0")
+            .body(Body::empty())
+            .unwrap();
+        self.stamp_correlation_id(&mut request);
+
+        let options = SendOptions {
+            timeout: self.timeout,
+            cancel: None,
+        };
+        match self.transport.send_with(request, &options) {
+            Ok((_, status, _)) if status.is_server_error() => {
+                Check::failed(format!("server error {}", status), start.elapsed())
+            }
+            Ok(_) => Check::ok(start.elapsed()),
+            Err(err) => Check::failed(err, start.elapsed()),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Intent recognition with LUIS
+//!
+//! With a LUIS app set through `Websocket::set_intent_app` or
+//! `RecognitionSession::set_intent_app`, the service runs every recognized
+//! phrase through the app and sends the result in a "response" message,
+//! delivered to `Handler::on_intent` right after the phrase.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::intent::IntentResult;
+//! use bing_rs::speech::websocket::Handler;
+//!
+//! struct Commands;
+//!
+//! impl Handler for Commands {
+//!     fn on_intent(&mut self, result: IntentResult) {
+//!         if let Some(intent) = result.top_intent() {
+//!             println!("{} ({})", intent.intent, intent.score);
+//!         }
+//!         for entity in &result.entities {
+//!             println!("  {}: {}", entity.kind, entity.entity);
+//!         }
+//!     }
+//! }
+//! ```
+
+// serde_json
+use serde_json::Value;
+
+/// LUIS app the recognized phrases are sent to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntentApp {
+    pub app_id: String,
+    /// LUIS subscription key of the app
+    pub key: String,
+}
+
+impl IntentApp {
+    pub fn new(app_id: &str, key: &str) -> Self {
+        IntentApp {
+            app_id: app_id.to_string(),
+            key: key.to_string(),
+        }
+    }
+
+    /// Query parameters selecting the app in recognition URLs
+    pub fn query_pairs(&self) -> [(&'static str, &str); 2] {
+        [
+            ("luisAppId", self.app_id.as_str()),
+            ("luisSubscriptionKey", self.key.as_str()),
+        ]
+    }
+}
+
+/// LUIS result for a recognized phrase
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IntentResult {
+    /// Phrase text LUIS received
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub top_scoring_intent: Option<Intent>,
+    /// Every intent with its score, if the app is set to return them all
+    #[serde(default)]
+    pub intents: Vec<Intent>,
+    #[serde(default)]
+    pub entities: Vec<Entity>,
+}
+
+impl IntentResult {
+    /// Intent with the highest score
+    pub fn top_intent(&self) -> Option<&Intent> {
+        if let Some(ref intent) = self.top_scoring_intent {
+            return Some(intent);
+        }
+        let mut best: Option<&Intent> = None;
+        for intent in &self.intents {
+            if best.map_or(true, |best| intent.score > best.score) {
+                best = Some(intent);
+            }
+        }
+        best
+    }
+
+    /// Entities of type `kind`, e.g. "builtin.number"
+    pub fn entities_of(&self, kind: &str) -> Vec<&Entity> {
+        self.entities
+            .iter()
+            .filter(|entity| entity.kind == kind)
+            .collect()
+    }
+}
+
+/// Intent and how confident LUIS is that the phrase expresses it
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Intent {
+    pub intent: String,
+    /// Confidence between 0 and 1
+    #[serde(default)]
+    pub score: f64,
+}
+
+/// Entity found in the phrase
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Entity {
+    /// Entity text as found in the phrase
+    pub entity: String,
+    /// Entity type, e.g. "Room" or "builtin.datetimeV2.date"
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Character offsets of the entity in the phrase, inclusive
+    #[serde(default)]
+    pub start_index: usize,
+    #[serde(default)]
+    pub end_index: usize,
+    #[serde(default)]
+    pub score: Option<f64>,
+    /// Normalized value of built-in entities, whose shape depends on the
+    /// type
+    #[serde(default)]
+    pub resolution: Option<Value>,
+}
@@ -0,0 +1,351 @@
+//! Keyword spotting on recognition results
+//!
+//! A `KeywordMatcher` compiles a set of keywords into an Aho-Corasick
+//! automaton. Wrap a session's `Handler` in a `KeywordSpotter` to get a
+//! callback whenever a keyword appears in a hypothesis or phrase, e.g. for
+//! command spotting or compliance monitoring.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::sync::{Arc, Mutex};
+//! use bing_rs::speech::keywords::*;
+//! use bing_rs::speech::websocket::Handler;
+//!
+//! struct MyHandler;
+//! impl Handler for MyHandler {}
+//!
+//! let matcher = KeywordMatcher::new(&["cancel my order", "refund"], MatchMode::Fuzzy).unwrap();
+//! let spotter = KeywordSpotter::new(matcher, MyHandler, |found: &KeywordMatch| {
+//!     println!("heard {:?}", found.keyword);
+//! });
+//! let handler = Arc::new(Mutex::new(spotter));
+//! // ws.connect(token, mode, format, false, "", handler)
+//! ```
+
+// std
+use std::collections::HashSet;
+
+// aho-corasick
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+
+// internal
+use super::intent::IntentResult;
+use super::websocket::Handler;
+use super::{Hypothesis, Phrase};
+use errors::*;
+
+/// How keywords are compared with recognized text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchMode {
+    /// Case-sensitive match on the text as recognized
+    Exact,
+    /// Case-insensitive match ignoring punctuation and repeated whitespace,
+    /// so "hey cortana" matches "Hey, Cortana!"
+    Fuzzy,
+}
+
+/// Result the keyword was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchSource {
+    Hypothesis,
+    Phrase,
+}
+
+/// Occurrence of a keyword
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeywordMatch {
+    pub keyword: String,
+    /// Index of the keyword in the list given to `KeywordMatcher::new`
+    pub index: usize,
+    /// Byte range of the match in the (normalized, for fuzzy matching) text
+    pub start: usize,
+    pub end: usize,
+    pub source: MatchSource,
+}
+
+/// Set of keywords compiled for fast matching
+#[derive(Debug, Clone)]
+pub struct KeywordMatcher {
+    automaton: AhoCorasick,
+    keywords: Vec<String>,
+    mode: MatchMode,
+}
+
+impl KeywordMatcher {
+    pub fn new<I, S>(keywords: I, mode: MatchMode) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let keywords: Vec<String> = keywords
+            .into_iter()
+            .map(|keyword| keyword.as_ref().to_string())
+            .collect();
+        let patterns: Vec<String> = keywords
+            .iter()
+            .map(|keyword| normalize(keyword, mode))
+            .collect();
+        let automaton = match AhoCorasickBuilder::new()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&patterns)
+        {
+            Ok(automaton) => automaton,
+            Err(err) => bail!(ErrorKind::InvalidRequest(format!(
+                "invalid keyword set: {}",
+                err
+            ))),
+        };
+
+        Ok(KeywordMatcher {
+            automaton,
+            keywords,
+            mode,
+        })
+    }
+
+    /// Keywords as given to `new`
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// All whole-word keyword occurrences in `text`
+    pub fn find(&self, text: &str, source: MatchSource) -> Vec<KeywordMatch> {
+        let text = normalize(text, self.mode);
+        self.automaton
+            .find_iter(&text)
+            .filter(|found| is_word_boundary(&text, found.start(), found.end()))
+            .map(|found| {
+                let index = found.pattern().as_usize();
+                KeywordMatch {
+                    keyword: self.keywords[index].clone(),
+                    index,
+                    start: found.start(),
+                    end: found.end(),
+                    source,
+                }
+            }).collect()
+    }
+}
+
+/// `Handler` wrapper firing a callback when keywords are recognized
+///
+/// Successive hypotheses repeat the utterance recognized so far, so each
+/// occurrence fires only once per utterance: from the first hypothesis it
+/// appears in, or from the final phrase if no hypothesis contained it.
+pub struct KeywordSpotter<H, F> {
+    matcher: KeywordMatcher,
+    inner: H,
+    on_keyword: F,
+    fired: HashSet<(usize, usize)>,
+}
+
+impl<H, F> KeywordSpotter<H, F>
+where
+    H: Handler,
+    F: FnMut(&KeywordMatch),
+{
+    pub fn new(matcher: KeywordMatcher, inner: H, on_keyword: F) -> Self {
+        KeywordSpotter {
+            matcher,
+            inner,
+            on_keyword,
+            fired: HashSet::new(),
+        }
+    }
+
+    /// Wrapped handler
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut H {
+        &mut self.inner
+    }
+
+    fn spot(&mut self, text: &str, source: MatchSource) {
+        // Identify occurrences by keyword and how many times it was seen
+        // before, since positions shift as hypotheses are revised
+        let mut seen = vec![0; self.matcher.keywords.len()];
+        for found in self.matcher.find(text, source) {
+            let occurrence = seen[found.index];
+            seen[found.index] += 1;
+            if self.fired.insert((found.index, occurrence)) {
+                (self.on_keyword)(&found);
+            }
+        }
+    }
+}
+
+impl<H, F> Handler for KeywordSpotter<H, F>
+where
+    H: Handler,
+    F: FnMut(&KeywordMatch),
+{
+    fn on_turn_start(&mut self) {
+        self.inner.on_turn_start();
+    }
+
+    fn on_turn_end(&mut self) {
+        self.fired.clear();
+        self.inner.on_turn_end();
+    }
+
+    fn on_speech_start(&mut self) {
+        self.inner.on_speech_start();
+    }
+
+    fn on_speech_end(&mut self) {
+        self.inner.on_speech_end();
+    }
+
+    fn on_local_speech_start(&mut self) {
+        self.inner.on_local_speech_start();
+    }
+
+    fn on_local_speech_end(&mut self) {
+        self.inner.on_local_speech_end();
+    }
+
+    fn on_speech_hypothesis(&mut self, hypothesis: Hypothesis) {
+        self.spot(&hypothesis.text, MatchSource::Hypothesis);
+        self.inner.on_speech_hypothesis(hypothesis);
+    }
+
+    fn on_speech_phrase(&mut self, phrase: Phrase) {
+        if let Some(text) = phrase.text() {
+            self.spot(text, MatchSource::Phrase);
+        }
+        self.fired.clear();
+        self.inner.on_speech_phrase(phrase);
+    }
+
+    fn on_speaker_change(&mut self, speaker_id: &str) {
+        self.inner.on_speaker_change(speaker_id);
+    }
+
+    fn on_intent(&mut self, result: IntentResult) {
+        self.inner.on_intent(result);
+    }
+
+    fn on_raw_message(&mut self, path: &str, headers: &[(&str, &str)], body: &str) {
+        self.inner.on_raw_message(path, headers, body);
+    }
+
+    fn on_connect(&mut self, connection_id: &str) {
+        self.inner.on_connect(connection_id);
+    }
+
+    fn on_disconnect(&mut self, code: u16, reason: &str) {
+        self.inner.on_disconnect(code, reason);
+    }
+
+    fn on_error(&mut self, err: &Error) {
+        self.inner.on_error(err);
+    }
+
+    fn on_reconnect(&mut self, attempt: u32) {
+        self.fired.clear();
+        self.inner.on_reconnect(attempt);
+    }
+}
+
+fn normalize(text: &str, mode: MatchMode) -> String {
+    match mode {
+        MatchMode::Exact => text.to_string(),
+        MatchMode::Fuzzy => text
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '\'' { c } else { ' ' })
+            .collect::<String>()
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_lowercase(),
+    }
+}
+
+fn is_word_boundary(text: &str, start: usize, end: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    !before.map_or(false, char::is_alphanumeric) && !after.map_or(false, char::is_alphanumeric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Ignore;
+    impl Handler for Ignore {}
+
+    fn hypothesis(text: &str) -> Hypothesis {
+        Hypothesis {
+            text: text.to_string(),
+            offset: 0.0,
+            duration: 0.0,
+        }
+    }
+
+    #[test]
+    fn exact_matches_text_as_recognized() {
+        let matcher = KeywordMatcher::new(&["Refund"], MatchMode::Exact).unwrap();
+        let found = matcher.find("I want a Refund now", MatchSource::Phrase);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].start, found[0].end), (9, 15));
+        assert_eq!(found[0].source, MatchSource::Phrase);
+        assert!(matcher
+            .find("I want a refund now", MatchSource::Phrase)
+            .is_empty());
+    }
+
+    #[test]
+    fn fuzzy_ignores_case_and_punctuation() {
+        let matcher = KeywordMatcher::new(&["hey cortana"], MatchMode::Fuzzy).unwrap();
+        let found = matcher.find("Hey,  Cortana! What's up?", MatchSource::Hypothesis);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].keyword, "hey cortana");
+        assert_eq!((found[0].start, found[0].end), (0, 11));
+    }
+
+    #[test]
+    fn only_matches_whole_words() {
+        let matcher = KeywordMatcher::new(&["cat"], MatchMode::Exact).unwrap();
+        let found = matcher.find("concatenate cat cats", MatchSource::Phrase);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].start, found[0].end), (12, 15));
+    }
+
+    #[test]
+    fn prefers_leftmost_longest_keyword() {
+        let matcher =
+            KeywordMatcher::new(&["cancel", "cancel my order"], MatchMode::Fuzzy).unwrap();
+        let found = matcher.find("Please cancel my order.", MatchSource::Phrase);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].index, 1);
+        assert_eq!(found[0].keyword, "cancel my order");
+    }
+
+    #[test]
+    fn spots_each_occurrence_once_per_utterance() {
+        let matcher = KeywordMatcher::new(&["refund"], MatchMode::Fuzzy).unwrap();
+        let mut heard = Vec::new();
+        {
+            let mut spotter = KeywordSpotter::new(matcher, Ignore, |found: &KeywordMatch| {
+                heard.push(found.source)
+            });
+            spotter.on_speech_hypothesis(hypothesis("refund"));
+            spotter.on_speech_hypothesis(hypothesis("refund please"));
+            spotter.on_speech_hypothesis(hypothesis("refund please refund"));
+            spotter.spot("Refund, please. Refund!", MatchSource::Phrase);
+            spotter.on_turn_end();
+            spotter.on_speech_hypothesis(hypothesis("refund"));
+        }
+        assert_eq!(
+            heard,
+            vec![
+                MatchSource::Hypothesis,
+                MatchSource::Hypothesis,
+                MatchSource::Hypothesis,
+            ]
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! Language support per mode and candidate languages for language
+//! identification
+//!
+//! # Examples
+//!
+//! ```
+//! use bing_rs::speech::*;
+//!
+//! let language: Language = "ko-KR".parse().unwrap();
+//! assert!(language.supports(ModeKind::Dictation));
+//! assert!(!language.supports(ModeKind::Conversation));
+//!
+//! let candidates: LanguageSet = "en-US,de-DE".parse().unwrap();
+//! let mode = Mode::Detect(candidates);
+//! assert_eq!(mode.to_spec(), "detect:de-DE,en-US");
+//! ```
+
+use super::*;
+
+impl Language {
+    /// Whether the service recognizes this language in `kind` mode
+    pub fn supports(self, kind: ModeKind) -> bool {
+        match kind {
+            ModeKind::Conversation => ConversationLanguage::try_from(self).is_ok(),
+            ModeKind::Interactive | ModeKind::Dictation | ModeKind::Detect => true,
+        }
+    }
+
+    /// Languages supported in `kind` mode
+    pub fn supported_by(kind: ModeKind) -> Vec<Language> {
+        Language::all()
+            .iter()
+            .cloned()
+            .filter(|language| language.supports(kind))
+            .collect()
+    }
+}
+
+/// Set of languages the service chooses from with `Mode::Detect`
+///
+/// Stored as a bit set so `Mode` stays `Copy`. Iteration follows the
+/// declaration order of `Language`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LanguageSet {
+    bits: u64,
+}
+
+impl LanguageSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `language` to the set
+    pub fn with(mut self, language: Language) -> Self {
+        self.insert(language);
+        self
+    }
+
+    pub fn insert(&mut self, language: Language) {
+        self.bits |= bit(language);
+    }
+
+    pub fn remove(&mut self, language: Language) {
+        self.bits &= !bit(language);
+    }
+
+    pub fn contains(&self, language: Language) -> bool {
+        self.bits & bit(language) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// Languages in the set
+    pub fn iter(&self) -> ::std::vec::IntoIter<Language> {
+        Language::all()
+            .iter()
+            .cloned()
+            .filter(|&language| self.contains(language))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// First language of the set, sent as the primary language
+    pub fn first(&self) -> Option<Language> {
+        self.iter().next()
+    }
+}
+
+fn bit(language: Language) -> u64 {
+    1 << (language as u64)
+}
+
+impl ::std::iter::FromIterator<Language> for LanguageSet {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Language>,
+    {
+        let mut set = LanguageSet::new();
+        for language in iter {
+            set.insert(language);
+        }
+        set
+    }
+}
+
+impl<'a> From<&'a [Language]> for LanguageSet {
+    fn from(languages: &'a [Language]) -> Self {
+        languages.iter().cloned().collect()
+    }
+}
+
+impl Display for LanguageSet {
+    /// Comma-separated language codes, e.g. "de-DE,en-US"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let codes: Vec<String> = self.iter().map(|language| language.to_string()).collect();
+        write!(f, "{}", codes.join(","))
+    }
+}
+
+impl FromStr for LanguageSet {
+    type Err = Error;
+
+    /// Parse comma-separated language codes such as "en-US,de-DE"
+    fn from_str(s: &str) -> Result<Self> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+            .map(|code| code.parse::<Language>())
+            .collect()
+    }
+}
+
+impl<'a> TryFrom<&'a str> for LanguageSet {
+    type Error = Error;
+
+    fn try_from(s: &'a str) -> Result<Self> {
+        s.parse()
+    }
+}
@@ -0,0 +1,215 @@
+//! Audio formats returned by text-to-speech
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//!
+//! let speech = Speech::new("your_subscription_key").unwrap();
+//! let format = OutputFormat::Audio16Khz128KbitrateMonoMp3;
+//! let (_, _, audio) = speech
+//!     .synthesize_with_format("Hello World!", voice::en_us::JESSA_RUS, format)
+//!     .unwrap();
+//! ```
+
+use super::*;
+
+/// Value of the `X-Microsoft-OutputFormat` header sent with a synthesis
+/// request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputFormat {
+    Raw8Khz8BitMonoMulaw,
+    Riff8Khz8BitMonoMulaw,
+    Raw16Khz16BitMonoPcm,
+    Riff16Khz16BitMonoPcm,
+    Raw24Khz16BitMonoPcm,
+    Riff24Khz16BitMonoPcm,
+    Raw16Khz16BitMonoTruesilk,
+    Raw24Khz16BitMonoTruesilk,
+    Riff16Khz16KbpsMonoSiren,
+    Audio16Khz16KbpsMonoSiren,
+    Audio16Khz32KbitrateMonoMp3,
+    Audio16Khz64KbitrateMonoMp3,
+    Audio16Khz128KbitrateMonoMp3,
+    Audio24Khz48KbitrateMonoMp3,
+    Audio24Khz96KbitrateMonoMp3,
+    Audio24Khz160KbitrateMonoMp3,
+    Ogg16Khz16BitMonoOpus,
+    Ogg24Khz16BitMonoOpus,
+    Webm16Khz16BitMonoOpus,
+    Webm24Khz16BitMonoOpus,
+}
+
+const OUTPUT_FORMATS: &[(OutputFormat, &str, u32)] = &[
+    (
+        OutputFormat::Raw8Khz8BitMonoMulaw,
+        "raw-8khz-8bit-mono-mulaw",
+        8000,
+    ),
+    (
+        OutputFormat::Riff8Khz8BitMonoMulaw,
+        "riff-8khz-8bit-mono-mulaw",
+        8000,
+    ),
+    (
+        OutputFormat::Raw16Khz16BitMonoPcm,
+        "raw-16khz-16bit-mono-pcm",
+        16000,
+    ),
+    (
+        OutputFormat::Riff16Khz16BitMonoPcm,
+        "riff-16khz-16bit-mono-pcm",
+        16000,
+    ),
+    (
+        OutputFormat::Raw24Khz16BitMonoPcm,
+        "raw-24khz-16bit-mono-pcm",
+        24000,
+    ),
+    (
+        OutputFormat::Riff24Khz16BitMonoPcm,
+        "riff-24khz-16bit-mono-pcm",
+        24000,
+    ),
+    (
+        OutputFormat::Raw16Khz16BitMonoTruesilk,
+        "raw-16khz-16bit-mono-truesilk",
+        16000,
+    ),
+    (
+        OutputFormat::Raw24Khz16BitMonoTruesilk,
+        "raw-24khz-16bit-mono-truesilk",
+        24000,
+    ),
+    (
+        OutputFormat::Riff16Khz16KbpsMonoSiren,
+        "riff-16khz-16kbps-mono-siren",
+        16000,
+    ),
+    (
+        OutputFormat::Audio16Khz16KbpsMonoSiren,
+        "audio-16khz-16kbps-mono-siren",
+        16000,
+    ),
+    (
+        OutputFormat::Audio16Khz32KbitrateMonoMp3,
+        "audio-16khz-32kbitrate-mono-mp3",
+        16000,
+    ),
+    (
+        OutputFormat::Audio16Khz64KbitrateMonoMp3,
+        "audio-16khz-64kbitrate-mono-mp3",
+        16000,
+    ),
+    (
+        OutputFormat::Audio16Khz128KbitrateMonoMp3,
+        "audio-16khz-128kbitrate-mono-mp3",
+        16000,
+    ),
+    (
+        OutputFormat::Audio24Khz48KbitrateMonoMp3,
+        "audio-24khz-48kbitrate-mono-mp3",
+        24000,
+    ),
+    (
+        OutputFormat::Audio24Khz96KbitrateMonoMp3,
+        "audio-24khz-96kbitrate-mono-mp3",
+        24000,
+    ),
+    (
+        OutputFormat::Audio24Khz160KbitrateMonoMp3,
+        "audio-24khz-160kbitrate-mono-mp3",
+        24000,
+    ),
+    (
+        OutputFormat::Ogg16Khz16BitMonoOpus,
+        "ogg-16khz-16bit-mono-opus",
+        16000,
+    ),
+    (
+        OutputFormat::Ogg24Khz16BitMonoOpus,
+        "ogg-24khz-16bit-mono-opus",
+        24000,
+    ),
+    (
+        OutputFormat::Webm16Khz16BitMonoOpus,
+        "webm-16khz-16bit-mono-opus",
+        16000,
+    ),
+    (
+        OutputFormat::Webm24Khz16BitMonoOpus,
+        "webm-24khz-16bit-mono-opus",
+        24000,
+    ),
+];
+
+impl OutputFormat {
+    /// Every known output format
+    pub fn all() -> Vec<OutputFormat> {
+        OUTPUT_FORMATS
+            .iter()
+            .map(|&(format, _, _)| format)
+            .collect()
+    }
+
+    /// Header value, e.g. "riff-16khz-16bit-mono-pcm"
+    pub fn name(self) -> &'static str {
+        self.entry().1
+    }
+
+    /// Sample rate of the decoded audio in Hz
+    pub fn sample_rate(self) -> u32 {
+        self.entry().2
+    }
+
+    /// Whether the audio is uncompressed PCM without a container
+    pub fn is_raw_pcm(self) -> bool {
+        match self {
+            OutputFormat::Raw16Khz16BitMonoPcm | OutputFormat::Raw24Khz16BitMonoPcm => true,
+            _ => false,
+        }
+    }
+
+    fn entry(self) -> &'static (OutputFormat, &'static str, u32) {
+        OUTPUT_FORMATS
+            .iter()
+            .find(|&&(format, _, _)| format == self)
+            .unwrap()
+    }
+}
+
+impl Default for OutputFormat {
+    /// The format `synthesize` has always requested
+    fn default() -> Self {
+        OutputFormat::Raw16Khz16BitMonoPcm
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    /// Parse a header value such as "ogg-24khz-16bit-mono-opus", ignoring case
+    fn from_str(s: &str) -> Result<Self> {
+        match OUTPUT_FORMATS
+            .iter()
+            .find(|&&(_, name, _)| s.eq_ignore_ascii_case(name))
+        {
+            Some(&(format, _, _)) => Ok(format),
+            None => bail!(ErrorKind::UnknownVariant("OutputFormat", s.to_string())),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for OutputFormat {
+    type Error = Error;
+
+    fn try_from(s: &'a str) -> Result<Self> {
+        s.parse()
+    }
+}
@@ -0,0 +1,182 @@
+//! OS and device detection for the `speech.config` payload
+//!
+//! Detection reads well-known system files or asks small system tools
+//! (`sw_vers`, `getprop`, `uname`, ...). Anything that can't be determined
+//! is reported as "Unknown".
+
+use std::fs;
+use std::process::Command;
+
+const UNKNOWN: &str = "Unknown";
+
+/// Operating system reported to the service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OsInfo {
+    /// Platform family, e.g. "Linux" or "macOS"
+    pub platform: String,
+    /// Distribution or product name, e.g. "Ubuntu"
+    pub name: String,
+    pub version: String,
+}
+
+/// Device reported to the service
+///
+/// Desktop hardware is detected where possible; embedded and mobile
+/// integrations usually know better and can pass their own to
+/// `speech_config_with_device`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub manufacturer: String,
+    pub model: String,
+    pub version: String,
+}
+
+impl Default for DeviceInfo {
+    fn default() -> Self {
+        DeviceInfo {
+            manufacturer: UNKNOWN.to_string(),
+            model: UNKNOWN.to_string(),
+            version: UNKNOWN.to_string(),
+        }
+    }
+}
+
+/// Platform family of the compilation target
+pub fn platform() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "Windows"
+    } else if cfg!(target_os = "macos") {
+        "macOS"
+    } else if cfg!(target_os = "ios") {
+        "iOS"
+    } else if cfg!(target_os = "android") {
+        "Android"
+    } else if cfg!(target_os = "linux") {
+        "Linux"
+    } else if cfg!(target_os = "freebsd") {
+        "FreeBSD"
+    } else if cfg!(target_os = "dragonfly") {
+        "DragonflyBSD"
+    } else if cfg!(target_os = "bitrig") {
+        "Bitrig"
+    } else if cfg!(target_os = "openbsd") {
+        "OpenBSD"
+    } else if cfg!(target_os = "netbsd") {
+        "NetBSD"
+    } else {
+        UNKNOWN
+    }
+}
+
+/// Detect the running operating system
+pub fn detect_os() -> OsInfo {
+    let (name, version) = if cfg!(target_os = "macos") {
+        (
+            command(&["sw_vers", "-productName"]),
+            command(&["sw_vers", "-productVersion"]),
+        )
+    } else if cfg!(target_os = "android") {
+        (
+            Some("Android".to_string()),
+            command(&["getprop", "ro.build.version.release"]),
+        )
+    } else if cfg!(target_os = "linux") {
+        let release = fs::read_to_string("/etc/os-release").unwrap_or_default();
+        (
+            os_release_field(&release, "NAME"),
+            os_release_field(&release, "VERSION_ID").or_else(|| command(&["uname", "-r"])),
+        )
+    } else if cfg!(target_os = "windows") {
+        (
+            Some("Windows".to_string()),
+            command(&["cmd", "/C", "ver"]).and_then(|ver| windows_version(&ver)),
+        )
+    } else if cfg!(unix) {
+        (command(&["uname", "-s"]), command(&["uname", "-r"]))
+    } else {
+        (None, None)
+    };
+
+    OsInfo {
+        platform: platform().to_string(),
+        name: name.unwrap_or_else(|| UNKNOWN.to_string()),
+        version: version.unwrap_or_else(|| UNKNOWN.to_string()),
+    }
+}
+
+/// Detect the device hardware
+pub fn detect_device() -> DeviceInfo {
+    let (manufacturer, model, version) = if cfg!(target_os = "macos") {
+        (
+            Some("Apple".to_string()),
+            command(&["sysctl", "-n", "hw.model"]),
+            None,
+        )
+    } else if cfg!(target_os = "android") {
+        (
+            command(&["getprop", "ro.product.manufacturer"]),
+            command(&["getprop", "ro.product.model"]),
+            command(&["getprop", "ro.build.id"]),
+        )
+    } else if cfg!(target_os = "linux") {
+        (
+            dmi("sys_vendor"),
+            dmi("product_name"),
+            dmi("product_version"),
+        )
+    } else {
+        (None, None, None)
+    };
+
+    let default = DeviceInfo::default();
+    DeviceInfo {
+        manufacturer: manufacturer.unwrap_or(default.manufacturer),
+        model: model.unwrap_or(default.model),
+        version: version.unwrap_or(default.version),
+    }
+}
+
+/// Trimmed standard output of a successful command, if not empty
+fn command(args: &[&str]) -> Option<String> {
+    let output = Command::new(args[0]).args(&args[1..]).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    non_empty(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn dmi(field: &str) -> Option<String> {
+    fs::read_to_string(format!("/sys/class/dmi/id/{}", field))
+        .ok()
+        .and_then(|value| non_empty(&value))
+}
+
+/// Value of `key` in an os-release(5) file
+fn os_release_field(release: &str, key: &str) -> Option<String> {
+    release
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(k), Some(v)) if k.trim() == key => Some(v),
+                _ => None,
+            }
+        }).next()
+        .and_then(|value| non_empty(value.trim().trim_matches('"')))
+}
+
+/// Version out of "Microsoft Windows [Version 10.0.19045.3448]"
+fn windows_version(ver: &str) -> Option<String> {
+    let start = ver.find("Version ")? + "Version ".len();
+    let end = ver[start..].find(']').map_or(ver.len(), |end| start + end);
+    non_empty(&ver[start..end])
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
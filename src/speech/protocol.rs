@@ -0,0 +1,257 @@
+//! Pure builders and parsers for Bing Speech websocket messages
+//!
+//! The parsers operate on untrusted server input and return errors instead
+//! of panicking, which is what the `fuzz/` targets exercise. The builders
+//! and parsers are benchmarked in `benches/hot_paths.rs`.
+
+// serde_json
+use serde_json;
+
+// internal
+use errors::*;
+use speech::websocket::ServerEvent;
+use speech::Phrase;
+
+/// Text message split into its headers and body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextMessage<'a> {
+    pub headers: Vec<(&'a str, &'a str)>,
+    pub body: &'a str,
+}
+
+impl<'a> TextMessage<'a> {
+    /// Value of the header `name`, compared case-insensitively
+    pub fn header(&self, name: &str) -> Option<&'a str> {
+        self.headers
+            .iter()
+            .find(|&&(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|&(_, value)| value)
+    }
+
+    /// Value of the `Path` header
+    pub fn path(&self) -> Option<&'a str> {
+        self.header("Path")
+    }
+}
+
+/// Binary message split into its headers and payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryMessage<'a> {
+    pub headers: Vec<(&'a str, &'a str)>,
+    pub payload: &'a [u8],
+}
+
+impl<'a> BinaryMessage<'a> {
+    /// Value of the `Path` header, compared case-insensitively
+    pub fn path(&self) -> Option<&'a str> {
+        self.headers
+            .iter()
+            .find(|&&(key, _)| key.eq_ignore_ascii_case("Path"))
+            .map(|&(_, value)| value)
+    }
+}
+
+/// Assemble a text message from its headers and body
+pub fn build_text_message(
+    path: &str,
+    request_id: &str,
+    timestamp: &str,
+    content_type: &str,
+    body: &str,
+) -> String {
+    format!(
+        "Path: {}\r\nX-RequestId: {}\r\nX-Timestamp: {}\r\nContent-Type: {}\r\n\r\n{}",
+        path, request_id, timestamp, content_type, body
+    )
+}
+
+/// Assemble a binary `audio` message carrying `audio`
+///
+/// An empty `audio` marks the end of the audio stream.
+pub fn build_audio_message(request_id: &str, timestamp: &str, audio: &[u8]) -> Vec<u8> {
+    let header = format!(
+        "Path: {}\r\nX-RequestId: {}\r\nX-Timestamp: {}\r\nContent-Type: {}\r\n\r\n",
+        "audio", request_id, timestamp, "audio/x-wav",
+    );
+
+    let header_len = header.len() as u16;
+    let mut data = Vec::with_capacity(2 + header.len() + audio.len());
+    data.push(((header_len >> 8) & 0xFF) as u8);
+    data.push((header_len & 0xFF) as u8);
+    data.extend_from_slice(header.as_bytes());
+    data.extend_from_slice(audio);
+    data
+}
+
+/// Split a text message into headers and body
+pub fn parse_text_message(text: &str) -> Result<TextMessage> {
+    let mut sections = text.splitn(2, "\r\n\r\n");
+    let header = sections.next().unwrap_or("");
+    let body = match sections.next() {
+        Some(body) => body,
+        None => bail!(ErrorKind::Protocol(
+            "text message without header terminator".to_string()
+        )),
+    };
+
+    Ok(TextMessage {
+        headers: parse_headers(header)?,
+        body,
+    })
+}
+
+/// Split a binary message into headers and payload
+///
+/// The message starts with the header length as a 2-byte big-endian integer,
+/// followed by the UTF-8 header text and the payload.
+pub fn parse_binary_message(data: &[u8]) -> Result<BinaryMessage> {
+    if data.len() < 2 {
+        bail!(ErrorKind::Protocol(
+            "binary message shorter than its header length".to_string()
+        ));
+    }
+
+    let header_len = ((data[0] as usize) << 8) | data[1] as usize;
+    let header_end = 2 + header_len;
+    if data.len() < header_end {
+        bail!(ErrorKind::Protocol(format!(
+            "binary message header length {} exceeds message length {}",
+            header_len,
+            data.len() - 2
+        )));
+    }
+
+    let header = match ::std::str::from_utf8(&data[2..header_end]) {
+        Ok(header) => header,
+        Err(_) => bail!(ErrorKind::Protocol(
+            "binary message header is not UTF-8".to_string()
+        )),
+    };
+
+    Ok(BinaryMessage {
+        headers: parse_headers(header.trim_end_matches("\r\n"))?,
+        payload: &data[header_end..],
+    })
+}
+
+/// Decode a text message from the server
+///
+/// Messages with a path this crate doesn't handle decode to
+/// `ServerEvent::Unknown`.
+pub fn parse_server_message(text: &str) -> Result<ServerEvent> {
+    let message = parse_text_message(text)?;
+    let path = match message.path() {
+        Some(path) => path,
+        None => bail!(ErrorKind::Protocol("message without Path header".to_string())),
+    };
+
+    Ok(match path {
+        "turn.start" => ServerEvent::TurnStart,
+        "turn.end" => ServerEvent::TurnEnd,
+        "speech.startDetected" => ServerEvent::SpeechStartDetected,
+        "speech.endDetected" => ServerEvent::SpeechEndDetected,
+        "speech.hypothesis" => ServerEvent::SpeechHypothesis(serde_json::from_str(message.body)?),
+        "speech.phrase" => {
+            let value: serde_json::Value = serde_json::from_str(message.body)?;
+            ServerEvent::SpeechPhrase(Phrase::from_json_value(&value)?)
+        }
+        "response" => ServerEvent::Intent(serde_json::from_str(message.body)?),
+        "translation.hypothesis" => {
+            ServerEvent::TranslationHypothesis(serde_json::from_str(message.body)?)
+        }
+        "translation.phrase" => ServerEvent::TranslationPhrase(serde_json::from_str(message.body)?),
+        "translation.synthesis.end" => {
+            ServerEvent::TranslationAudioEnd(serde_json::from_str(message.body)?)
+        }
+        _ => ServerEvent::Unknown,
+    })
+}
+
+fn parse_headers(header: &str) -> Result<Vec<(&str, &str)>> {
+    let mut headers = Vec::new();
+    for line in header.split("\r\n") {
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut kv = line.splitn(2, ':');
+        match (kv.next(), kv.next()) {
+            (Some(key), Some(value)) => headers.push((key.trim(), value.trim())),
+            _ => bail!(ErrorKind::Protocol(format!("malformed header line {:?}", line))),
+        }
+    }
+    Ok(headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_protocol_error<T>(result: Result<T>) -> bool {
+        match result {
+            Err(err) => match *err.kind() {
+                ErrorKind::Protocol(_) => true,
+                _ => false,
+            },
+            Ok(_) => false,
+        }
+    }
+
+    #[test]
+    fn text_message_round_trip() {
+        let text = build_text_message(
+            "speech.config",
+            "0123",
+            "2018-01-01T00:00:00.000Z",
+            "application/json",
+            "{\"a\":1}",
+        );
+        let message = parse_text_message(&text).unwrap();
+        assert_eq!(message.path(), Some("speech.config"));
+        assert_eq!(message.header("x-requestid"), Some("0123"));
+        assert_eq!(message.header("Content-Type"), Some("application/json"));
+        assert_eq!(message.body, "{\"a\":1}");
+    }
+
+    #[test]
+    fn audio_message_round_trip() {
+        let data = build_audio_message("0123", "2018-01-01T00:00:00.000Z", &[1, 2, 3]);
+        let message = parse_binary_message(&data).unwrap();
+        assert_eq!(message.path(), Some("audio"));
+        assert!(message.headers.contains(&("X-RequestId", "0123")));
+        assert_eq!(message.payload, &[1, 2, 3][..]);
+
+        let end = build_audio_message("0123", "2018-01-01T00:00:00.000Z", &[]);
+        assert!(parse_binary_message(&end).unwrap().payload.is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        assert!(is_protocol_error(parse_text_message("Path: turn.end")));
+        assert!(is_protocol_error(parse_text_message("no colon\r\n\r\n")));
+        assert!(is_protocol_error(parse_binary_message(&[0])));
+        assert!(is_protocol_error(parse_binary_message(&[0, 10, b'P'])));
+        assert!(is_protocol_error(parse_binary_message(&[0, 2, 0xFF, 0xFE])));
+        assert!(is_protocol_error(parse_server_message(
+            "X-RequestId: 0123\r\n\r\n"
+        )));
+    }
+
+    #[test]
+    fn decodes_server_messages() {
+        match parse_server_message("Path: turn.end\r\n\r\n").unwrap() {
+            ServerEvent::TurnEnd => {}
+            _ => panic!("expected turn.end"),
+        }
+        let hypothesis = "Path: speech.hypothesis\r\nContent-Type: application/json\r\n\r\n\
+                          {\"Text\":\"hello\",\"Offset\":0,\"Duration\":100}";
+        match parse_server_message(hypothesis).unwrap() {
+            ServerEvent::SpeechHypothesis(hypothesis) => assert_eq!(hypothesis.text, "hello"),
+            _ => panic!("expected speech.hypothesis"),
+        }
+        match parse_server_message("Path: something.new\r\n\r\n{}").unwrap() {
+            ServerEvent::Unknown => {}
+            _ => panic!("expected an unknown message"),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Durable offline job queue for recognitions
+//!
+//! `JobQueue` stores each job as an audio file plus a JSON metadata file in a
+//! directory, so recordings made while offline survive restarts and are
+//! recognized once connectivity returns. Files are written to a temporary
+//! name and renamed into place, so a crash mid-enqueue never leaves a
+//! half-written job behind.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use bing_rs::speech::*;
+//! use bing_rs::speech::queue::JobQueue;
+//!
+//! let queue = JobQueue::open("recordings").unwrap();
+//! let mode = Mode::Dictation(Language::EnglishUnitedStates);
+//! queue.enqueue(&[0u8; 32000], mode, Format::Simple).unwrap();
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! queue.run(&mut speech, Duration::from_secs(60), |job, phrase| {
+//!     println!("{}: {:?}", job.id, phrase.text());
+//!     Ok(())
+//! });
+//! ```
+
+// std
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+// chrono / serde
+use chrono::prelude::*;
+use serde_json;
+
+// internal
+use super::websocket::generate_uuid;
+use super::*;
+
+/// Attempts after which a job is given up on
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 10;
+
+/// Metadata of a queued recognition
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Job {
+    pub id: String,
+    /// Mode and language, e.g. "interactive:en-US"
+    pub mode: String,
+    /// "simple" or "detailed"
+    pub format: String,
+    pub created: String,
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl Job {
+    pub fn mode(&self) -> Result<Mode> {
+        self.mode.parse()
+    }
+
+    pub fn format(&self) -> Result<Format> {
+        self.format.parse()
+    }
+}
+
+/// Outcome of one pass over the queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessSummary {
+    pub completed: usize,
+    /// Jobs that failed and stay queued for a later pass
+    pub retried: usize,
+    /// Jobs that exceeded the maximum attempts and were moved to `failed/`
+    pub failed: usize,
+}
+
+/// Directory-backed queue of recognition jobs
+pub struct JobQueue {
+    dir: PathBuf,
+    max_attempts: u32,
+}
+
+impl JobQueue {
+    /// Open or create a queue in `dir`
+    pub fn open<P>(dir: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(dir.join("failed"))?;
+        Ok(JobQueue {
+            dir,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        })
+    }
+
+    pub fn set_max_attempts(&mut self, max_attempts: u32) {
+        self.max_attempts = max_attempts;
+    }
+
+    /// Store 16 kHz 16-bit mono PCM `audio` for recognition
+    pub fn enqueue(&self, audio: &[u8], mode: Mode, format: Format) -> Result<Job> {
+        let now = Utc::now();
+        let job = Job {
+            id: format!(
+                "{}-{}",
+                now.format("%Y%m%dT%H%M%S%.3f"),
+                &generate_uuid()[..8]
+            ),
+            mode: mode.to_spec(),
+            format: format.to_string(),
+            created: now.to_rfc3339(),
+            attempts: 0,
+            last_error: None,
+        };
+
+        // Audio first: a job only exists once its metadata is in place
+        write_atomic(&self.audio_path(&job.id), audio)?;
+        self.save(&job)?;
+        Ok(job)
+    }
+
+    /// Pending jobs, oldest first
+    pub fn pending(&self) -> Result<Vec<Job>> {
+        let mut ids = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
+                if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    ids.push(stem.to_string());
+                }
+            }
+        }
+        ids.sort();
+
+        let mut jobs = Vec::new();
+        for id in ids {
+            let file = File::open(self.metadata_path(&id))?;
+            match serde_json::from_reader(file) {
+                Ok(job) => jobs.push(job),
+                Err(err) => warn!("Skipping unreadable job {}: {}", id, err),
+            }
+        }
+        Ok(jobs)
+    }
+
+    /// Audio of a queued job
+    pub fn audio(&self, job: &Job) -> Result<Vec<u8>> {
+        let mut audio = Vec::new();
+        File::open(self.audio_path(&job.id))?.read_to_end(&mut audio)?;
+        Ok(audio)
+    }
+
+    /// Recognize every pending job once
+    ///
+    /// `on_complete` receives each result; the job is removed only after it
+    /// returns `Ok`, so results aren't lost if handling them fails. A network
+    /// failure ends the pass early since the remaining jobs would fail too.
+    /// Failing to update a job's files is logged and doesn't end the pass.
+    pub fn process<F>(&self, speech: &mut Speech, mut on_complete: F) -> Result<ProcessSummary>
+    where
+        F: FnMut(&Job, &Phrase) -> Result<()>,
+    {
+        let mut summary = ProcessSummary::default();
+        let jobs = self.pending()?;
+        if jobs.is_empty() {
+            return Ok(summary);
+        }
+        if let Err(err) = speech.fetch_token() {
+            warn!("Job queue offline: {}", err);
+            summary.retried = jobs.len();
+            return Ok(summary);
+        }
+
+        let total = jobs.len();
+        for (i, mut job) in jobs.into_iter().enumerate() {
+            job.attempts += 1;
+            let audio = match self.audio(&job) {
+                Ok(audio) => audio,
+                Err(err) => {
+                    // Without its audio the job can never succeed
+                    job.last_error = Some(err.to_string());
+                    self.cleanup(&job, self.fail(&job));
+                    summary.failed += 1;
+                    continue;
+                }
+            };
+
+            let result = recognize_job(speech, &job, audio).and_then(|phrase| {
+                on_complete(&job, &phrase)?;
+                Ok(())
+            });
+
+            match result {
+                Ok(()) => {
+                    self.cleanup(&job, self.remove(&job));
+                    summary.completed += 1;
+                }
+                Err(err) => {
+                    job.last_error = Some(err.to_string());
+                    if job.attempts >= self.max_attempts {
+                        self.cleanup(&job, self.fail(&job));
+                        summary.failed += 1;
+                    } else {
+                        self.cleanup(&job, self.save(&job));
+                        summary.retried += 1;
+                    }
+
+                    if ErrorClass::from(&err) == ErrorClass::Network {
+                        warn!("Job queue offline: {}", err);
+                        summary.retried += total - (i + 1);
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Process the queue every `interval`, forever
+    pub fn run<F>(&self, speech: &mut Speech, interval: Duration, mut on_complete: F)
+    where
+        F: FnMut(&Job, &Phrase) -> Result<()>,
+    {
+        loop {
+            match self.process(speech, &mut on_complete) {
+                Ok(summary) => debug!("Job queue pass: {:?}", summary),
+                Err(err) => error!("Job queue pass failed: {}", err),
+            }
+            thread::sleep(interval);
+        }
+    }
+
+    /// Log a failure to update `job` on disk without ending the pass
+    fn cleanup(&self, job: &Job, result: Result<()>) {
+        if let Err(err) = result {
+            warn!("Failed to update queued job {}: {}", job.id, err);
+        }
+    }
+
+    fn save(&self, job: &Job) -> Result<()> {
+        write_atomic(
+            &self.metadata_path(&job.id),
+            serde_json::to_string_pretty(job)?.as_bytes(),
+        )
+    }
+
+    fn remove(&self, job: &Job) -> Result<()> {
+        fs::remove_file(self.metadata_path(&job.id))?;
+        fs::remove_file(self.audio_path(&job.id))?;
+        Ok(())
+    }
+
+    fn fail(&self, job: &Job) -> Result<()> {
+        let failed = self.dir.join("failed");
+        write_atomic(
+            &failed.join(format!("{}.json", job.id)),
+            serde_json::to_string_pretty(job)?.as_bytes(),
+        )?;
+        // The audio may be what went missing
+        let audio_path = self.audio_path(&job.id);
+        if audio_path.exists() {
+            fs::rename(audio_path, failed.join(format!("{}.raw", job.id)))?;
+        }
+        fs::remove_file(self.metadata_path(&job.id))?;
+        Ok(())
+    }
+
+    fn audio_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.raw", id))
+    }
+
+    fn metadata_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+}
+
+fn recognize_job(speech: &Speech, job: &Job, audio: Vec<u8>) -> Result<Phrase> {
+    let (_, _, phrase) = speech.recognize(audio, job.mode()?, job.format()?)?;
+    match phrase {
+        Some(phrase) => Ok(phrase),
+        None => bail!("empty recognition response"),
+    }
+}
+
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+    }
+    fs::rename(tmp, path)?;
+    Ok(())
+}
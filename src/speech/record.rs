@@ -0,0 +1,161 @@
+//! Websocket session recording and replay
+//!
+//! A `Recorder` attached to a `Websocket` writes every frame sent and
+//! received as JSON Lines. `replay` feeds the received frames of such a
+//! recording back through the message parser into a `Handler`, which makes
+//! it possible to regression-test event handling offline.
+
+// std
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+// chrono / serde
+use chrono::prelude::*;
+use serde_json;
+
+// internal
+use super::websocket::{dispatch_binary_message, dispatch_text_message, Handler, ServerEvents};
+use errors::*;
+
+/// Direction of a recorded frame
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// Single websocket frame in a recording
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecordedFrame {
+    pub direction: Direction,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binary: Option<Vec<u8>>,
+}
+
+/// Writes websocket frames to a JSON Lines recording
+///
+/// Clones share the same underlying writer.
+#[derive(Clone)]
+pub struct Recorder {
+    writer: Arc<Mutex<Box<Write + Send>>>,
+}
+
+impl Recorder {
+    /// Record into a new file at `path`
+    pub fn create<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let file = File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    /// Record into an arbitrary writer
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: Write + Send + 'static,
+    {
+        Recorder {
+            writer: Arc::new(Mutex::new(Box::new(writer))),
+        }
+    }
+
+    /// Record a text frame
+    pub fn text(&self, direction: Direction, text: &str) {
+        self.write(RecordedFrame {
+            direction,
+            timestamp: Local::now().to_rfc3339(),
+            text: Some(text.to_string()),
+            binary: None,
+        });
+    }
+
+    /// Record a binary frame
+    pub fn binary(&self, direction: Direction, data: &[u8]) {
+        self.write(RecordedFrame {
+            direction,
+            timestamp: Local::now().to_rfc3339(),
+            text: None,
+            binary: Some(data.to_vec()),
+        });
+    }
+
+    /// Flush buffered frames to the underlying writer
+    pub fn flush(&self) -> Result<()> {
+        Ok(self.writer.lock().unwrap().flush()?)
+    }
+
+    fn write(&self, frame: RecordedFrame) {
+        let line = match serde_json::to_string(&frame) {
+            Ok(line) => line,
+            Err(err) => {
+                error!("Failed to serialize recorded frame: {}", err);
+                return;
+            }
+        };
+
+        let mut writer = self.writer.lock().unwrap();
+        if let Err(err) = writeln!(writer, "{}", line) {
+            error!("Failed to record frame: {}", err);
+        }
+    }
+}
+
+/// Read all frames of a recording
+pub fn read_recording<P>(path: P) -> Result<Vec<RecordedFrame>>
+where
+    P: AsRef<Path>,
+{
+    let reader = BufReader::new(File::open(path)?);
+    let mut frames = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        frames.push(serde_json::from_str(&line)?);
+    }
+    Ok(frames)
+}
+
+/// Feed the received frames of a recording back into `handler`
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::sync::{Arc, Mutex};
+/// use bing_rs::speech::record;
+/// use bing_rs::speech::websocket::Handler;
+///
+/// struct MyHandler;
+/// impl Handler for MyHandler {}
+///
+/// let handler = Arc::new(Mutex::new(MyHandler));
+/// record::replay("session.jsonl", handler).unwrap();
+/// ```
+pub fn replay<P>(path: P, handler: Arc<Mutex<Handler + Send + Sync>>) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let audio_uuid = Mutex::new(None);
+    let mut last_speaker = None;
+    let events = ServerEvents::default();
+    for frame in read_recording(path)? {
+        if frame.direction != Direction::Received {
+            continue;
+        }
+        if let Some(ref text) = frame.text {
+            dispatch_text_message(text, &handler, &audio_uuid, &mut last_speaker, &events);
+        }
+        if let Some(ref binary) = frame.binary {
+            dispatch_binary_message(binary, &handler, &events);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! Azure regions and latency probing
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! let region = speech.select_fastest_region(Region::all()).unwrap();
+//! println!("Using {}", region);
+//! ```
+
+use super::*;
+
+/// Azure region hosting the speech service
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Region {
+    WestUS,
+    WestUS2,
+    EastUS,
+    EastUS2,
+    CentralUS,
+    SouthCentralUS,
+    NorthEurope,
+    WestEurope,
+    UKSouth,
+    SoutheastAsia,
+    EastAsia,
+    JapanEast,
+    CentralIndia,
+    AustraliaEast,
+}
+
+const REGIONS: &[Region] = &[
+    Region::WestUS,
+    Region::WestUS2,
+    Region::EastUS,
+    Region::EastUS2,
+    Region::CentralUS,
+    Region::SouthCentralUS,
+    Region::NorthEurope,
+    Region::WestEurope,
+    Region::UKSouth,
+    Region::SoutheastAsia,
+    Region::EastAsia,
+    Region::JapanEast,
+    Region::CentralIndia,
+    Region::AustraliaEast,
+];
+
+impl Region {
+    /// Every known region
+    pub fn all() -> &'static [Region] {
+        REGIONS
+    }
+
+    /// Identifier used in host names, e.g. "westus"
+    pub fn name(self) -> &'static str {
+        match self {
+            Region::WestUS => "westus",
+            Region::WestUS2 => "westus2",
+            Region::EastUS => "eastus",
+            Region::EastUS2 => "eastus2",
+            Region::CentralUS => "centralus",
+            Region::SouthCentralUS => "southcentralus",
+            Region::NorthEurope => "northeurope",
+            Region::WestEurope => "westeurope",
+            Region::UKSouth => "uksouth",
+            Region::SoutheastAsia => "southeastasia",
+            Region::EastAsia => "eastasia",
+            Region::JapanEast => "japaneast",
+            Region::CentralIndia => "centralindia",
+            Region::AustraliaEast => "australiaeast",
+        }
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Region {
+    type Err = Error;
+
+    /// Parse a region identifier such as "westus", ignoring case
+    fn from_str(s: &str) -> Result<Self> {
+        match REGIONS
+            .iter()
+            .find(|region| s.eq_ignore_ascii_case(region.name()))
+        {
+            Some(&region) => Ok(region),
+            None => bail!(ErrorKind::UnknownVariant("Region", s.to_string())),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Region {
+    type Error = Error;
+
+    fn try_from(s: &'a str) -> Result<Self> {
+        s.parse()
+    }
+}
+
+/// Round-trip times measured against a single region
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionProbe {
+    pub region: Region,
+    /// Time to get a token issued, or why it failed
+    pub token: ::std::result::Result<Duration, String>,
+    /// Time for the recognition endpoint to answer, or why it failed
+    pub recognition: ::std::result::Result<Duration, String>,
+}
+
+impl RegionProbe {
+    /// Combined round-trip time, if both probes succeeded
+    pub fn latency(&self) -> Option<Duration> {
+        match (&self.token, &self.recognition) {
+            (&Ok(token), &Ok(recognition)) => Some(token + recognition),
+            _ => None,
+        }
+    }
+}
+
+impl Speech {
+    /// Measure token and recognition endpoint round-trip times of `regions`
+    ///
+    /// Probes run one after another with this client's subscription key.
+    /// Neither the current token nor the configured endpoints are changed.
+    pub fn probe_regions(&self, regions: &[Region]) -> Vec<RegionProbe> {
+        regions
+            .iter()
+            .map(|&region| self.probe_region(region))
+            .collect()
+    }
+
+    /// Probe `regions`, switch to the endpoints of the fastest one and fetch
+    /// a token there
+    ///
+    /// Fails if no region could be reached or the new token couldn't be
+    /// fetched.
+    pub fn select_fastest_region(&mut self, regions: &[Region]) -> Result<Region> {
+        let fastest = self
+            .probe_regions(regions)
+            .into_iter()
+            .filter_map(|probe| probe.latency().map(|latency| (probe.region, latency)))
+            .min_by_key(|&(_, latency)| latency);
+
+        match fastest {
+            Some((region, latency)) => {
+                debug!("Selected region {} ({:?})", region, latency);
+                self.set_region(region);
+                // Tokens are only valid in the region that issued them
+                self.fetch_token()?;
+                Ok(region)
+            }
+            None => bail!("none of the probed regions could be reached"),
+        }
+    }
+
+    fn probe_region(&self, region: Region) -> RegionProbe {
+        let endpoints = Endpoints::region(region);
+
+        // Regional tokens are only valid in their own region
+        let start = Instant::now();
+        let token = self
+            .probe_token(&endpoints.token)
+            .map(|token| (token, start.elapsed()));
+        let (token, latency) = match token {
+            Ok((token, latency)) => (token, latency),
+            Err(err) => {
+                return RegionProbe {
+                    region,
+                    token: Err(err.to_string()),
+                    recognition: Err("skipped".to_string()),
+                }
+            }
+        };
+
+        let url = endpoints.recognition_url(
+            Mode::Interactive(Language::EnglishUnitedStates),
+            Format::Simple,
+            None,
+            RecognitionParams::default(),
+        );
+        let start = Instant::now();
+        let recognition = self
+            .probe_recognition(&url, &token)
+            .map(|_| start.elapsed())
+            .map_err(|err| err.to_string());
+
+        RegionProbe {
+            region,
+            token: Ok(latency),
+            recognition,
+        }
+    }
+
+    fn probe_token(&self, url: &str) -> Result<String> {
+        let uri: Uri = url.parse()?;
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("Ocp-Apim-Subscription-Key", self.subscription_key.as_str())
+            .header("Content-Length", "0")
+            .body(Body::empty())
+            .unwrap();
+        self.stamp_correlation_id(&mut request);
+
+        let (header, status, body) = self.execute(RequestKind::Token, request, 0)?;
+        check_response(&header, status, &body)?;
+        if body.is_empty() {
+            bail!("no token issued ({})", status);
+        }
+        Ok(String::from_utf8(body)?)
+    }
+
+    /// Send an empty authorized request and treat any non-5xx answer as reachable
+    fn probe_recognition(&self, url: &str, token: &str) -> Result<()> {
+        let uri: Uri = url.parse()?;
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .header("Authorization", format!("Bearer {}", token).as_str())
+            .header("Content-Length", "0")
+            .body(Body::empty())
+            .unwrap();
+        self.stamp_correlation_id(&mut request);
+
+        let (_, status, _) = self.execute(RequestKind::Recognize, request, 0)?;
+        if status.is_server_error() {
+            bail!("server error {}", status);
+        }
+        Ok(())
+    }
+}
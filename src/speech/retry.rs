@@ -0,0 +1,146 @@
+//! Retrying requests that couldn't connect or that the service rejected as
+//! transient
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.set_retry_policy(Some(RetryPolicy::new(4)));
+//! speech.fetch_token().unwrap();
+//! println!("retries: {}", speech.stats().retries);
+//! ```
+
+// std
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// internal
+use super::*;
+
+/// When and how often to retry requests that failed to connect or with 429
+/// or 5xx
+///
+/// Retry `n` waits `base_delay * 2^(n - 1)`, shortened by up to a half at
+/// random so clients don't retry in lockstep, and never more than
+/// `max_delay`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    /// Longest wait before a retry; a `Retry-After` asking for more gives
+    /// up instead
+    pub max_delay: Duration,
+    /// Wait as long as a 429 response's `Retry-After` header asks instead
+    pub honor_retry_after: bool,
+}
+
+impl RetryPolicy {
+    /// Policy giving up after `max_attempts` with the default delays
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry `retry`, starting at 1, without jitter
+    pub fn delay(&self, retry: u32) -> Duration {
+        (self.base_delay * (1u32 << retry.saturating_sub(1).min(16))).min(self.max_delay)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            honor_retry_after: true,
+        }
+    }
+}
+
+/// Shorten `delay` by a pseudo-random amount of up to a half
+fn jitter(delay: Duration) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.subsec_nanos())
+        .unwrap_or(0);
+    delay - delay / 2 * (nanos % 1000) / 1000
+}
+
+impl Speech {
+    /// Retry fetching tokens, recognizing and synthesizing according to
+    /// `policy`, or never with `None`
+    ///
+    /// Streamed recognitions are never retried since their audio can't be
+    /// sent again. Retries are counted in `ClientStats::retries`.
+    pub fn set_retry_policy(&mut self, policy: Option<RetryPolicy>) {
+        self.retry_policy = policy;
+    }
+
+    /// `execute` a request built by `make_request`, retrying according to
+    /// the retry policy
+    pub(crate) fn execute_with_retry<F>(
+        &self,
+        kind: RequestKind,
+        body_len: usize,
+        make_request: F,
+    ) -> Result<(HeaderMap, StatusCode, Vec<u8>)>
+    where
+        F: Fn() -> Result<Request<Body>>,
+    {
+        let policy = match self.retry_policy {
+            Some(policy) => policy,
+            None => return self.execute(kind, make_request()?, body_len),
+        };
+
+        let mut retry = 0;
+        loop {
+            let last = retry + 1 >= policy.max_attempts;
+            let (reason, retry_after) = match self.execute(kind, make_request()?, body_len) {
+                // The request never reached the service
+                Err(Error(ErrorKind::Hyper(ref err), _)) if err.is_connect() && !last => {
+                    (err.to_string(), None)
+                }
+                Err(err) => return Err(err),
+                Ok((header, status, body)) => {
+                    let retryable =
+                        status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+                    if !retryable || last {
+                        return Ok((header, status, body));
+                    }
+                    let retry_after = match check_status(&header, status) {
+                        Err(Error(ErrorKind::RateLimited(Some(retry_after)), _))
+                            if policy.honor_retry_after =>
+                        {
+                            Some(retry_after)
+                        }
+                        _ => None,
+                    };
+                    // Don't let the service block the caller for longer
+                    if retry_after.map_or(false, |delay| delay > policy.max_delay) {
+                        return Ok((header, status, body));
+                    }
+                    (status.to_string(), retry_after)
+                }
+            };
+
+            retry += 1;
+            self.stats.record_retry();
+            let delay = retry_after.unwrap_or_else(|| jitter(policy.delay(retry)));
+            warn!(
+                "[{}] {:?} request failed with {}, retrying in {:?} ({}/{})",
+                self.correlation_id().unwrap_or("-"),
+                kind,
+                reason,
+                delay,
+                retry,
+                policy.max_attempts - 1
+            );
+            thread::sleep(delay);
+        }
+    }
+}
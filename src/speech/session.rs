@@ -0,0 +1,420 @@
+//! Push-based continuous recognition
+//!
+//! `RecognitionSession` wraps a `Websocket` for callers that produce audio
+//! themselves, e.g. from a capture callback. It sends the `speech.config`
+//! message, splits audio into websocket-sized chunks, lets the websocket
+//! start a new X-RequestId for every turn, and signals the end of audio on
+//! `stop()`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.fetch_token().unwrap();
+//! let mode = Mode::Interactive(Language::EnglishUnitedStates);
+//! let mut session = speech.recognition_session(ContinuousOptions::new(mode, Format::Simple));
+//! let events = session.take_events().unwrap();
+//!
+//! session.start().unwrap();
+//! session.push_audio(&[0; 3200]).unwrap();
+//! session.stop().unwrap();
+//!
+//! for event in events.try_iter() {
+//!     if let SessionEvent::Phrase(phrase) = event {
+//!         println!("{}", phrase.text().unwrap_or(""));
+//!     }
+//! }
+//! ```
+
+// std
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+// internal
+use super::intent::{IntentApp, IntentResult};
+use super::websocket::{Handler, ReconnectPolicy, Websocket};
+use super::*;
+use audio::vad::{Activity, Detector};
+use audio::{Converter, Spec};
+
+/// Event delivered by a `RecognitionSession`
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    TurnStart,
+    /// Voice was detected in the pushed audio, see
+    /// `RecognitionSession::set_voice_detection`
+    LocalSpeechStart,
+    /// The voice detected in the pushed audio ended
+    LocalSpeechEnd,
+    SpeechStart,
+    Hypothesis(Hypothesis),
+    SpeechEnd,
+    Phrase(Phrase),
+    /// The next phrase is attributed to another speaker
+    SpeakerChange(String),
+    /// LUIS result for the last phrase
+    Intent(IntentResult),
+    TurnEnd,
+    /// The connection dropped; the string is the server's reason, if any
+    Disconnected(String),
+    /// The connection was re-established after this many attempts
+    Reconnected(u32),
+}
+
+type Callback = Arc<Mutex<Box<FnMut(SessionEvent) + Send>>>;
+
+/// Forwards websocket callbacks as `SessionEvent`s
+struct SessionHandler {
+    events: Mutex<Sender<SessionEvent>>,
+    callback: Option<Callback>,
+    turn_end: Mutex<Sender<()>>,
+    min_confidence: Option<f64>,
+}
+
+impl SessionHandler {
+    fn emit(&mut self, event: SessionEvent) {
+        match self.callback {
+            Some(ref callback) => (&mut *callback.lock().unwrap())(event),
+            None => {
+                let _ = self.events.lock().unwrap().send(event);
+            }
+        }
+    }
+}
+
+impl Handler for SessionHandler {
+    fn on_turn_start(&mut self) {
+        self.emit(SessionEvent::TurnStart);
+    }
+
+    fn on_turn_end(&mut self) {
+        self.emit(SessionEvent::TurnEnd);
+        let _ = self.turn_end.lock().unwrap().send(());
+    }
+
+    fn on_speech_start(&mut self) {
+        self.emit(SessionEvent::SpeechStart);
+    }
+
+    fn on_speech_end(&mut self) {
+        self.emit(SessionEvent::SpeechEnd);
+    }
+
+    fn on_local_speech_start(&mut self) {
+        self.emit(SessionEvent::LocalSpeechStart);
+    }
+
+    fn on_local_speech_end(&mut self) {
+        self.emit(SessionEvent::LocalSpeechEnd);
+    }
+
+    fn on_speech_hypothesis(&mut self, hypothesis: Hypothesis) {
+        self.emit(SessionEvent::Hypothesis(hypothesis));
+    }
+
+    fn on_speech_phrase(&mut self, phrase: Phrase) {
+        let phrase = match self.min_confidence {
+            Some(min_confidence) => phrase.filter_confidence(min_confidence),
+            None => phrase,
+        };
+        self.emit(SessionEvent::Phrase(phrase));
+    }
+
+    fn on_speaker_change(&mut self, speaker_id: &str) {
+        self.emit(SessionEvent::SpeakerChange(speaker_id.to_string()));
+    }
+
+    fn on_intent(&mut self, result: IntentResult) {
+        self.emit(SessionEvent::Intent(result));
+    }
+
+    fn on_disconnect(&mut self, _code: u16, reason: &str) {
+        self.emit(SessionEvent::Disconnected(reason.to_string()));
+    }
+
+    fn on_reconnect(&mut self, attempt: u32) {
+        self.emit(SessionEvent::Reconnected(attempt));
+    }
+}
+
+/// Continuous recognition fed with audio as it becomes available
+///
+/// Created with `Speech::recognition_session`. Events are delivered on the
+/// channel returned by `take_events`, or to the callback set with
+/// `set_callback` instead.
+pub struct RecognitionSession<'a> {
+    speech: &'a Speech,
+    options: ContinuousOptions,
+    ws: Option<Websocket>,
+    handler: Option<Arc<Mutex<Handler + Send + Sync>>>,
+    events_tx: Sender<SessionEvent>,
+    events_rx: Option<Receiver<SessionEvent>>,
+    callback: Option<Callback>,
+    turn_end: Option<Receiver<()>>,
+    min_confidence: Option<f64>,
+    converter: Option<Converter>,
+    input_spec: Spec,
+    intent_app: Option<IntentApp>,
+    vad: Option<Detector>,
+}
+
+impl<'a> RecognitionSession<'a> {
+    fn new(speech: &'a Speech, options: ContinuousOptions) -> Self {
+        let (events_tx, events_rx) = channel();
+        RecognitionSession {
+            speech,
+            options,
+            ws: None,
+            handler: None,
+            events_tx,
+            events_rx: Some(events_rx),
+            callback: None,
+            turn_end: None,
+            min_confidence: speech.min_confidence,
+            converter: None,
+            input_spec: Spec::default(),
+            intent_app: None,
+            vad: None,
+        }
+    }
+
+    /// Receiver of the session's events
+    ///
+    /// Only the first call returns the receiver.
+    pub fn take_events(&mut self) -> Option<Receiver<SessionEvent>> {
+        self.events_rx.take()
+    }
+
+    /// Deliver events to `callback` instead of the channel
+    ///
+    /// The callback runs on the websocket thread. Takes effect on the next
+    /// `start()`.
+    pub fn set_callback<F>(&mut self, callback: F)
+    where
+        F: FnMut(SessionEvent) + Send + 'static,
+    {
+        self.callback = Some(Arc::new(Mutex::new(Box::new(callback))));
+    }
+
+    /// Filter phrases like `Speech::set_min_confidence`, whose setting is
+    /// used by default
+    ///
+    /// Takes effect on the next `start()`.
+    pub fn set_min_confidence(&mut self, min_confidence: Option<f64>) {
+        self.min_confidence = min_confidence;
+    }
+
+    /// Layout of the 16-bit PCM audio passed to `push_audio`
+    ///
+    /// Audio in another layout than 16 kHz mono, e.g. 48 kHz stereo from a
+    /// capture device, is converted with `audio::Converter` before it's
+    /// sent. Fails with `ErrorKind::InvalidRequest` if the rate or the
+    /// number of channels is zero.
+    pub fn set_input_format(&mut self, sample_rate: u32, channels: u16) -> Result<()> {
+        if sample_rate == 0 || channels == 0 {
+            bail!(ErrorKind::InvalidRequest(format!(
+                "invalid input format: {} Hz, {} channels",
+                sample_rate, channels
+            )));
+        }
+        self.input_spec = Spec::new(sample_rate, channels, 16);
+        let converter = Converter::new(sample_rate, channels);
+        self.converter = if converter.is_passthrough() {
+            None
+        } else {
+            Some(converter)
+        };
+        Ok(())
+    }
+
+    /// Layout of the audio passed to `push_audio`, 16 kHz mono unless set
+    /// with `set_input_format`
+    pub fn input_spec(&self) -> Spec {
+        self.input_spec
+    }
+
+    /// Size of the audio messages sent by `push_audio`
+    pub fn chunk_size(&self) -> usize {
+        self.options.chunk_size.max(2)
+    }
+
+    /// Whether `start()` was called and `stop()` wasn't yet
+    pub fn is_started(&self) -> bool {
+        self.ws.is_some()
+    }
+
+    /// Run recognized phrases through the LUIS app `app_id`, authenticated
+    /// with its subscription `key`, delivering results as
+    /// `SessionEvent::Intent`
+    ///
+    /// Takes effect on the next `start()`.
+    pub fn set_intent_app(&mut self, app_id: &str, key: &str) {
+        self.intent_app = Some(IntentApp::new(app_id, key));
+    }
+
+    /// Only send audio while `detector` hears voice, or send everything
+    /// with `None` (the default)
+    ///
+    /// Silence isn't sent, saving quota. `SessionEvent::LocalSpeechStart`
+    /// and `LocalSpeechEnd` mark the detected voice, and the turn's audio
+    /// ends with it so the final phrase comes back right away.
+    pub fn set_voice_detection(&mut self, detector: Option<Detector>) {
+        self.vad = detector;
+    }
+
+    /// Connect and send the `speech.config` message
+    ///
+    /// Dropped connections are re-established up to
+    /// `ContinuousOptions::max_reconnects` times.
+    pub fn start(&mut self) -> Result<()> {
+        if self.ws.is_some() {
+            bail!("recognition session already started");
+        }
+
+        let (turn_end_tx, turn_end_rx) = channel();
+        let handler: Arc<Mutex<Handler + Send + Sync>> = Arc::new(Mutex::new(SessionHandler {
+            events: Mutex::new(self.events_tx.clone()),
+            callback: self.callback.clone(),
+            turn_end: Mutex::new(turn_end_tx),
+            min_confidence: self.min_confidence,
+        }));
+
+        let mut ws = self.speech.continuous_websocket();
+        if self.options.max_reconnects > 0 {
+            let mut policy = ReconnectPolicy::new(self.options.max_reconnects);
+            policy.connect_timeout = self.options.connect_timeout;
+            ws.set_reconnect(Some(policy));
+        }
+        if let Some(ref app) = self.intent_app {
+            ws.set_intent_app(&app.app_id, &app.key);
+        }
+        self.speech
+            .open_continuous(&ws, &self.options, handler.clone())?;
+        ws.config(&default_speech_config())?;
+
+        if let Some(ref mut converter) = self.converter {
+            converter.reset();
+        }
+        if let Some(ref mut vad) = self.vad {
+            vad.reset();
+        }
+        self.ws = Some(ws);
+        self.handler = Some(handler);
+        self.turn_end = Some(turn_end_rx);
+        Ok(())
+    }
+
+    /// Send 16 kHz 16-bit mono PCM audio, or audio in the layout set with
+    /// `set_input_format`
+    ///
+    /// Audio is split into messages of `ContinuousOptions::chunk_size`
+    /// bytes. A new turn, with its own X-RequestId, starts with the first
+    /// audio after the previous turn ended. Audio pushed while the
+    /// connection is opening or reconnecting is queued, see
+    /// `Websocket::push_audio`. With voice detection set, chunks without
+    /// voice are dropped.
+    pub fn push_audio(&mut self, audio: &[u8]) -> Result<()> {
+        let chunk_size = self.chunk_size();
+        let (ws, handler) = match (&mut self.ws, &self.handler) {
+            (&mut Some(ref mut ws), &Some(ref handler)) => (ws, handler),
+            _ => bail!("recognition session not started"),
+        };
+        let converted;
+        let audio = match self.converter {
+            Some(ref mut converter) => {
+                converted = converter.process(audio);
+                &converted[..]
+            }
+            None => audio,
+        };
+        for chunk in audio.chunks(chunk_size) {
+            let vad = match self.vad {
+                Some(ref mut vad) => vad,
+                None => {
+                    ws.push_audio(chunk)?;
+                    continue;
+                }
+            };
+            match vad.process(chunk) {
+                Activity::Start => {
+                    handler.lock().unwrap().on_local_speech_start();
+                    ws.push_audio(chunk)?;
+                }
+                Activity::Voice => ws.push_audio(chunk)?,
+                Activity::End => {
+                    handler.lock().unwrap().on_local_speech_end();
+                    ws.end_audio()?;
+                }
+                Activity::Silence => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Signal the end of the current turn's audio without disconnecting
+    ///
+    /// The service returns the final phrase right away instead of waiting
+    /// for silence. The next audio pushed starts a new turn.
+    pub fn end_turn(&mut self) -> Result<()> {
+        match self.ws {
+            Some(ref mut ws) => Ok(ws.end_audio()?),
+            None => bail!("recognition session not started"),
+        }
+    }
+
+    /// Signal the end of audio, wait for the final phrase and disconnect
+    ///
+    /// Waits at most `ContinuousOptions::final_phrase_timeout`.
+    pub fn stop(&mut self) -> Result<()> {
+        let mut ws = match self.ws.take() {
+            Some(ws) => ws,
+            None => return Ok(()),
+        };
+        let handler = self.handler.take();
+        if let Some(ref mut vad) = self.vad {
+            if vad.is_active() {
+                vad.reset();
+                if let Some(handler) = handler {
+                    handler.lock().unwrap().on_local_speech_end();
+                }
+            }
+        }
+
+        let turn_end = self.turn_end.take();
+        if let Some(ref turn_end) = turn_end {
+            // Only the turn ended by this end of audio counts
+            while turn_end.try_recv().is_ok() {}
+        }
+        ws.end_audio()?;
+        if let Some(turn_end) = turn_end {
+            if turn_end
+                .recv_timeout(self.options.final_phrase_timeout)
+                .is_err()
+            {
+                warn!("Timed out waiting for the final phrase");
+            }
+        }
+        ws.disconnect()
+    }
+}
+
+impl<'a> Drop for RecognitionSession<'a> {
+    fn drop(&mut self) {
+        self.handler = None;
+        if let Some(mut ws) = self.ws.take() {
+            let _ = ws.disconnect();
+        }
+    }
+}
+
+impl Speech {
+    /// Start building a push-based continuous recognition session
+    ///
+    /// `ContinuousOptions::pacing` doesn't apply; audio is sent as it's
+    /// pushed.
+    pub fn recognition_session(&self, options: ContinuousOptions) -> RecognitionSession {
+        RecognitionSession::new(self, options)
+    }
+}
@@ -0,0 +1,127 @@
+//! SSML documents for text-to-speech
+//!
+//! `Speech::synthesize` wraps plain text in a fixed template. `Builder`
+//! produces a full document for `Speech::synthesize_ssml` instead, with
+//! control over prosody, pauses and how words are read out.
+//!
+//! # Examples
+//!
+//! ```
+//! use bing_rs::speech::{ssml, voice};
+//!
+//! let ssml = ssml::Builder::new(voice::en_us::JESSA_RUS)
+//!     .rate("-10%")
+//!     .text("Your order ships on")
+//!     .break_ms(200)
+//!     .say_as("date", "2018-07-04")
+//!     .build();
+//! assert!(ssml.contains("<break time='200ms'/>"));
+//! ```
+
+use super::voice;
+
+/// Builds an SSML document spoken by a single voice
+#[derive(Debug, Clone)]
+pub struct Builder {
+    font: &'static voice::Font,
+    rate: Option<String>,
+    pitch: Option<String>,
+    volume: Option<String>,
+    body: String,
+}
+
+impl Builder {
+    pub fn new(font: &'static voice::Font) -> Self {
+        Builder {
+            font,
+            rate: None,
+            pitch: None,
+            volume: None,
+            body: String::new(),
+        }
+    }
+
+    /// Speaking rate, e.g. "slow", "+20%" or "0.8"
+    pub fn rate(mut self, rate: &str) -> Self {
+        self.rate = Some(rate.to_string());
+        self
+    }
+
+    /// Baseline pitch, e.g. "high", "+2st" or "-10%"
+    pub fn pitch(mut self, pitch: &str) -> Self {
+        self.pitch = Some(pitch.to_string());
+        self
+    }
+
+    /// Volume, e.g. "loud", "-6dB" or "50"
+    pub fn volume(mut self, volume: &str) -> Self {
+        self.volume = Some(volume.to_string());
+        self
+    }
+
+    /// Append text to speak, escaping any markup characters
+    pub fn text(mut self, text: &str) -> Self {
+        if !self.body.is_empty() && !self.body.ends_with('>') {
+            self.body.push(' ');
+        }
+        self.body.push_str(&escape(text));
+        self
+    }
+
+    /// Append a pause of `ms` milliseconds
+    pub fn break_ms(mut self, ms: u32) -> Self {
+        self.body.push_str(&format!("<break time='{}ms'/>", ms));
+        self
+    }
+
+    /// Append `text` read out as `interpret_as`, e.g. "date", "digits",
+    /// "telephone" or "characters"
+    pub fn say_as(mut self, interpret_as: &str, text: &str) -> Self {
+        self.body.push_str(&format!(
+            "<say-as interpret-as='{}'>{}</say-as>",
+            escape(interpret_as),
+            escape(text)
+        ));
+        self
+    }
+
+    /// Render the document
+    pub fn build(&self) -> String {
+        let mut prosody = String::new();
+        for &(name, value) in &[
+            ("rate", &self.rate),
+            ("pitch", &self.pitch),
+            ("volume", &self.volume),
+        ] {
+            if let Some(ref value) = *value {
+                prosody.push_str(&format!(" {}='{}'", name, escape(value)));
+            }
+        }
+        let body = if prosody.is_empty() {
+            self.body.clone()
+        } else {
+            format!("<prosody{}>{}</prosody>", prosody, self.body)
+        };
+
+        format!(
+            "<speak version='1.0' xmlns='http://www.w3.org/2001/10/synthesis' xml:lang='{}'><voice xml:lang='{}' xml:gender='{}' name='{}'>{}</voice></speak>",
+            self.font.lang, self.font.lang, self.font.gender, self.font.name, body
+        )
+    }
+}
+
+/// Escape the characters XML reserves in text and attribute values
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\'' => escaped.push_str("&apos;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
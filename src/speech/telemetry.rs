@@ -0,0 +1,250 @@
+//! Websocket protocol telemetry
+//!
+//! The speech websocket protocol expects a `telemetry` message at the end of
+//! every turn, listing when each server message was received plus connection
+//! and audio timing. `TelemetryTracker` collects those per turn; `Websocket`
+//! sends them automatically on `turn.end`.
+
+// std
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+// chrono / serde
+use chrono::prelude::*;
+use serde_json;
+
+// internal
+use errors::*;
+
+/// Body of a `telemetry` message
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Telemetry {
+    /// Receive times of the turn's server messages, one single-key object
+    /// per path in the order the paths were first received, e.g.
+    /// `[{"turn.start": "..."}, {"speech.hypothesis": ["...", "..."]}]`
+    pub received_messages: Vec<BTreeMap<String, ReceivedAt>>,
+    pub metrics: Vec<TelemetryMetric>,
+}
+
+/// When the messages with one path were received
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ReceivedAt {
+    Once(String),
+    Repeatedly(Vec<String>),
+}
+
+impl ReceivedAt {
+    fn new(mut timestamps: Vec<String>) -> Self {
+        if timestamps.len() == 1 {
+            ReceivedAt::Once(timestamps.remove(0))
+        } else {
+            ReceivedAt::Repeatedly(timestamps)
+        }
+    }
+}
+
+impl Telemetry {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+/// Timing of a connection or of the audio stream
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "PascalCase")]
+pub struct TelemetryMetric {
+    /// "Connection" or "Microphone"
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub start: String,
+    pub end: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct State {
+    connection_id: Option<String>,
+    connection_start: Option<String>,
+    connection_end: Option<String>,
+    connection_error: Option<String>,
+    /// Connection metric already reported in an earlier turn
+    connection_reported: bool,
+    audio_start: Option<String>,
+    audio_end: Option<String>,
+    /// Receive times by path, in the order paths were first received
+    received: Vec<(String, Vec<String>)>,
+}
+
+/// Collects telemetry for the current turn
+///
+/// Clones share the same state.
+#[derive(Clone, Default)]
+pub struct TelemetryTracker {
+    state: Arc<Mutex<State>>,
+}
+
+impl TelemetryTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A connection with `connection_id` is being opened
+    pub fn connecting(&self, connection_id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.connection_id = Some(connection_id.to_string());
+        state.connection_start = Some(timestamp());
+        state.connection_end = None;
+        state.connection_error = None;
+        state.connection_reported = false;
+    }
+
+    /// The connection's handshake completed
+    pub fn connected(&self) {
+        self.state.lock().unwrap().connection_end = Some(timestamp());
+    }
+
+    /// The connection failed with `error`
+    pub fn connection_failed(&self, error: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.connection_end = Some(timestamp());
+        state.connection_error = Some(error.to_string());
+    }
+
+    /// A chunk of audio was sent
+    pub fn audio_sent(&self) {
+        let mut state = self.state.lock().unwrap();
+        let now = timestamp();
+        if state.audio_start.is_none() {
+            state.audio_start = Some(now.clone());
+        }
+        state.audio_end = Some(now);
+    }
+
+    /// A server message with `path` was received
+    pub fn received(&self, path: &str) {
+        let mut state = self.state.lock().unwrap();
+        let now = timestamp();
+        match state.received.iter().position(|&(ref p, _)| p == path) {
+            Some(i) => state.received[i].1.push(now),
+            None => state.received.push((path.to_string(), vec![now])),
+        }
+    }
+
+    /// Telemetry of the current turn, starting a new one
+    pub fn take_turn(&self) -> Telemetry {
+        let mut state = self.state.lock().unwrap();
+        let mut metrics = Vec::new();
+
+        if !state.connection_reported {
+            if let (Some(start), Some(end)) =
+                (state.connection_start.clone(), state.connection_end.clone())
+            {
+                metrics.push(TelemetryMetric {
+                    name: "Connection".to_string(),
+                    id: state.connection_id.clone(),
+                    start,
+                    end,
+                    error: state.connection_error.clone(),
+                });
+                state.connection_reported = true;
+            }
+        }
+        if let (Some(start), Some(end)) = (state.audio_start.take(), state.audio_end.take()) {
+            metrics.push(TelemetryMetric {
+                name: "Microphone".to_string(),
+                id: None,
+                start,
+                end,
+                error: None,
+            });
+        }
+
+        let received_messages = state
+            .received
+            .drain(..)
+            .map(|(path, timestamps)| {
+                let mut message = BTreeMap::new();
+                message.insert(path, ReceivedAt::new(timestamps));
+                message
+            }).collect();
+        Telemetry {
+            received_messages,
+            metrics,
+        }
+    }
+}
+
+/// ISO 8601 UTC timestamp with millisecond precision
+fn timestamp() -> String {
+    Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_received_messages_by_path() {
+        let tracker = TelemetryTracker::new();
+        tracker.received("turn.start");
+        tracker.received("speech.hypothesis");
+        tracker.received("speech.hypothesis");
+        tracker.received("turn.end");
+
+        let telemetry = tracker.take_turn();
+        let paths: Vec<&str> = telemetry
+            .received_messages
+            .iter()
+            .flat_map(|message| message.keys().map(|path| path.as_str()))
+            .collect();
+        assert_eq!(paths, vec!["turn.start", "speech.hypothesis", "turn.end"]);
+        match telemetry.received_messages[1]["speech.hypothesis"] {
+            ReceivedAt::Repeatedly(ref timestamps) => assert_eq!(timestamps.len(), 2),
+            ReceivedAt::Once(_) => panic!("expected two timestamps"),
+        }
+
+        let json: serde_json::Value = serde_json::from_str(&telemetry.to_json().unwrap()).unwrap();
+        assert!(json["ReceivedMessages"][0]["turn.start"].is_string());
+        assert!(json["ReceivedMessages"][1]["speech.hypothesis"].is_array());
+        let parsed: Telemetry = serde_json::from_str(&telemetry.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, telemetry);
+
+        assert!(tracker.take_turn().received_messages.is_empty());
+    }
+
+    #[test]
+    fn reports_connection_once_and_audio_per_turn() {
+        let tracker = TelemetryTracker::new();
+        tracker.connecting("connection");
+        tracker.connected();
+        tracker.audio_sent();
+        tracker.audio_sent();
+
+        let metrics = tracker.take_turn().metrics;
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].name, "Connection");
+        assert_eq!(metrics[0].id, Some("connection".to_string()));
+        assert_eq!(metrics[0].error, None);
+        assert_eq!(metrics[1].name, "Microphone");
+
+        assert!(tracker.take_turn().metrics.is_empty());
+        tracker.audio_sent();
+        let metrics = tracker.take_turn().metrics;
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].name, "Microphone");
+    }
+
+    #[test]
+    fn reports_connection_failures() {
+        let tracker = TelemetryTracker::new();
+        tracker.connecting("connection");
+        tracker.connection_failed("refused");
+        let metrics = tracker.take_turn().metrics;
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].error, Some("refused".to_string()));
+    }
+}
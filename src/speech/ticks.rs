@@ -0,0 +1,50 @@
+//! Conversions for the 100-nanosecond ticks used in recognition results
+//!
+//! `Offset` and `Duration` in Bing Speech results are expressed in ticks of
+//! 100 ns, e.g. an offset of `10000000.0` means one second into the audio.
+
+use std::time::Duration;
+
+/// Number of ticks in one second
+pub const TICKS_PER_SECOND: u64 = 10_000_000;
+
+/// Number of nanoseconds in one tick
+pub const NANOS_PER_TICK: u64 = 100;
+
+/// Convert ticks into a `Duration`, treating negative values as zero
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use bing_rs::speech::ticks;
+///
+/// assert_eq!(ticks::to_duration(15000000.0), Duration::from_millis(1500));
+/// ```
+pub fn to_duration(ticks: f64) -> Duration {
+    if !(ticks > 0.0) {
+        return Duration::new(0, 0);
+    }
+
+    let ticks = ticks.round() as u64;
+    Duration::new(
+        ticks / TICKS_PER_SECOND,
+        ((ticks % TICKS_PER_SECOND) * NANOS_PER_TICK) as u32,
+    )
+}
+
+/// Convert a `Duration` into ticks
+pub fn from_duration(duration: Duration) -> f64 {
+    (duration.as_secs() * TICKS_PER_SECOND
+        + u64::from(duration.subsec_nanos()) / NANOS_PER_TICK) as f64
+}
+
+/// Convert ticks into milliseconds
+pub fn to_millis(ticks: f64) -> f64 {
+    ticks / (TICKS_PER_SECOND / 1000) as f64
+}
+
+/// Convert milliseconds into ticks
+pub fn from_millis(millis: f64) -> f64 {
+    millis * (TICKS_PER_SECOND / 1000) as f64
+}
@@ -0,0 +1,380 @@
+//! Expiry-aware token caching
+//!
+//! Bing Speech tokens are valid for 10 minutes. A `TokenProvider` remembers
+//! when the current token was issued and fetches a new one once it's older
+//! than `max_age`, either lazily when `Speech` is about to send a request or
+//! from a background thread started with `spawn`.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use bing_rs::speech::*;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! let provider = speech.token_provider();
+//! provider.set_max_age(Duration::from_secs(8 * 60));
+//! speech.set_token_provider(Some(provider.clone()));
+//!
+//! // Tokens are now fetched on demand by recognize / synthesize
+//! provider.spawn();
+//! // ...
+//! provider.stop();
+//! ```
+
+// std
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+
+use super::*;
+
+/// Age after which tokens are refreshed by default
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(9 * 60);
+
+/// How often the background thread checks the token age
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+struct Settings {
+    max_age: Duration,
+    max_retries: u32,
+    initial_backoff: Duration,
+}
+
+struct Inner {
+    token: Arc<Mutex<String>>,
+    issued_at: Mutex<Option<Instant>>,
+    settings: Mutex<Settings>,
+    token_url: String,
+    subscription_key: String,
+    transport: Mutex<Option<Arc<Transport>>>,
+    stopped: AtomicBool,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Shared token cache that refreshes tokens before they expire
+///
+/// Clones share the same token and state. Refreshes go through the transport
+/// given to `with_transport`, or a `HyperTransport` of the provider's own
+/// created on first use. Either way the provider can be used from any thread.
+#[derive(Clone)]
+pub struct TokenProvider {
+    inner: Arc<Inner>,
+}
+
+impl TokenProvider {
+    /// Provider issuing tokens from `token_url` into `token`
+    pub fn new(subscription_key: &str, token_url: &str, token: Arc<Mutex<String>>) -> Self {
+        Self::with_optional_transport(subscription_key, token_url, token, None)
+    }
+
+    /// Provider issuing tokens from `token_url` into `token`, requested
+    /// through `transport`
+    pub fn with_transport(
+        subscription_key: &str,
+        token_url: &str,
+        token: Arc<Mutex<String>>,
+        transport: Arc<Transport>,
+    ) -> Self {
+        Self::with_optional_transport(subscription_key, token_url, token, Some(transport))
+    }
+
+    fn with_optional_transport(
+        subscription_key: &str,
+        token_url: &str,
+        token: Arc<Mutex<String>>,
+        transport: Option<Arc<Transport>>,
+    ) -> Self {
+        TokenProvider {
+            inner: Arc::new(Inner {
+                token,
+                issued_at: Mutex::new(None),
+                settings: Mutex::new(Settings {
+                    max_age: DEFAULT_MAX_AGE,
+                    max_retries: 3,
+                    initial_backoff: Duration::from_millis(500),
+                }),
+                token_url: token_url.to_string(),
+                subscription_key: subscription_key.to_string(),
+                transport: Mutex::new(transport),
+                stopped: AtomicBool::new(false),
+                worker: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Refresh tokens once they're older than `max_age`
+    pub fn set_max_age(&self, max_age: Duration) {
+        self.inner.settings.lock().unwrap().max_age = max_age;
+    }
+
+    /// Retry transient failures up to `max_retries` times, doubling the
+    /// delay from `initial_backoff` after every attempt
+    pub fn set_retries(&self, max_retries: u32, initial_backoff: Duration) {
+        let mut settings = self.inner.settings.lock().unwrap();
+        settings.max_retries = max_retries;
+        settings.initial_backoff = initial_backoff;
+    }
+
+    /// Shared token storage, as passed to `Websocket::connect`
+    pub fn shared_token(&self) -> Arc<Mutex<String>> {
+        self.inner.token.clone()
+    }
+
+    /// Time since the current token was issued, if one was
+    pub fn age(&self) -> Option<Duration> {
+        self.inner
+            .issued_at
+            .lock()
+            .unwrap()
+            .map(|issued_at| issued_at.elapsed())
+    }
+
+    /// Whether there's no token or it's older than `max_age`
+    pub fn is_stale(&self) -> bool {
+        let max_age = self.inner.settings.lock().unwrap().max_age;
+        match self.age() {
+            Some(age) => age >= max_age || self.inner.token.lock().unwrap().is_empty(),
+            None => true,
+        }
+    }
+
+    /// Current token, fetching a new one first if it's stale
+    pub fn token(&self) -> Result<String> {
+        if self.is_stale() {
+            self.refresh()
+        } else {
+            Ok(self.inner.token.lock().unwrap().clone())
+        }
+    }
+
+    /// Fetch a new token regardless of the current one's age
+    ///
+    /// Network failures and server errors are retried with exponential
+    /// backoff; any other failure, e.g. a rejected subscription key, is
+    /// returned right away.
+    pub fn refresh(&self) -> Result<String> {
+        let (max_retries, mut backoff) = {
+            let settings = self.inner.settings.lock().unwrap();
+            (settings.max_retries, settings.initial_backoff)
+        };
+
+        let mut attempt = 0;
+        loop {
+            match self.request_token() {
+                Ok(token) => {
+                    self.set_token(&token);
+                    return Ok(token);
+                }
+                Err((err, retryable)) => {
+                    if !retryable || attempt >= max_retries {
+                        return Err(err);
+                    }
+                    warn!("Token refresh failed, retrying in {:?}: {}", backoff, err);
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Record a token obtained elsewhere, e.g. by `Speech::fetch_token`
+    pub fn set_token(&self, token: &str) {
+        *self.inner.token.lock().unwrap() = token.to_string();
+        *self.inner.issued_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    /// Keep the token fresh from a background thread until `stop` is called
+    ///
+    /// Does nothing if the thread is already running.
+    pub fn spawn(&self) {
+        let mut worker = self.inner.worker.lock().unwrap();
+        if worker.is_some() {
+            return;
+        }
+
+        self.inner.stopped.store(false, Ordering::SeqCst);
+        let provider = self.clone();
+        *worker = Some(thread::spawn(move || {
+            while !provider.inner.stopped.load(Ordering::SeqCst) {
+                if provider.is_stale() {
+                    if let Err(err) = provider.refresh() {
+                        error!("Failed to refresh token: {}", err);
+                    }
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }));
+    }
+
+    /// Stop the background thread and wait for it to exit
+    pub fn stop(&self) {
+        self.inner.stopped.store(true, Ordering::SeqCst);
+        let worker = self.inner.worker.lock().unwrap().take();
+        if let Some(worker) = worker {
+            let _ = worker.join();
+        }
+    }
+
+    /// Transport token requests go through
+    fn transport(&self) -> Result<Arc<Transport>> {
+        let mut transport = self.inner.transport.lock().unwrap();
+        if transport.is_none() {
+            *transport = Some(Arc::new(HyperTransport::new()?));
+        }
+        Ok(transport.as_ref().unwrap().clone())
+    }
+
+    /// Request a token, telling whether a failure is worth retrying
+    fn request_token(&self) -> ::std::result::Result<String, (Error, bool)> {
+        let result = self
+            .inner
+            .token_url
+            .parse::<Uri>()
+            .map_err(Error::from)
+            .and_then(|uri| {
+                let request = Request::builder()
+                    .method(Method::POST)
+                    .uri(uri)
+                    .header(
+                        "Ocp-Apim-Subscription-Key",
+                        self.inner.subscription_key.as_str(),
+                    )
+                    .header("Content-Length", "0")
+                    .body(Body::empty())
+                    .map_err(|err| ErrorKind::InvalidRequest(err.to_string()))?;
+                self.transport()?.send(request)
+            });
+
+        match result {
+            Ok((header, status, body)) => {
+                if let Err(err) = check_response(&header, status, &body) {
+                    let retryable = match *err.kind() {
+                        ErrorKind::RateLimited(_) | ErrorKind::ServiceUnavailable(_) => true,
+                        _ => false,
+                    };
+                    Err((err, retryable))
+                } else if body.is_empty() {
+                    Err((format!("no token issued ({})", status).into(), false))
+                } else {
+                    String::from_utf8(body).map_err(|err| (err.into(), false))
+                }
+            }
+            Err(err) => {
+                let retryable = ErrorClass::from(&err) == ErrorClass::Network;
+                Err((err, retryable))
+            }
+        }
+    }
+}
+
+/// Background token refresh started by `Speech::auto_fetch_token`
+///
+/// Refreshing stops when `stop` is called or the refresher is dropped.
+/// Failed refreshes are logged and delivered on the channel returned by
+/// `take_errors`.
+#[must_use = "the token stops being refreshed when the TokenRefresher is dropped"]
+pub struct TokenRefresher {
+    stop: Option<Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+    errors: Option<Receiver<Error>>,
+}
+
+impl TokenRefresher {
+    fn spawn(provider: TokenProvider, interval: Duration) -> Self {
+        let (stop_tx, stop_rx) = channel();
+        let (errors_tx, errors_rx) = channel();
+        let worker = thread::spawn(move || loop {
+            match stop_rx.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => break,
+            }
+            if let Err(err) = provider.refresh() {
+                warn!("Failed to refresh token: {}", err);
+                let _ = errors_tx.send(err);
+            }
+        });
+
+        TokenRefresher {
+            stop: Some(stop_tx),
+            worker: Some(worker),
+            errors: Some(errors_rx),
+        }
+    }
+
+    /// Receiver of refresh failures
+    ///
+    /// Only the first call returns the receiver.
+    pub fn take_errors(&mut self) -> Option<Receiver<Error>> {
+        self.errors.take()
+    }
+
+    /// Stop refreshing and wait for the background thread to exit
+    ///
+    /// A refresh in progress is allowed to finish.
+    pub fn stop(&mut self) {
+        self.stop.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for TokenRefresher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Speech {
+    /// Refresh the token every 9 minutes from a background thread
+    ///
+    /// Tokens are valid for 10 minutes, so call `fetch_token` first. The
+    /// refresh stops when the returned `TokenRefresher` is dropped.
+    pub fn auto_fetch_token(&self) -> TokenRefresher {
+        self.auto_fetch_token_every(DEFAULT_MAX_AGE)
+    }
+
+    /// Refresh the token every `interval` from a background thread
+    ///
+    /// Refreshes go through the provider set with `set_token_provider`, if
+    /// any, and are retried like `TokenProvider::refresh`.
+    pub fn auto_fetch_token_every(&self, interval: Duration) -> TokenRefresher {
+        let provider = match self.token_provider {
+            Some(ref provider) => provider.clone(),
+            None => self.token_provider(),
+        };
+        TokenRefresher::spawn(provider, interval)
+    }
+
+    /// Token provider sharing this client's token, subscription key, token
+    /// endpoint and transport
+    pub fn token_provider(&self) -> TokenProvider {
+        TokenProvider::with_transport(
+            &self.subscription_key,
+            &self.endpoints().token,
+            self.token.clone(),
+            self.transport.clone(),
+        )
+    }
+
+    /// Fetch tokens on demand through `provider`, or stop with `None`
+    ///
+    /// Before every request the provider's token is checked and refreshed if
+    /// it's stale. The provider's token storage replaces `self.token`.
+    pub fn set_token_provider(&mut self, provider: Option<TokenProvider>) {
+        if let Some(ref provider) = provider {
+            self.token = provider.shared_token();
+        }
+        self.token_provider = provider;
+    }
+
+    /// Make sure a fresh token is available if a provider is set
+    pub(crate) fn ensure_token(&self) -> Result<()> {
+        if let Some(ref provider) = self.token_provider {
+            provider.token()?;
+        }
+        Ok(())
+    }
+}
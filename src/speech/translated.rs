@@ -0,0 +1,106 @@
+//! Recognition followed by text translation
+//!
+//! `Speech::recognize_and_translate` recognizes speech as usual, then sends
+//! the display text of the result to Translator Text.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::speech::*;
+//! use bing_rs::translate::Translator;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.fetch_token().unwrap();
+//! speech.set_translator(Some(Translator::new("your_translator_key").unwrap()));
+//! let mode = Mode::Interactive(Language::EnglishUnitedStates);
+//! let (_, _, translated) = speech
+//!     .recognize_and_translate(vec![0; 32000], mode, "fr")
+//!     .unwrap();
+//! if let Some(translated) = translated {
+//!     println!("{} -> {}", translated.original, translated.translated);
+//! }
+//! ```
+
+use super::*;
+
+/// Recognized phrase together with its translation
+#[derive(Debug, Clone)]
+pub struct TranslatedPhrase {
+    pub phrase: Phrase,
+    /// Display text of `phrase`
+    pub original: String,
+    pub translated: String,
+    /// Language code of `translated`
+    pub to: String,
+}
+
+impl Speech {
+    /// Sets the Translator Text client used by `recognize_and_translate`
+    pub fn set_translator(&mut self, translator: Option<Translator>) {
+        self.translator = translator;
+    }
+
+    /// Recognize `audio` and translate the display text of the result into
+    /// the language `to`, e.g. "de" or "zh-Hans"
+    ///
+    /// The source language is the one of `mode`, or the identified one for
+    /// `Mode::Detect`. Results without text, such as silence, come back as
+    /// `None` without calling Translator Text.
+    /// Fails with `ErrorKind::InvalidRequest` if no translator is set.
+    pub fn recognize_and_translate(
+        &self,
+        audio: Vec<u8>,
+        mode: Mode,
+        to: &str,
+    ) -> Result<(HeaderMap, StatusCode, Option<TranslatedPhrase>)> {
+        let translator = match self.translator {
+            Some(ref translator) => translator,
+            None => bail!(ErrorKind::InvalidRequest(
+                "no translator set, see Speech::set_translator".to_string()
+            )),
+        };
+
+        let (header, status, phrase) = self.recognize(audio, mode, Format::Simple)?;
+        let phrase = match phrase {
+            Some(phrase) => phrase,
+            None => return Ok((header, status, None)),
+        };
+        let original = match phrase.text() {
+            Some(text) if !text.trim().is_empty() => text.to_string(),
+            _ => return Ok((header, status, None)),
+        };
+
+        let from = match mode {
+            Mode::Detect(_) => phrase.language().map(translator_language),
+            _ => Some(translator_language(&mode.language().to_string())),
+        };
+        let from = from.as_ref().map(|language| language.as_str());
+        let result = translator.translate_from(&original, from, to)?;
+        let translated = match result.translations.into_iter().next() {
+            Some(translation) => translation,
+            None => bail!("no translation returned for {:?}", original),
+        };
+        Ok((
+            header,
+            status,
+            Some(TranslatedPhrase {
+                phrase,
+                original,
+                translated: translated.text,
+                to: translated.to,
+            }),
+        ))
+    }
+}
+
+/// Translator Text code of the speech language `language`, e.g. "fr" for
+/// "fr-FR"
+///
+/// Translator Text tells written Chinese variants apart instead of regions.
+fn translator_language(language: &str) -> String {
+    match language {
+        "zh-CN" => "zh-Hans".to_string(),
+        "zh-HK" | "zh-TW" => "zh-Hant".to_string(),
+        _ => language.split('-').next().unwrap_or(language).to_string(),
+    }
+}
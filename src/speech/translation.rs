@@ -0,0 +1,225 @@
+//! Speech translation over the websocket
+//!
+//! The speech translation endpoint recognizes speech like the recognition
+//! one and streams the translations of every hypothesis and phrase into
+//! one or more languages. With a voice set, it also synthesizes the
+//! translated phrases and streams the audio as binary messages.
+//!
+//! Results arrive through the `Handler::on_translation_*` callbacks, and as
+//! `ServerEvent`s for `Websocket::server_event_receiver` subscribers.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::sync::{Arc, Mutex};
+//! use bing_rs::audio;
+//! use bing_rs::speech::translation::{self, TranslationPhrase};
+//! use bing_rs::speech::websocket::Handler;
+//! use bing_rs::speech::*;
+//!
+//! struct Printer;
+//!
+//! impl Handler for Printer {
+//!     fn on_translation_phrase(&mut self, phrase: TranslationPhrase) {
+//!         for translation in &phrase.translation.translations {
+//!             println!("{}: {}", translation.language, translation.text);
+//!         }
+//!     }
+//! }
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.set_region(Region::WestEurope);
+//! speech.fetch_token().unwrap();
+//!
+//! let mut session = translation::Session::new(Language::EnglishUnitedStates, &["de", "fr"]);
+//! session.set_region(Region::WestEurope);
+//! session
+//!     .connect(speech.token.clone(), Arc::new(Mutex::new(Printer)))
+//!     .unwrap();
+//! let (_, pcm) = audio::read_wav("assets/audio.wav").unwrap();
+//! session.push_audio(&pcm).unwrap();
+//! session.end_audio().unwrap();
+//! ```
+
+use super::websocket::{Handler, Websocket};
+use super::*;
+use url::Url;
+use ws;
+
+/// Translations of a recognized text
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Translations {
+    /// "Success" unless the text couldn't be translated
+    #[serde(default)]
+    pub translation_status: Option<String>,
+    #[serde(default)]
+    pub failure_reason: Option<String>,
+    #[serde(default)]
+    pub translations: Vec<LanguageText>,
+}
+
+impl Translations {
+    /// Translation into `language`, e.g. "de"
+    pub fn get(&self, language: &str) -> Option<&str> {
+        self.translations
+            .iter()
+            .find(|translation| translation.language.eq_ignore_ascii_case(language))
+            .map(|translation| translation.text.as_str())
+    }
+}
+
+/// Text translated into one language
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct LanguageText {
+    pub language: String,
+    pub text: String,
+}
+
+/// Partial translation while still in the middle of speech
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct TranslationHypothesis {
+    /// Recognized text in the source language
+    pub text: String,
+    pub offset: f64,
+    pub duration: f64,
+    #[serde(default)]
+    pub translation: Translations,
+}
+
+/// Final translation of a phrase
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct TranslationPhrase {
+    /// "Success", or why nothing was recognized, e.g. "InitialSilenceTimeout"
+    pub recognition_status: String,
+    /// Recognized text in the source language
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub offset: f64,
+    #[serde(default)]
+    pub duration: f64,
+    #[serde(default)]
+    pub translation: Translations,
+}
+
+/// End of the synthesized audio of a translated phrase
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct SynthesisEnd {
+    /// "Success" unless synthesis failed
+    pub synthesis_status: String,
+    #[serde(default)]
+    pub failure_reason: Option<String>,
+}
+
+/// Websocket session translating speech in `from` into the `to` languages
+pub struct Session {
+    websocket: Websocket,
+    from: Language,
+    to: Vec<String>,
+    voice: Option<String>,
+    region: Region,
+    endpoint: Option<String>,
+}
+
+impl Session {
+    /// Session translating speech in `from` into every language of `to`,
+    /// e.g. "de" or "zh-Hans"
+    pub fn new(from: Language, to: &[&str]) -> Self {
+        Session {
+            websocket: Websocket::new(),
+            from,
+            to: to.iter().map(|to| to.to_string()).collect(),
+            voice: None,
+            region: Region::WestUS,
+            endpoint: None,
+        }
+    }
+
+    /// Connect to the speech translation endpoint of `region` (West US by
+    /// default)
+    pub fn set_region(&mut self, region: Region) {
+        self.region = region;
+    }
+
+    /// Connect to `endpoint` instead of the regional one, e.g. to point at
+    /// `testing::MockServer`, or go back to it with `None`
+    pub fn set_endpoint(&mut self, endpoint: Option<&str>) {
+        self.endpoint = endpoint.map(String::from);
+    }
+
+    /// Synthesize the translated phrases with `voice`, e.g. "de-DE-Hedda",
+    /// or send text only with `None` (the default)
+    ///
+    /// The audio arrives through `Handler::on_translation_audio`.
+    pub fn set_voice(&mut self, voice: Option<&str>) {
+        self.voice = voice.map(String::from);
+    }
+
+    /// URL the session connects to, with the languages and voice as query
+    /// parameters
+    pub fn url(&self) -> Result<String> {
+        let base = match self.endpoint {
+            Some(ref endpoint) => endpoint.clone(),
+            None => format!(
+                "wss://{}.s2s.speech.microsoft.com/speech/translation/cognitiveservices/v1",
+                self.region
+            ),
+        };
+        if self.to.is_empty() {
+            bail!(ErrorKind::InvalidRequest("no target language".to_string()));
+        }
+
+        let mut url = Url::parse(&base)?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("from", &self.from.to_string());
+            for to in &self.to {
+                query.append_pair("to", to);
+            }
+            if let Some(ref voice) = self.voice {
+                query.append_pair("features", "texttospeech");
+                query.append_pair("voice", voice);
+            }
+        }
+        Ok(url.into_string())
+    }
+
+    /// Websocket carrying the session, e.g. to set a recorder or send the
+    /// "speech.config" message
+    pub fn websocket(&self) -> &Websocket {
+        &self.websocket
+    }
+
+    pub fn websocket_mut(&mut self) -> &mut Websocket {
+        &mut self.websocket
+    }
+
+    /// Open the connection, authenticating with `token`
+    pub fn connect(
+        &self,
+        token: Arc<Mutex<String>>,
+        handler: Arc<Mutex<Handler + Send + Sync>>,
+    ) -> Result<()> {
+        self.websocket.connect_url(self.url()?, token, handler)
+    }
+
+    /// Send audio, queueing it while the connection is opening, see
+    /// `Websocket::push_audio`
+    pub fn push_audio(&mut self, audio: &[u8]) -> ws::Result<()> {
+        self.websocket.push_audio(audio)
+    }
+
+    /// Signal the end of the audio of the current turn
+    pub fn end_audio(&mut self) -> ws::Result<()> {
+        self.websocket.end_audio()
+    }
+
+    pub fn disconnect(&mut self) -> Result<()> {
+        self.websocket.disconnect()
+    }
+}
@@ -1,15 +1,145 @@
+//! Text-to-speech voices
+//!
+//! Every voice is a `Font` constant in the module of its language, e.g.
+//! `voice::en_us::JESSA_RUS`. Look them up at runtime with `all`,
+//! `by_lang` and `by_name`. Newer voices, including neural ones, are
+//! listed by the service with `Speech::list_voices`.
+//!
+//! # Examples
+//!
+//! ```
+//! use bing_rs::speech::voice::{self, Gender};
+//!
+//! let font = voice::by_name("JessaRUS").unwrap();
+//! assert_eq!(font.lang, "en-US");
+//! assert_eq!(font.gender, Gender::Female);
+//!
+//! let male: Vec<_> = voice::by_lang("en-GB")
+//!     .into_iter()
+//!     .filter(|font| font.gender == Gender::Male)
+//!     .collect();
+//! assert_eq!(male[0].short_name(), "George, Apollo");
+//! ```
+
+// std
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+// serde_json
+use serde_json;
+
+// internal
+use errors::*;
+
+/// Gender of a voice, as sent in the SSML `xml:gender` attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Gender {
+    Female,
+    Male,
+    Neutral,
+}
+
+impl Gender {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Gender::Female => "Female",
+            Gender::Male => "Male",
+            Gender::Neutral => "Neutral",
+        }
+    }
+}
+
+impl Display for Gender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Gender {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "female" => Ok(Gender::Female),
+            "male" => Ok(Gender::Male),
+            "neutral" => Ok(Gender::Neutral),
+            _ => bail!(ErrorKind::UnknownVariant("Gender", s.to_string())),
+        }
+    }
+}
+
+/// Synthesis technology behind a voice
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VoiceType {
+    Standard,
+    Neural,
+}
+
+impl FromStr for VoiceType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "standard" => Ok(VoiceType::Standard),
+            "neural" => Ok(VoiceType::Neural),
+            _ => bail!(ErrorKind::UnknownVariant("VoiceType", s.to_string())),
+        }
+    }
+}
+
+/// Voice used for synthesis
+///
+/// The constants of this module borrow static strings; fonts returned by
+/// `Speech::list_voices` own theirs.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Font {
-    pub lang: &'static str,
-    pub gender: &'static str,
-    pub name: &'static str,
+    pub lang: Cow<'static, str>,
+    pub gender: Gender,
+    pub name: Cow<'static, str>,
+    pub voice_type: VoiceType,
+}
+
+impl Font {
+    /// Name of the voice without the service prefix and language, e.g.
+    /// "JessaRUS" or "George, Apollo"
+    pub fn short_name(&self) -> &str {
+        let name = &*self.name;
+        let start = match name.find('(') {
+            Some(open) => match name[open..].find(", ") {
+                Some(comma) => open + comma + 2,
+                None => open + 1,
+            },
+            None => return name,
+        };
+        let end = name.rfind(')').unwrap_or_else(|| name.len());
+        if start < end {
+            &name[start..end]
+        } else {
+            name
+        }
+    }
+
+    /// Whether `name` designates this voice
+    ///
+    /// Accepts the full service name, the short name, or the short name
+    /// prefixed with the language and with commas as dashes, e.g.
+    /// "en-GB-George-Apollo". Names are compared case-insensitively.
+    fn is_named(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        let short_name = self.short_name().to_lowercase();
+        let dashed = format!("{}-{}", self.lang, short_name.replace(", ", "-")).to_lowercase();
+        name == self.name.to_lowercase() || name == short_name || name == dashed
+    }
 }
 
 macro_rules! font {
-    ($var:ident { $lang:expr, $gender:expr, $name:expr }) => {
+    ($var:ident { $lang:expr, $gender:ident, $name:expr }) => {
         pub const $var: &'static super::Font = &super::Font {
-            lang: $lang,
-            gender: $gender,
-            name: $name,
+            lang: ::std::borrow::Cow::Borrowed($lang),
+            gender: super::Gender::$gender,
+            name: ::std::borrow::Cow::Borrowed($name),
+            voice_type: super::VoiceType::Standard,
         };
     };
 }
@@ -17,7 +147,7 @@ macro_rules! font {
 pub mod ar_eg {
     font!(HODA {
         "ar-EG",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (ar-EG, Hoda)"
     });
 }
@@ -25,7 +155,7 @@ pub mod ar_eg {
 pub mod ar_sa {
     font!(NAAYF {
         "ar-SA",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (ar-SA, Naayf)"
     });
 }
@@ -33,7 +163,7 @@ pub mod ar_sa {
 pub mod bg_bg {
     font!(IVAN {
         "bg-BG",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (bg-BG, Ivan)"
     });
 }
@@ -41,23 +171,27 @@ pub mod bg_bg {
 pub mod ca_es {
     font!(HERENA_RUS {
         "ca-ES",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (ca-ES, HerenaRUS)"
     });
 }
 
-pub mod ca_cz {
+pub mod cs_cz {
     font!(JAKUB {
-        "ca-CZ",
-        "Male",
+        "cs-CZ",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (cs-CZ, Jakub)"
     });
 }
 
+/// Former, misspelled name of `cs_cz`
+#[doc(hidden)]
+pub use self::cs_cz as ca_cz;
+
 pub mod da_dk {
     font!(HELLE_RUS {
         "da-DK",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (da-DK, HelleRUS)"
     });
 }
@@ -65,7 +199,7 @@ pub mod da_dk {
 pub mod de_at {
     font!(MICHAEL {
         "de-AT",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (de-AT, Michael)"
     });
 }
@@ -73,7 +207,7 @@ pub mod de_at {
 pub mod de_ch {
     font!(KARSTEN {
         "de-CH",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (de-CH, Karsten)"
     });
 }
@@ -81,19 +215,19 @@ pub mod de_ch {
 pub mod de_de {
     font!(HEDDA {
         "de-DE",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (de-DE, Hedda)"
     });
 
     font!(HEDDA_RUS {
         "de-DE",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (de-DE, HeddaRUS)"
     });
 
     font!(STEFAN_APOLLO {
         "de-DE",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (de-DE, Stefan, Apollo)"
     });
 }
@@ -101,7 +235,7 @@ pub mod de_de {
 pub mod el_gr {
     font!(STEFANOS {
         "el-GR",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (el-GR, Stefanos)"
     });
 }
@@ -109,13 +243,13 @@ pub mod el_gr {
 pub mod en_au {
     font!(CATHERINE {
         "en-AU",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-AU, Catherine)"
     });
 
     font!(HAYLEY_RUS {
         "en-AU",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-AU, HayleyRUS)"
     });
 }
@@ -123,13 +257,13 @@ pub mod en_au {
 pub mod en_ca {
     font!(LINDA {
         "en-CA",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-CA, Linda)"
     });
 
     font!(HEATHER_RUS {
         "en-CA",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-CA, HeatherRUS)"
     });
 }
@@ -137,19 +271,19 @@ pub mod en_ca {
 pub mod en_gb {
     font!(SUSAN_APOLLO {
         "en-GB",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-GB, Susan, Apollo)"
     });
 
     font!(HAZEL_RUS {
         "en-GB",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-GB, HazelRUS)"
     });
 
     font!(GEORGE_APOLLO {
         "en-GB",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (en-GB, George, Apollo)"
     });
 }
@@ -157,7 +291,7 @@ pub mod en_gb {
 pub mod en_ie {
     font!(SEAN {
         "en-IE",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (en-IE, Sean)"
     });
 }
@@ -165,19 +299,19 @@ pub mod en_ie {
 pub mod en_in {
     font!(HEERA_APOLLO {
         "en-IN",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-IN, Heera, Apollo)"
     });
 
     font!(PRIYA_RUS {
         "en-IN",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-IN, PriyaRUS)"
     });
 
     font!(RAVI_APOLLO {
         "en-IN",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (en-IN, Ravi, Apollo)"
     });
 }
@@ -185,19 +319,19 @@ pub mod en_in {
 pub mod en_us {
     font!(ZIRA_RUS {
         "en-US",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-US, ZiraRUS)"
     });
 
     font!(JESSA_RUS {
         "en-US",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (en-US, JessaRUS)"
     });
 
     font!(BENJAMIN_RUS {
         "en-US",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (en-US, BenjaminRUS)"
     });
 }
@@ -205,19 +339,19 @@ pub mod en_us {
 pub mod es_es {
     font!(LAURA_APOLLO {
         "es-ES",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (es-ES, Laura, Apollo)"
     });
 
     font!(HELENA_RUS {
         "es-ES",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (es-ES, HelenaRUS)"
     });
 
     font!(PABLO_APOLLO {
         "es-ES",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (es-ES, Pablo, Apollo)"
     });
 }
@@ -225,13 +359,13 @@ pub mod es_es {
 pub mod es_mx {
     font!(HILDA_RUS {
         "es-MX",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (es-MX, HildaRUS)"
     });
 
     font!(RAUL_APOLLO {
         "es-MX",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (es-MX, Raul, Apollo)"
     });
 }
@@ -239,7 +373,7 @@ pub mod es_mx {
 pub mod fi_fi {
     font!(HEIDI_RUS {
         "fi-FI",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (fi-FI, HeidiRUS)"
     });
 }
@@ -247,13 +381,13 @@ pub mod fi_fi {
 pub mod fr_ca {
     font!(CAROLINE {
         "fr-CA",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (fr-CA, Caroline)"
     });
 
     font!(HARMONIE_RUS {
         "fr-CA",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (fr-CA, HarmonieRUS)"
     });
 }
@@ -261,7 +395,7 @@ pub mod fr_ca {
 pub mod fr_ch {
     font!(GUILLAUME {
         "fr-CH",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (fr-CH, Guillaume)"
     });
 }
@@ -269,19 +403,19 @@ pub mod fr_ch {
 pub mod fr_fr {
     font!(JULIE_APOLLO {
         "fr-FR",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (fr-FR, JulieApollo)"
     });
 
     font!(HORTENSE_RUS {
         "fr-FR",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (fr-FR, HortenseRUS)"
     });
 
     font!(PAUL_APOLLO {
         "fr-FR",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (fr-FR, PaulApollo)"
     });
 }
@@ -289,7 +423,7 @@ pub mod fr_fr {
 pub mod he_il {
     font!(ASAF {
         "he-IL",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (he-IL, Asaf)"
     });
 }
@@ -297,19 +431,19 @@ pub mod he_il {
 pub mod hi_in {
     font!(KALPANA_APOLLO {
         "hi-IN",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (hi-IN, Kalpana, Apollo)"
     });
 
     font!(KALPANA {
         "hi-IN",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (hi-IN, Kalpana)"
     });
 
     font!(HEMANT {
         "hi-IN",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (hi-IN, Hemant)"
     });
 }
@@ -317,7 +451,7 @@ pub mod hi_in {
 pub mod hr_hr {
     font!(MATEJ {
         "hr-HR",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (hr-HR, Matej)"
     });
 }
@@ -325,7 +459,7 @@ pub mod hr_hr {
 pub mod hu_hu {
     font!(SZABOLCS {
         "hu-HU",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (hu-HU, Szabolcs)"
     });
 }
@@ -333,7 +467,7 @@ pub mod hu_hu {
 pub mod id_id {
     font!(ANDIKA {
         "id-ID",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (id-ID, Andika)"
     });
 }
@@ -341,7 +475,7 @@ pub mod id_id {
 pub mod it_it {
     font!(COSIMA_APOLLO {
         "it-IT",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (it-IT, Cosimo, Apollo)"
     });
 }
@@ -349,31 +483,31 @@ pub mod it_it {
 pub mod ja_jp {
     font!(AYUMI_APOLLO {
         "ja-JP",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (ja-JP, Ayumi, Apollo)"
     });
 
     font!(ICHIRO_APOLLO {
         "ja-JP",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (ja-JP, Ichiro, Apollo)"
     });
 
     font!(HARUKA_RUS {
         "ja-JP",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (ja-JP, HarukaRUS)"
     });
 
     font!(LUCIA_RUS {
         "ja-JP",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (ja-JP, LuciaRUS)"
     });
 
     font!(EKATERINA_RUS {
         "ja-JP",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (ja-JP, EkaterinaRUS)"
     });
 }
@@ -381,7 +515,7 @@ pub mod ja_jp {
 pub mod ko_kr {
     font!(HEAMI_RUS {
         "ko-KR",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (ko-KR, HeamiRUS)"
     });
 }
@@ -389,7 +523,7 @@ pub mod ko_kr {
 pub mod ms_my {
     font!(RIZWAN {
         "ms-MY",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (ms-MY, Rizwan)"
     });
 }
@@ -397,7 +531,7 @@ pub mod ms_my {
 pub mod nb_no {
     font!(HULDA_RUS {
         "nb-NO",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (nb-NO, HuldaRUS)"
     });
 }
@@ -405,7 +539,7 @@ pub mod nb_no {
 pub mod nl_nl {
     font!(HANNA_RUS {
         "nl-NL",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (nl-NL, HannaRUS)"
     });
 }
@@ -413,7 +547,7 @@ pub mod nl_nl {
 pub mod pl_pl {
     font!(PAULINA_RUS {
         "pl-PL",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (pl-PL, PaulinaRUS)"
     });
 }
@@ -421,13 +555,13 @@ pub mod pl_pl {
 pub mod pt_br {
     font!(HELOISA_RUS {
         "pt-BR",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (pt-BR, HeloisaRUS)"
     });
 
     font!(DANIEL_APOLLO {
         "pt-BR",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (pt-BR, DanielApollo)"
     });
 }
@@ -435,7 +569,7 @@ pub mod pt_br {
 pub mod pt_pt {
     font!(HELIA_RUS {
         "pt-PT",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (pt-PT, HeliaRUS)"
     });
 }
@@ -443,7 +577,7 @@ pub mod pt_pt {
 pub mod ro_ro {
     font!(ANDREI {
         "ro-RO",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (ro-RO, Andrei)"
     });
 }
@@ -451,13 +585,13 @@ pub mod ro_ro {
 pub mod ru_ru {
     font!(IRINA_APOLLO {
         "ru-RU",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (ru-RU, Irina, Apollo)"
     });
 
     font!(PAVEL_APOLLO {
         "ru-RU",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (ru-RU, Pavel, Apollo)"
     });
 }
@@ -465,7 +599,7 @@ pub mod ru_ru {
 pub mod sk_sk {
     font!(FILIP {
         "sk-SK",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (sk-SK, Filip)"
     });
 }
@@ -473,7 +607,7 @@ pub mod sk_sk {
 pub mod sl_si {
     font!(LADO {
         "sl-SI",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (sl-SI, Lado)"
     });
 }
@@ -481,7 +615,7 @@ pub mod sl_si {
 pub mod sv_se {
     font!(HEDVIG_RUS {
         "sv-SE",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (sv-SE, HedvigRUS)"
     });
 }
@@ -489,7 +623,7 @@ pub mod sv_se {
 pub mod ta_in {
     font!(VALLUVAR {
         "ta-IN",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (ta-IN, Valluvar)"
     });
 }
@@ -497,7 +631,7 @@ pub mod ta_in {
 pub mod th_th {
     font!(PATTARA {
         "th-TH",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (th-TH, Pattara)"
     });
 }
@@ -505,7 +639,7 @@ pub mod th_th {
 pub mod tr_tr {
     font!(SEDA_RUS {
         "tr-TR",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (tr-TR, SedaRUS)"
     });
 }
@@ -513,7 +647,7 @@ pub mod tr_tr {
 pub mod vi_vn {
     font!(AN {
         "vi-VN",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (vi-VN, An)"
     });
 }
@@ -521,19 +655,19 @@ pub mod vi_vn {
 pub mod zh_cn {
     font!(HUIHUI_RUS {
         "zh-CN",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (zh-CN, HuihuiRUS)"
     });
 
     font!(YAOYAO_APOLLO {
         "zh-CN",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (zh-CN, Yaoyao, Apollo)"
     });
 
     font!(KANGKANG_APOLLO {
         "zh-CN",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (zh-CN, Kangkang, Apollo)"
     });
 }
@@ -541,19 +675,19 @@ pub mod zh_cn {
 pub mod zh_hk {
     font!(TRACY_APOLLO {
         "zh-HK",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (zh-HK, Tracy, Apollo)"
     });
 
     font!(TRACY_RUS {
         "zh-HK",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (zh-HK, TracyRUS)"
     });
 
     font!(DANNY_APOLLO {
         "zh-HK",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (zh-HK, Danny, Apollo)"
     });
 }
@@ -561,19 +695,225 @@ pub mod zh_hk {
 pub mod zh_tw {
     font!(YATING_APOLLO {
         "zh-TW",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (zh-TW, Yating, Apollo)"
     });
 
     font!(HANHAN_RUS {
         "zh-TW",
-        "Female",
+        Female,
         "Microsoft Server Speech Text to Speech Voice (zh-TW, HanHanRUS)"
     });
 
     font!(ZHIWEI_APOLLO {
         "zh-TW",
-        "Male",
+        Male,
         "Microsoft Server Speech Text to Speech Voice (zh-TW, Zhiwei, Apollo)"
     });
 }
+
+/// Default voice for a language code such as "en-US"
+///
+/// Language codes are compared case-insensitively. Returns `None` for
+/// languages without a voice.
+pub fn default_for(lang: &str) -> Option<&'static Font> {
+    let font = match lang.to_lowercase().as_str() {
+        "ar-eg" => ar_eg::HODA,
+        "ar-sa" => ar_sa::NAAYF,
+        "bg-bg" => bg_bg::IVAN,
+        "ca-es" => ca_es::HERENA_RUS,
+        "cs-cz" => cs_cz::JAKUB,
+        "da-dk" => da_dk::HELLE_RUS,
+        "de-at" => de_at::MICHAEL,
+        "de-ch" => de_ch::KARSTEN,
+        "de-de" => de_de::HEDDA,
+        "el-gr" => el_gr::STEFANOS,
+        "en-au" => en_au::CATHERINE,
+        "en-ca" => en_ca::LINDA,
+        "en-gb" => en_gb::SUSAN_APOLLO,
+        "en-ie" => en_ie::SEAN,
+        "en-in" => en_in::HEERA_APOLLO,
+        "en-us" => en_us::JESSA_RUS,
+        "es-es" => es_es::LAURA_APOLLO,
+        "es-mx" => es_mx::HILDA_RUS,
+        "fi-fi" => fi_fi::HEIDI_RUS,
+        "fr-ca" => fr_ca::CAROLINE,
+        "fr-ch" => fr_ch::GUILLAUME,
+        "fr-fr" => fr_fr::JULIE_APOLLO,
+        "he-il" => he_il::ASAF,
+        "hi-in" => hi_in::KALPANA_APOLLO,
+        "hr-hr" => hr_hr::MATEJ,
+        "hu-hu" => hu_hu::SZABOLCS,
+        "id-id" => id_id::ANDIKA,
+        "it-it" => it_it::COSIMA_APOLLO,
+        "ja-jp" => ja_jp::AYUMI_APOLLO,
+        "ko-kr" => ko_kr::HEAMI_RUS,
+        "ms-my" => ms_my::RIZWAN,
+        "nb-no" => nb_no::HULDA_RUS,
+        "nl-nl" => nl_nl::HANNA_RUS,
+        "pl-pl" => pl_pl::PAULINA_RUS,
+        "pt-br" => pt_br::HELOISA_RUS,
+        "pt-pt" => pt_pt::HELIA_RUS,
+        "ro-ro" => ro_ro::ANDREI,
+        "ru-ru" => ru_ru::IRINA_APOLLO,
+        "sk-sk" => sk_sk::FILIP,
+        "sl-si" => sl_si::LADO,
+        "sv-se" => sv_se::HEDVIG_RUS,
+        "ta-in" => ta_in::VALLUVAR,
+        "th-th" => th_th::PATTARA,
+        "tr-tr" => tr_tr::SEDA_RUS,
+        "vi-vn" => vi_vn::AN,
+        "zh-cn" => zh_cn::HUIHUI_RUS,
+        "zh-hk" => zh_hk::TRACY_APOLLO,
+        "zh-tw" => zh_tw::YATING_APOLLO,
+        _ => return None,
+    };
+    Some(font)
+}
+
+static ALL: &[&Font] = &[
+    ar_eg::HODA,
+    ar_sa::NAAYF,
+    bg_bg::IVAN,
+    ca_es::HERENA_RUS,
+    cs_cz::JAKUB,
+    da_dk::HELLE_RUS,
+    de_at::MICHAEL,
+    de_ch::KARSTEN,
+    de_de::HEDDA,
+    de_de::HEDDA_RUS,
+    de_de::STEFAN_APOLLO,
+    el_gr::STEFANOS,
+    en_au::CATHERINE,
+    en_au::HAYLEY_RUS,
+    en_ca::LINDA,
+    en_ca::HEATHER_RUS,
+    en_gb::SUSAN_APOLLO,
+    en_gb::HAZEL_RUS,
+    en_gb::GEORGE_APOLLO,
+    en_ie::SEAN,
+    en_in::HEERA_APOLLO,
+    en_in::PRIYA_RUS,
+    en_in::RAVI_APOLLO,
+    en_us::ZIRA_RUS,
+    en_us::JESSA_RUS,
+    en_us::BENJAMIN_RUS,
+    es_es::LAURA_APOLLO,
+    es_es::HELENA_RUS,
+    es_es::PABLO_APOLLO,
+    es_mx::HILDA_RUS,
+    es_mx::RAUL_APOLLO,
+    fi_fi::HEIDI_RUS,
+    fr_ca::CAROLINE,
+    fr_ca::HARMONIE_RUS,
+    fr_ch::GUILLAUME,
+    fr_fr::JULIE_APOLLO,
+    fr_fr::HORTENSE_RUS,
+    fr_fr::PAUL_APOLLO,
+    he_il::ASAF,
+    hi_in::KALPANA_APOLLO,
+    hi_in::KALPANA,
+    hi_in::HEMANT,
+    hr_hr::MATEJ,
+    hu_hu::SZABOLCS,
+    id_id::ANDIKA,
+    it_it::COSIMA_APOLLO,
+    ja_jp::AYUMI_APOLLO,
+    ja_jp::ICHIRO_APOLLO,
+    ja_jp::HARUKA_RUS,
+    ja_jp::LUCIA_RUS,
+    ja_jp::EKATERINA_RUS,
+    ko_kr::HEAMI_RUS,
+    ms_my::RIZWAN,
+    nb_no::HULDA_RUS,
+    nl_nl::HANNA_RUS,
+    pl_pl::PAULINA_RUS,
+    pt_br::HELOISA_RUS,
+    pt_br::DANIEL_APOLLO,
+    pt_pt::HELIA_RUS,
+    ro_ro::ANDREI,
+    ru_ru::IRINA_APOLLO,
+    ru_ru::PAVEL_APOLLO,
+    sk_sk::FILIP,
+    sl_si::LADO,
+    sv_se::HEDVIG_RUS,
+    ta_in::VALLUVAR,
+    th_th::PATTARA,
+    tr_tr::SEDA_RUS,
+    vi_vn::AN,
+    zh_cn::HUIHUI_RUS,
+    zh_cn::YAOYAO_APOLLO,
+    zh_cn::KANGKANG_APOLLO,
+    zh_hk::TRACY_APOLLO,
+    zh_hk::TRACY_RUS,
+    zh_hk::DANNY_APOLLO,
+    zh_tw::YATING_APOLLO,
+    zh_tw::HANHAN_RUS,
+    zh_tw::ZHIWEI_APOLLO,
+];
+
+/// Every voice, grouped by language
+pub fn all() -> &'static [&'static Font] {
+    ALL
+}
+
+/// Voices for a language code such as "en-US", compared case-insensitively
+pub fn by_lang(lang: &str) -> Vec<&'static Font> {
+    ALL.iter()
+        .cloned()
+        .filter(|font| font.lang.eq_ignore_ascii_case(lang))
+        .collect()
+}
+
+/// Voice called `name`, e.g. "JessaRUS", "en-US-JessaRUS" or the full
+/// service name
+pub fn by_name(name: &str) -> Option<&'static Font> {
+    ALL.iter().cloned().find(|font| font.is_named(name))
+}
+
+/// Entry of the service's voice list
+#[derive(Deserialize)]
+struct ListedVoice {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "Gender")]
+    gender: String,
+    #[serde(rename = "Locale")]
+    locale: String,
+    #[serde(rename = "VoiceType", default)]
+    voice_type: Option<String>,
+}
+
+/// Parse the JSON voice list returned by the service
+///
+/// Voices with an unknown gender or type are skipped with a warning.
+pub(crate) fn fonts_from_list(body: &[u8]) -> Result<Vec<Font>> {
+    let voices: Vec<ListedVoice> = serde_json::from_slice(body)?;
+    let mut fonts = Vec::with_capacity(voices.len());
+    for voice in voices {
+        let gender = match voice.gender.parse() {
+            Ok(gender) => gender,
+            Err(err) => {
+                warn!("Skipping voice {}: {}", voice.name, err);
+                continue;
+            }
+        };
+        let voice_type = match voice.voice_type {
+            Some(ref voice_type) => match voice_type.parse() {
+                Ok(voice_type) => voice_type,
+                Err(err) => {
+                    warn!("Skipping voice {}: {}", voice.name, err);
+                    continue;
+                }
+            },
+            None => VoiceType::Standard,
+        };
+        fonts.push(Font {
+            lang: Cow::Owned(voice.locale),
+            gender,
+            name: Cow::Owned(voice.name),
+            voice_type,
+        });
+    }
+    Ok(fonts)
+}
@@ -1,13 +1,32 @@
-use std::sync::{Arc, Mutex};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
-use url::Url;
+use std::time::{Duration, Instant};
+use url::{form_urlencoded, Url};
 use uuid::Uuid;
 use ws;
 
 use chrono::prelude::*;
+use hyper::header::HeaderValue;
 use serde_json;
 
+use audio::endpoint::{Endpoint, Endpointer};
+use audio::wav::{self, WavHeader};
+use audio::{self, Processor};
+use budget::Budget;
+use client::Proxy;
+use metrics::{self, MetricsSink, RequestKind};
+use speech::events::EventLog;
+use speech::intent::{IntentApp, IntentResult};
+use speech::protocol;
+use speech::record::{Direction, Recorder};
+use speech::telemetry::{Telemetry, TelemetryTracker};
+use speech::translation::{SynthesisEnd, TranslationHypothesis, TranslationPhrase};
 use speech::*;
+use stats::{ClientStats, Metric, StatsTracker};
+use trace::{self, TraceHook};
 
 /// Server event handler
 pub trait Handler {
@@ -15,11 +34,163 @@ pub trait Handler {
     fn on_turn_end(&mut self) {}
     fn on_speech_start(&mut self) {}
     fn on_speech_end(&mut self) {}
+    /// Voice was detected in the outgoing audio, when a `RecognitionSession`
+    /// only sends voice
+    fn on_local_speech_start(&mut self) {}
+    /// The voice detected in the outgoing audio ended
+    fn on_local_speech_end(&mut self) {}
     fn on_speech_hypothesis(&mut self, _hypothesis: Hypothesis) {}
     fn on_speech_phrase(&mut self, _phrase: Phrase) {}
+    /// A phrase was attributed to a different speaker than the previous
+    /// one, called before `on_speech_phrase`
+    fn on_speaker_change(&mut self, _speaker_id: &str) {}
+    /// LUIS result for the last phrase, when an intent app is set
+    fn on_intent(&mut self, _result: IntentResult) {}
+    /// Every text message from the server, before the crate parses it
+    ///
+    /// Lets applications handle paths without a dedicated callback, such as
+    /// "speech.fragment". `path` is empty if the message has no Path header.
+    fn on_raw_message(&mut self, _path: &str, _headers: &[(&str, &str)], _body: &str) {}
+    /// The handshake completed; `connection_id` is the X-ConnectionId sent
+    fn on_connect(&mut self, _connection_id: &str) {}
+    /// The connection was closed with the websocket close `code`; `reason`
+    /// is empty unless the server gave one
+    fn on_disconnect(&mut self, _code: u16, _reason: &str) {}
+    /// The connection failed or broke with `err`
+    fn on_error(&mut self, _err: &Error) {}
+    /// The connection was re-established after `attempt` tries
+    fn on_reconnect(&mut self, _attempt: u32) {}
+    /// Partial translation from a `translation::Session`
+    fn on_translation_hypothesis(&mut self, _hypothesis: TranslationHypothesis) {}
+    /// Final translation from a `translation::Session`
+    fn on_translation_phrase(&mut self, _phrase: TranslationPhrase) {}
+    /// Chunk of the audio synthesized for a translated phrase, when the
+    /// session has a voice set
+    fn on_translation_audio(&mut self, _audio: &[u8]) {}
+    /// All the audio of the translated phrase was sent
+    fn on_translation_audio_end(&mut self, _end: SynthesisEnd) {}
+}
+
+/// Handler ignoring every event, for use with `Websocket::server_event_receiver`
+pub struct NoopHandler;
+
+impl Handler for NoopHandler {}
+
+/// When and how often to re-establish dropped connections
+///
+/// Attempt `n` waits `initial_backoff * 2^(n - 1)`, capped at `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReconnectPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// How long to wait for each attempt's handshake
+    pub connect_timeout: Duration,
+}
+
+impl ReconnectPolicy {
+    /// Policy giving up after `max_attempts` with the default delays
+    pub fn new(max_attempts: u32) -> Self {
+        ReconnectPolicy {
+            max_attempts,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before attempt `attempt`, starting at 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u32 << attempt.saturating_sub(1).min(16);
+        (self.initial_backoff * factor).min(self.max_backoff)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Number of messages `push_audio` buffers by default
+pub const DEFAULT_SEND_QUEUE_CAPACITY: usize = 100;
+
+/// What `Websocket::push_audio` does when the send queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backpressure {
+    /// Wait until the connection opens and the queue drains
+    ///
+    /// Gives up with an error if the connection is closed or lost for good.
+    Block,
+    /// Fail right away
+    Error,
+}
+
+/// Connection state shared by a `Websocket` and its handlers
+struct Shared {
+    /// The handshake completed and the connection hasn't closed since
+    open: AtomicBool,
+    /// `disconnect` was called, so closing is expected
+    closing: AtomicBool,
+    /// An open connection dropped and hasn't been re-established
+    lost: AtomicBool,
+    reconnecting: AtomicBool,
+    /// Body of the last "speech.config" message, re-sent after reconnecting
+    config: Mutex<Option<String>>,
+    /// Messages waiting for the connection to open, sent in order
+    queue: Mutex<VecDeque<ws::Message>>,
+    /// Signalled whenever the queue is flushed or the connection goes away
+    queue_space: Condvar,
+    /// X-ConnectionId of the current or last connection
+    connection_id: Mutex<Option<String>>,
+}
+
+impl Shared {
+    fn new() -> Self {
+        Shared {
+            open: AtomicBool::new(false),
+            closing: AtomicBool::new(false),
+            lost: AtomicBool::new(false),
+            reconnecting: AtomicBool::new(false),
+            config: Mutex::new(None),
+            queue: Mutex::new(VecDeque::new()),
+            queue_space: Condvar::new(),
+            connection_id: Mutex::new(None),
+        }
+    }
+
+    /// Whether queued messages can't be sent anymore
+    fn is_gone(&self) -> bool {
+        self.closing.load(Ordering::SeqCst)
+            || (self.lost.load(Ordering::SeqCst) && !self.reconnecting.load(Ordering::SeqCst))
+    }
+
+    /// Send the queued messages on `sender` in order
+    fn flush_queue(&self, sender: &ws::Sender) {
+        let mut queue = self.queue.lock().unwrap();
+        while let Some(msg) = queue.pop_front() {
+            if let Err(err) = sender.send(msg) {
+                warn!("Failed to send queued message: {}", err);
+                break;
+            }
+        }
+        self.queue_space.notify_all();
+    }
+}
+
+/// Where and how to reconnect
+struct Reconnect {
+    policy: ReconnectPolicy,
+    url: String,
+    metrics: Arc<MetricsSink>,
 }
 
 /// Enum of event that comes from server
+///
+/// Text messages are decoded into these by `protocol::parse_server_message`.
 #[derive(Clone)]
 pub enum ServerEvent {
     Connect(ws::Sender),
@@ -29,49 +200,138 @@ pub enum ServerEvent {
     SpeechHypothesis(Hypothesis),
     SpeechEndDetected,
     SpeechPhrase(Phrase),
+    Intent(IntentResult),
+    TranslationHypothesis(TranslationHypothesis),
+    TranslationPhrase(TranslationPhrase),
+    TranslationAudio(Vec<u8>),
+    /// All synthesized audio of the current translation has been sent
+    TranslationAudioEnd(SynthesisEnd),
     TurnEnd,
+    /// Message with a path this crate doesn't handle
     Unknown,
 }
 
+/// Channels subscribed with `Websocket::server_event_receiver`
+#[derive(Clone, Default)]
+pub(crate) struct ServerEvents {
+    senders: Arc<Mutex<Vec<Sender<ServerEvent>>>>,
+}
+
+impl ServerEvents {
+    fn subscribe(&self) -> Receiver<ServerEvent> {
+        let (tx, rx) = channel();
+        self.senders.lock().unwrap().push(tx);
+        rx
+    }
+
+    fn is_empty(&self) -> bool {
+        self.senders.lock().unwrap().is_empty()
+    }
+
+    /// Send `event` to every subscriber, dropping those that went away
+    fn send(&self, event: ServerEvent) {
+        self.senders
+            .lock()
+            .unwrap()
+            .retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
+
 pub struct Websocket {
     sender: Arc<Mutex<Option<ws::Sender>>>,
     audio_uuid: Arc<Mutex<Option<String>>>,
+    processors: Vec<Box<Processor + Send>>,
+    endpointer: Option<Endpointer>,
+    metrics: Arc<MetricsSink>,
+    trace: Arc<TraceHook>,
+    recorder: Option<Recorder>,
+    event_log: Option<EventLog>,
+    correlation_id: Option<String>,
+    endpoints: Option<Endpoints>,
+    custom_speech_endpoints: Option<Endpoints>,
+    stats: StatsTracker,
+    budget: Option<Budget>,
+    reconnect: Option<ReconnectPolicy>,
+    shared: Arc<Shared>,
+    telemetry: TelemetryTracker,
+    auto_telemetry: bool,
+    params: RecognitionParams,
+    intent_app: Option<IntentApp>,
+    events: ServerEvents,
+    proxy: Option<Proxy>,
+    queue_capacity: usize,
+    backpressure: Backpressure,
 }
 
 pub struct MyHandler {
     token: Arc<Mutex<String>>,
     handler: Arc<Mutex<Handler + Send + Sync>>,
     audio_uuid: Arc<Mutex<Option<String>>>,
+    recorder: Option<Recorder>,
+    event_log: Option<EventLog>,
+    correlation_id: Option<String>,
+    connection_id: Option<String>,
+    last_speaker: Option<String>,
+    stats: StatsTracker,
+    first_hypothesis: Option<Instant>,
+    /// When the connection was started, for the metrics sink
+    started: Instant,
+    opened: bool,
+    completed: bool,
+    factory: Factory,
 }
 
+#[derive(Clone)]
 struct Factory {
     sender: Arc<Mutex<Option<ws::Sender>>>,
     token: Arc<Mutex<String>>,
     handler: Arc<Mutex<Handler + Send + Sync>>,
     audio_uuid: Arc<Mutex<Option<String>>>,
+    recorder: Option<Recorder>,
+    event_log: Option<EventLog>,
+    correlation_id: Option<String>,
+    stats: StatsTracker,
+    shared: Arc<Shared>,
+    reconnect: Option<Arc<Reconnect>>,
+    telemetry: TelemetryTracker,
+    auto_telemetry: bool,
+    events: ServerEvents,
+    trace: Arc<TraceHook>,
+    metrics: Arc<MetricsSink>,
 }
 
-impl ws::Factory for Factory {
-    type Handler = MyHandler;
-
-    fn connection_made(&mut self, sender: ws::Sender) -> MyHandler {
-        *self.sender.lock().unwrap() = Some(sender);
-
+impl Factory {
+    fn handler(&self) -> MyHandler {
         MyHandler {
             token: self.token.clone(),
             handler: self.handler.clone(),
             audio_uuid: self.audio_uuid.clone(),
+            recorder: self.recorder.clone(),
+            event_log: self.event_log.clone(),
+            correlation_id: self.correlation_id.clone(),
+            connection_id: None,
+            last_speaker: None,
+            stats: self.stats.clone(),
+            first_hypothesis: None,
+            started: Instant::now(),
+            opened: false,
+            completed: false,
+            factory: self.clone(),
         }
     }
+}
 
-    fn client_connected(&mut self, sender: ws::Sender) -> MyHandler {
+impl ws::Factory for Factory {
+    type Handler = MyHandler;
+
+    fn connection_made(&mut self, sender: ws::Sender) -> MyHandler {
         *self.sender.lock().unwrap() = Some(sender);
+        self.handler()
+    }
 
-        MyHandler {
-            token: self.token.clone(),
-            handler: self.handler.clone(),
-            audio_uuid: self.audio_uuid.clone(),
-        }
+    fn client_connected(&mut self, sender: ws::Sender) -> MyHandler {
+        *self.sender.lock().unwrap() = Some(sender);
+        self.handler()
     }
 }
 
@@ -80,30 +340,377 @@ impl Websocket {
         let sender = Arc::new(Mutex::new(None));
         let audio_uuid = Arc::new(Mutex::new(None));
 
-        Websocket { sender, audio_uuid }
+        Websocket {
+            sender,
+            audio_uuid,
+            processors: Vec::new(),
+            endpointer: None,
+            metrics: metrics::noop(),
+            trace: trace::noop(),
+            recorder: None,
+            event_log: None,
+            correlation_id: None,
+            endpoints: None,
+            custom_speech_endpoints: None,
+            stats: StatsTracker::new(),
+            budget: None,
+            reconnect: None,
+            shared: Arc::new(Shared::new()),
+            telemetry: TelemetryTracker::new(),
+            auto_telemetry: true,
+            params: RecognitionParams::default(),
+            intent_app: None,
+            events: ServerEvents::default(),
+            proxy: None,
+            queue_capacity: DEFAULT_SEND_QUEUE_CAPACITY,
+            backpressure: Backpressure::Block,
+        }
+    }
+
+    /// Latency statistics of the sessions so far
+    pub fn stats(&self) -> ClientStats {
+        self.stats.snapshot()
+    }
+
+    /// Report latency statistics into `stats`, e.g. `Speech::stats_tracker()`
+    pub fn set_stats_tracker(&mut self, stats: StatsTracker) {
+        self.stats = stats;
+    }
+
+    /// Count audio sent against `budget`, or stop with `None`
+    ///
+    /// Audio that would exceed the hard limit isn't sent and `audio()`
+    /// returns an error instead.
+    pub fn set_budget(&mut self, budget: Option<Budget>) {
+        self.budget = budget;
+    }
+
+    /// Override the service URLs, or go back to the defaults with `None`
+    pub fn set_endpoints(&mut self, endpoints: Option<Endpoints>) {
+        self.endpoints = endpoints;
+    }
+
+    /// Re-establish dropped connections according to `policy`, or leave
+    /// them closed with `None`
+    ///
+    /// Applies to connections made afterwards. After reconnecting the last
+    /// "speech.config" message is sent again and `Handler::on_reconnect` is
+    /// called.
+    pub fn set_reconnect(&mut self, policy: Option<ReconnectPolicy>) {
+        self.reconnect = policy;
+    }
+
+    /// How profanity is handled in recognition results, or `None` for the
+    /// service default (masked)
+    ///
+    /// Applies to connections made afterwards.
+    pub fn set_profanity(&mut self, profanity: Option<Profanity>) {
+        self.params.profanity = profanity;
+    }
+
+    /// Include per-word offsets and durations in detailed results
+    ///
+    /// Applies to connections made afterwards.
+    pub fn set_word_level_timestamps(&mut self, word_level_timestamps: bool) {
+        self.params.word_level_timestamps = word_level_timestamps;
+    }
+
+    /// Set every optional query parameter of the websocket URL at once
+    pub fn set_recognition_params(&mut self, params: RecognitionParams) {
+        self.params = params;
+    }
+
+    /// Run recognized phrases through the LUIS app `app_id`, authenticated
+    /// with its subscription `key`
+    ///
+    /// Results arrive through `Handler::on_intent`. Applies to connections
+    /// made afterwards.
+    pub fn set_intent_app(&mut self, app_id: &str, key: &str) {
+        self.intent_app = Some(IntentApp::new(app_id, key));
+    }
+
+    /// Stop sending phrases to a LUIS app
+    pub fn clear_intent_app(&mut self) {
+        self.intent_app = None;
+    }
+
+    /// Sets the proxy connections should go through, or clears it with
+    /// `None`
+    ///
+    /// None by default: unlike REST requests, websockets ignore the
+    /// HTTPS_PROXY and HTTP_PROXY environment variables. The `ws` crate
+    /// can't tunnel connections yet, so connecting fails with
+    /// `ErrorKind::InvalidRequest` while a proxy set here applies to the
+    /// endpoint.
+    pub fn set_proxy(&mut self, proxy: Option<Proxy>) {
+        self.proxy = proxy;
+    }
+
+    /// Buffer up to `capacity` messages sent by `push_audio` while the
+    /// connection isn't open, handling a full queue with `backpressure`
+    ///
+    /// Defaults to `DEFAULT_SEND_QUEUE_CAPACITY` and `Backpressure::Block`.
+    pub fn set_send_queue(&mut self, capacity: usize, backpressure: Backpressure) {
+        self.queue_capacity = capacity.max(1);
+        self.backpressure = backpressure;
+    }
+
+    /// Number of messages waiting for the connection to open
+    pub fn queued(&self) -> usize {
+        self.shared.queue.lock().unwrap().len()
+    }
+
+    /// Connect to the websocket endpoint of `region`
+    pub fn set_region(&mut self, region: Region) {
+        self.set_endpoints(Some(Endpoints::region(region)));
+    }
+
+    /// Endpoints of the Custom Speech deployment used when connecting with
+    /// custom speech, or West US with `None`
+    ///
+    /// Ignored if endpoints were set with `set_endpoints`.
+    pub fn set_custom_speech_endpoints(&mut self, endpoints: Option<Endpoints>) {
+        self.custom_speech_endpoints = endpoints;
+    }
+
+    /// Use the Custom Speech deployment in `region` instead of West US
+    pub fn set_custom_speech_region(&mut self, region: Region) {
+        self.custom_speech_endpoints = Some(Endpoints::custom_speech_region(region));
+    }
+
+    /// Connect custom speech recognitions to the host of `url`, e.g.
+    /// "https://westeurope.stt.speech.microsoft.com"
+    pub fn set_custom_speech_endpoint(&mut self, url: &str) -> Result<()> {
+        let endpoints = self
+            .custom_speech_endpoints
+            .clone()
+            .unwrap_or_else(Endpoints::custom_speech)
+            .with_recognition_host(url)?;
+        self.custom_speech_endpoints = Some(endpoints);
+        Ok(())
+    }
+
+    /// Send telemetry automatically at the end of every turn (the default),
+    /// or leave it to `send_telemetry`
+    pub fn set_auto_telemetry(&mut self, auto_telemetry: bool) {
+        self.auto_telemetry = auto_telemetry;
+    }
+
+    /// Telemetry collected for the current turn
+    pub fn telemetry(&self) -> TelemetryTracker {
+        self.telemetry.clone()
+    }
+
+    /// Send the telemetry collected for the current turn and start a new one
+    pub fn send_telemetry(&self) -> ws::Result<()> {
+        let request_id = self
+            .audio_uuid
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(generate_uuid);
+        match *self.sender.lock().unwrap() {
+            Some(ref sender) => send_telemetry(
+                sender,
+                &request_id,
+                &self.telemetry.take_turn(),
+                self.recorder.as_ref(),
+                self.event_log.as_ref(),
+                &*self.trace,
+            ),
+            None => Ok(()),
+        }
+    }
+
+    /// Channel receiving every server event, as an alternative to `Handler`
+    ///
+    /// Each call returns a new receiver; all of them get every event from
+    /// then on, including those of connections already open. Pass
+    /// `NoopHandler` to `connect` when only the channel is needed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::sync::{Arc, Mutex};
+    /// use bing_rs::speech::*;
+    /// use bing_rs::speech::websocket::*;
+    ///
+    /// let speech = Speech::new("your_subscription_key").unwrap();
+    /// let ws = Websocket::new();
+    /// let events = ws.server_event_receiver();
+    /// let mode = Mode::Interactive(Language::EnglishUnitedStates);
+    /// let handler = Arc::new(Mutex::new(NoopHandler));
+    /// ws.connect(speech.token.clone(), mode, Format::Simple, false, "", handler)
+    ///     .unwrap();
+    /// for event in events {
+    ///     if let ServerEvent::SpeechPhrase(phrase) = event {
+    ///         println!("{}", phrase);
+    ///     }
+    /// }
+    /// ```
+    pub fn server_event_receiver(&self) -> Receiver<ServerEvent> {
+        self.events.subscribe()
+    }
+
+    /// Record every frame sent and received on subsequent connections
+    ///
+    /// See `speech::record` for replaying recordings.
+    pub fn set_recorder(&mut self, recorder: Option<Recorder>) {
+        self.recorder = recorder;
+    }
+
+    /// Write every session event as JSON Lines to `event_log`, or stop with `None`
+    pub fn set_event_log(&mut self, event_log: Option<EventLog>) {
+        if let Some(ref event_log) = event_log {
+            event_log.set_correlation_id(self.correlation_id.clone());
+        }
+        self.event_log = event_log;
+    }
+
+    /// Sets the correlation ID stamped onto the connection, logs, metrics and
+    /// session events, or clears it with `None`
+    ///
+    /// The ID is sent in the `X-CorrelationId` header of the websocket
+    /// handshake and applies to connections made afterwards.
+    pub fn set_correlation_id(&mut self, correlation_id: Option<String>) {
+        if let Some(ref event_log) = self.event_log {
+            event_log.set_correlation_id(correlation_id.clone());
+        }
+        self.correlation_id = correlation_id;
+    }
+
+    /// Sets the sink that receives connection and audio metrics
+    pub fn set_metrics_sink(&mut self, metrics: Arc<MetricsSink>) {
+        self.metrics = metrics;
+    }
+
+    /// Automatically end the audio stream once the endpointer detects
+    /// trailing silence after speech
+    pub fn set_endpointer(&mut self, endpointer: Option<Endpointer>) {
+        self.endpointer = endpointer;
+    }
+
+    /// Add an audio processing stage (e.g. `audio::filter::HighPass`)
+    ///
+    /// Processors run in the order they were added on every chunk passed to
+    /// `audio()` before it's sent.
+    pub fn add_processor<P>(&mut self, processor: P)
+    where
+        P: Processor + Send + 'static,
+    {
+        self.processors.push(Box::new(processor));
+    }
+
+    /// Remove all audio processing stages
+    pub fn clear_processors(&mut self) {
+        self.processors.clear();
+    }
+
+    /// Sets the hook receiving a trace event for every message
+    pub fn set_trace_hook(&mut self, trace: Arc<TraceHook>) {
+        self.trace = trace;
+    }
+
+    /// X-ConnectionId of the current connection, or of the last one once
+    /// disconnected
+    pub fn connection_id(&self) -> Option<String> {
+        self.shared.connection_id.lock().unwrap().clone()
+    }
+
+    /// X-RequestId of the turn in progress, if audio was sent since the
+    /// last turn ended
+    pub fn request_id(&self) -> Option<String> {
+        self.audio_uuid.lock().unwrap().clone()
+    }
+
+    /// Whether a connection has been made and audio can be sent
+    pub fn is_connected(&self) -> bool {
+        self.sender.lock().unwrap().is_some()
+    }
+
+    /// Whether an established connection dropped and hasn't been
+    /// re-established yet
+    pub fn is_lost(&self) -> bool {
+        self.shared.lost.load(Ordering::SeqCst)
     }
 
     /// Open the Websocket connection
     pub fn connect(
         &self,
         token: Arc<Mutex<String>>,
-        mode: &Mode,
-        format: &Format,
+        mode: Mode,
+        format: Format,
         is_custom_speech: bool,
         endpoint_id: &str,
         handler: Arc<Mutex<Handler + Send + Sync>>,
     ) -> Result<()> {
+        mode.check()?;
+        let url = self.build_url(mode, format, is_custom_speech, endpoint_id);
+        self.connect_url(url, token, handler)
+    }
+
+    /// Open the Websocket connection to `url`, e.g. a speech translation
+    /// URL built by `translation::Session`
+    pub fn connect_url(
+        &self,
+        url: String,
+        token: Arc<Mutex<String>>,
+        handler: Arc<Mutex<Handler + Send + Sync>>,
+    ) -> Result<()> {
+        if let Some(ref proxy) = self.proxy {
+            let parsed = Url::parse(&url)?;
+            if proxy.applies_to(parsed.host_str().unwrap_or("")) {
+                bail!(ErrorKind::InvalidRequest(format!(
+                    "websocket connections can't be tunnelled through proxy {}:{}; \
+                     clear it with set_proxy(None) or bypass the host",
+                    proxy.host(),
+                    proxy.port()
+                )));
+            }
+        }
+        self.shared.closing.store(false, Ordering::SeqCst);
+        self.shared.lost.store(false, Ordering::SeqCst);
+        let reconnect = self.reconnect.map(|policy| {
+            Arc::new(Reconnect {
+                policy,
+                url: url.clone(),
+                metrics: self.metrics.clone(),
+            })
+        });
+
         // Create new WebSocket instance
         let mut ws = ws::WebSocket::new(Factory {
             sender: self.sender.clone(),
             token: token.clone(),
             handler: handler.clone(),
             audio_uuid: self.audio_uuid.clone(),
+            recorder: self.recorder.clone(),
+            event_log: self.event_log.clone(),
+            correlation_id: self.correlation_id.clone(),
+            stats: self.stats.clone(),
+            shared: self.shared.clone(),
+            reconnect,
+            telemetry: self.telemetry.clone(),
+            auto_telemetry: self.auto_telemetry,
+            events: self.events.clone(),
+            trace: self.trace.clone(),
+            metrics: self.metrics.clone(),
         }).unwrap();
 
         // Connect to Bing Speech Websocket endpoint
-        let url = Self::build_url(mode, format, is_custom_speech, endpoint_id);
-        ws.connect(url.parse()?)?;
+        self.metrics.request_started(RequestKind::Websocket);
+        if let Some(ref correlation_id) = self.correlation_id {
+            self.metrics
+                .request_correlated(RequestKind::Websocket, correlation_id);
+        }
+        let started = Instant::now();
+        if let Err(err) = ws.connect(url.parse()?) {
+            self.metrics
+                .error(RequestKind::Websocket, metrics::ErrorClass::Network);
+            self.metrics
+                .request_completed(RequestKind::Websocket, None, started.elapsed());
+            return Err(err.into());
+        }
         thread::spawn(move || {
             ws.run().unwrap();
         });
@@ -113,62 +720,188 @@ impl Websocket {
 
     /// Send speech configuration data to Bing Speech API via Websocket
     pub fn config(&mut self, cfg: &ConfigPayload) -> ws::Result<()> {
-        let now = Local::now().to_rfc3339();
         let config_text = serde_json::to_string(&cfg).unwrap();
-        let text = format!(
-            "Path: {}\r\nX-RequestId: {}\r\nX-Timestamp: {}\r\nContent-Type: {}\r\n\r\n{}",
-            "speech.config",
-            generate_uuid(),
-            now,
-            "application/json; charset=utf-8",
-            config_text
-        );
+        let text = config_message(&config_text);
 
-        let msg = ws::Message::Text(text);
-        if let Some(ref mut s) = *self.sender.lock().unwrap() {
-            s.send(msg)
-        } else {
-            Ok(())
+        if let Some(ref recorder) = self.recorder {
+            recorder.text(Direction::Sent, &text);
+        }
+        if let Some(ref event_log) = self.event_log {
+            event_log.text(Direction::Sent, &text);
         }
+        self.trace.on_ws_frame(Direction::Sent, "speech.config");
+        *self.shared.config.lock().unwrap() = Some(config_text);
+
+        self.dispatch(ws::Message::Text(text), true)
     }
 
     /// Send audio data to Bing Speech API via Websocket
+    ///
+    /// Audio is dropped while the connection isn't open; use `push_audio`
+    /// to keep it until then.
     pub fn audio(&mut self, audio: &[u8]) -> ws::Result<()> {
-        if let Ok(sender_guard) = self.sender.lock() {
-            if let Some(ref sender) = *sender_guard {
-                let mut v = self.audio_uuid.lock().unwrap();
-                let uuid = if let Some(uuid) = v.clone() {
-                    uuid.clone()
-                } else {
-                    let uuid = generate_uuid();
-                    *v = Some(uuid.clone());
-                    uuid
-                };
-
-                let mut data = Vec::new();
-                let now = Local::now().to_rfc3339();
-                let text = format!(
-                    "Path: {}\r\nX-RequestId: {}\r\nX-Timestamp: {}\r\nContent-Type: {}\r\n\r\n",
-                    "audio", uuid, now, "audio/x-wav",
-                );
-
-                let header_len = text.len() as u16;
-                let s1 = ((header_len >> 8) & 0xFF) as u8;
-                let s2 = (header_len & 0xFF) as u8;
-                data.push(s1);
-                data.push(s2);
-                data.extend_from_slice(text.as_bytes());
-                data.extend_from_slice(&audio);
-
-                let msg = ws::Message::Binary(data);
+        self.process_audio(audio, false)
+    }
+
+    /// Send audio data, queueing it while the connection is opening or
+    /// being re-established
+    ///
+    /// Queued messages are sent in order as soon as the connection opens.
+    /// When the queue set with `set_send_queue` is full this blocks or
+    /// fails, depending on its `Backpressure`.
+    pub fn push_audio(&mut self, audio: &[u8]) -> ws::Result<()> {
+        self.process_audio(audio, true)
+    }
+
+    fn process_audio(&mut self, audio: &[u8], queue: bool) -> ws::Result<()> {
+        let mut processed;
+        let audio = if self.processors.is_empty() {
+            audio
+        } else {
+            processed = audio.to_vec();
+            for processor in &mut self.processors {
+                processor.process(&mut processed);
+            }
+            &processed[..]
+        };
+
+        self.send_audio(audio, queue)?;
+
+        let endpoint = match self.endpointer {
+            Some(ref mut endpointer) => endpointer.process(audio),
+            None => Endpoint::Waiting,
+        };
+        if endpoint == Endpoint::End {
+            info!("End of utterance detected");
+            self.end_audio()?;
+        }
+
+        Ok(())
+    }
+
+    /// Signal the end of the audio stream for the current turn
+    ///
+    /// Sends the empty audio message Bing uses to mark the end of audio so the
+    /// final phrase is returned without having to pad with silence. Does
+    /// nothing if no audio was sent in the current turn, since that would
+    /// open an empty one.
+    pub fn end_audio(&mut self) -> ws::Result<()> {
+        if self.audio_uuid.lock().unwrap().is_none() {
+            return Ok(());
+        }
+        self.send_audio(&[], false)?;
+        *self.audio_uuid.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn send_audio(&self, audio: &[u8], queue: bool) -> ws::Result<()> {
+        if !queue && !self.is_connected() && self.queued() == 0 {
+            if self.shared.lost.load(Ordering::SeqCst) {
+                return Err(ws::Error::new(
+                    ws::ErrorKind::Internal,
+                    "websocket connection lost",
+                ));
+            }
+            return Ok(());
+        }
+
+        // Only audio that's sent or queued counts against the budget
+        if let Some(ref budget) = self.budget {
+            if let Err(err) = budget.charge_audio(audio::duration(audio)) {
+                return Err(ws::Error::new(ws::ErrorKind::Internal, err.to_string()));
+            }
+        }
+
+        let (uuid, is_first) = {
+            let mut v = self.audio_uuid.lock().unwrap();
+            if let Some(uuid) = v.clone() {
+                (uuid, false)
+            } else {
+                let uuid = generate_uuid();
+                *v = Some(uuid.clone());
+                (uuid, true)
+            }
+        };
+
+        // The first chunk of a turn must carry the WAV header
+        let mut with_header;
+        let payload = if is_first && !audio.is_empty() && !wav::has_header(audio) {
+            with_header = WavHeader::streaming().to_bytes();
+            with_header.extend_from_slice(audio);
+            &with_header[..]
+        } else {
+            audio
+        };
+
+        let now = Local::now().to_rfc3339();
+        let data = protocol::build_audio_message(&uuid, &now, payload);
+
+        self.metrics.bytes_sent(RequestKind::Websocket, audio.len());
+        if !audio.is_empty() {
+            self.telemetry.audio_sent();
+        }
+        if let Some(ref recorder) = self.recorder {
+            recorder.binary(Direction::Sent, &data);
+        }
+        if let Some(ref event_log) = self.event_log {
+            event_log.binary(Direction::Sent, &data);
+        }
+        self.trace.on_ws_frame(Direction::Sent, "audio");
+        self.dispatch(ws::Message::Binary(data), queue)
+    }
+
+    /// Send `msg` right away if nothing is queued, otherwise queue it
+    /// behind the earlier messages
+    ///
+    /// With `queue`, messages are also queued until the connection is open;
+    /// without it they're dropped when there's no connection, as before.
+    fn dispatch(&self, msg: ws::Message, queue: bool) -> ws::Result<()> {
+        let mut pending = self.shared.queue.lock().unwrap();
+        if pending.is_empty() && (!queue || self.shared.open.load(Ordering::SeqCst)) {
+            if let Some(ref sender) = *self.sender.lock().unwrap() {
                 return sender.send(msg);
             }
         }
+        if !queue && pending.is_empty() {
+            return Ok(());
+        }
 
+        while pending.len() >= self.queue_capacity {
+            if self.backpressure == Backpressure::Error {
+                return Err(ws::Error::new(
+                    ws::ErrorKind::Internal,
+                    "websocket send queue full",
+                ));
+            }
+            if self.shared.is_gone() {
+                return Err(ws::Error::new(
+                    ws::ErrorKind::Internal,
+                    "websocket connection closed with messages queued",
+                ));
+            }
+            pending = self
+                .shared
+                .queue_space
+                .wait_timeout(pending, Duration::from_millis(10))
+                .unwrap()
+                .0;
+        }
+        pending.push_back(msg);
         Ok(())
     }
 
     pub fn disconnect(&mut self) -> Result<()> {
+        self.shared.closing.store(true, Ordering::SeqCst);
+        let dropped = {
+            let mut queue = self.shared.queue.lock().unwrap();
+            let dropped = queue.len();
+            queue.clear();
+            dropped
+        };
+        if dropped > 0 {
+            warn!("Dropping {} queued messages on disconnect", dropped);
+        }
+        self.shared.queue_space.notify_all();
         let sender = self.sender.lock().unwrap();
 
         if let Some(ref sender) = *sender {
@@ -179,129 +912,510 @@ impl Websocket {
     }
 
     fn build_url(
-        mode: &Mode,
-        format: &Format,
+        &self,
+        mode: Mode,
+        format: Format,
         is_custom_speech: bool,
         endpoint_id: &str,
     ) -> String {
-        let language = match mode {
-            Mode::Interactive(language) | Mode::Dictation(language) => language.to_string(),
-            Mode::Conversation(language) => language.to_string(),
+        let endpoints = match self.endpoints {
+            Some(ref endpoints) => endpoints.clone(),
+            None if is_custom_speech => self
+                .custom_speech_endpoints
+                .clone()
+                .unwrap_or_else(Endpoints::custom_speech),
+            None => Endpoints::bing(),
         };
-        if is_custom_speech {
-            format!(
-                "wss://westus.stt.speech.microsoft.com/speech/recognition/{}/cognitiveservices/v1?cid={}&language={}&format={}",
-                mode,
-                endpoint_id,
-                language,
-                format
-            )
+        let endpoint_id = if is_custom_speech {
+            Some(endpoint_id)
         } else {
-            format!(
-                "wss://speech.platform.bing.com/speech/recognition/{}/cognitiveservices/v1?language={}&format={}",
-                mode,
-                language,
-                format
-            )
+            None
+        };
+        let url = endpoints.websocket_url(mode, format, endpoint_id, self.params);
+        match self.intent_app {
+            Some(ref app) => {
+                let query: String = form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(app.query_pairs().iter())
+                    .finish();
+                format!("{}&{}", url, query)
+            }
+            None => url,
         }
     }
 }
 
 impl MyHandler {
-    fn parse_server_message(&self, msg: ws::Message) -> ws::Result<()> {
+    /// Send the telemetry of the turn ended by the `turn.end` message `text`
+    fn send_turn_telemetry(&self, text: &str) {
+        let request_id = protocol::parse_text_message(text)
+            .ok()
+            .and_then(|message| message.header("X-RequestId").map(String::from))
+            .unwrap_or_else(generate_uuid);
+        let telemetry = self.factory.telemetry.take_turn();
+        if let Some(ref sender) = *self.factory.sender.lock().unwrap() {
+            let result = send_telemetry(
+                sender,
+                &request_id,
+                &telemetry,
+                self.recorder.as_ref(),
+                self.event_log.as_ref(),
+                &*self.factory.trace,
+            );
+            if let Err(err) = result {
+                warn!("[{}] Failed to send telemetry: {}", self.log_id(), err);
+            }
+        }
+    }
+
+    /// Report the end of the connection to the metrics sink, once
+    ///
+    /// The status is the handshake's 101 if the connection opened.
+    fn complete_request(&mut self) {
+        if self.completed {
+            return;
+        }
+        self.completed = true;
+        let status = if self.opened { Some(101) } else { None };
+        self.factory.metrics.request_completed(
+            RequestKind::Websocket,
+            status,
+            self.started.elapsed(),
+        );
+    }
+
+    /// Correlation ID for log lines
+    fn log_id(&self) -> &str {
+        self.correlation_id
+            .as_ref()
+            .map(|id| id.as_str())
+            .unwrap_or("-")
+    }
+
+    fn parse_server_message(&mut self, msg: ws::Message) -> ws::Result<()> {
         match msg {
             ws::Message::Text(text) => self.parse_server_message_text(&text)?,
-            _ => warn!("Unimplemented"),
+            ws::Message::Binary(data) => self.parse_server_message_binary(&data),
         };
 
         Ok(())
     }
 
-    fn parse_server_message_text(&self, text: &str) -> ws::Result<()> {
-        info!("Received From Server: {}", text);
-
-        let sections: Vec<&str> = text.split("\r\n\r\n").collect();
-        let header = sections[0];
-        let body = sections[1];
-
-        let header_lines: Vec<&str> = header.split("\r\n").collect();
-        for line in header_lines {
-            let kv: Vec<&str> = line.split(':').collect();
-            let key = kv[0].trim();
-            let value = kv[1].trim();
-            if key == "Path" {
-                let h = self.handler.clone();
-                let mut h = h.lock().unwrap();
-                match value {
-                    "turn.start" => {
-                        h.on_turn_start();
-                    }
-                    "turn.end" => {
-                        *self.audio_uuid.lock().unwrap() = None;
-                        h.on_turn_end();
-                    }
-                    "speech.startDetected" => {
-                        h.on_speech_start();
-                    }
-                    "speech.endDetected" => {
-                        h.on_speech_end();
-                    }
-                    "speech.hypothesis" => {
-                        let json = serde_json::from_slice(body.as_bytes()).unwrap();
-                        h.on_speech_hypothesis(json);
-                    }
-                    "speech.phrase" => {
-                        let value: serde_json::Value = serde_json::from_str(body).unwrap();
-                        let phrase = Phrase::from_json_value(&value).unwrap();
-                        h.on_speech_phrase(phrase);
-                    }
-                    _ => {}
-                };
-            }
+    fn parse_server_message_binary(&mut self, data: &[u8]) {
+        let path = dispatch_binary_message(data, &self.handler, &self.factory.events);
+        if let Some(ref path) = path {
+            self.factory.telemetry.received(path);
+            self.factory.trace.on_ws_frame(Direction::Received, path);
         }
+    }
+
+    fn parse_server_message_text(&mut self, text: &str) -> ws::Result<()> {
+        let path = dispatch_text_message(
+            text,
+            &self.handler,
+            &self.audio_uuid,
+            &mut self.last_speaker,
+            &self.factory.events,
+        );
+        if let Some(ref path) = path {
+            self.factory.telemetry.received(path);
+            self.factory.trace.on_ws_frame(Direction::Received, path);
+        }
+        if path.as_ref().map_or(false, |path| path == "turn.end") && self.factory.auto_telemetry {
+            self.send_turn_telemetry(text);
+        }
+
+        // Track how long it takes for a turn's hypotheses to settle into a phrase
+        match path.as_ref().map(|path| path.as_str()) {
+            Some("speech.hypothesis") => {
+                if self.first_hypothesis.is_none() {
+                    self.first_hypothesis = Some(Instant::now());
+                }
+            }
+            Some("speech.phrase") => {
+                if let Some(first_hypothesis) = self.first_hypothesis.take() {
+                    self.stats
+                        .record(Metric::HypothesisToPhrase, first_hypothesis.elapsed());
+                }
+            }
+            Some("turn.end") => self.first_hypothesis = None,
+            _ => {}
+        };
 
         Ok(())
     }
 }
 
+/// Parse a text message from the server and invoke the matching `Handler` callback
+///
+/// Returns the message's `Path` header, or `None` for malformed messages,
+/// which are logged and otherwise ignored. `last_speaker` tracks the speaker
+/// of the previous phrase to detect speaker changes.
+pub(crate) fn dispatch_text_message(
+    text: &str,
+    handler: &Mutex<Handler + Send + Sync>,
+    audio_uuid: &Mutex<Option<String>>,
+    last_speaker: &mut Option<String>,
+    events: &ServerEvents,
+) -> Option<String> {
+    let path = match protocol::parse_text_message(text) {
+        Ok(raw) => {
+            let path = raw.path().unwrap_or("");
+            handler
+                .lock()
+                .unwrap()
+                .on_raw_message(path, &raw.headers, raw.body);
+            path.to_string()
+        }
+        Err(err) => {
+            warn!("{}", err);
+            return None;
+        }
+    };
+
+    let event = match protocol::parse_server_message(text) {
+        Ok(event) => event,
+        Err(err) => {
+            warn!("{}", err);
+            return None;
+        }
+    };
+
+    if !events.is_empty() {
+        events.send(event.clone());
+    }
+
+    let mut h = handler.lock().unwrap();
+    match event {
+        ServerEvent::TurnStart => h.on_turn_start(),
+        ServerEvent::TurnEnd => {
+            *audio_uuid.lock().unwrap() = None;
+            h.on_turn_end();
+        }
+        ServerEvent::SpeechStartDetected => h.on_speech_start(),
+        ServerEvent::SpeechEndDetected => h.on_speech_end(),
+        ServerEvent::SpeechHypothesis(hypothesis) => h.on_speech_hypothesis(hypothesis),
+        ServerEvent::SpeechPhrase(phrase) => {
+            if let Some(speaker_id) = phrase.speaker_id() {
+                if last_speaker.as_ref().map(|last| last.as_str()) != Some(speaker_id) {
+                    *last_speaker = Some(speaker_id.to_string());
+                    h.on_speaker_change(speaker_id);
+                }
+            }
+            h.on_speech_phrase(phrase);
+        }
+        ServerEvent::Intent(result) => h.on_intent(result),
+        ServerEvent::TranslationHypothesis(hypothesis) => h.on_translation_hypothesis(hypothesis),
+        ServerEvent::TranslationPhrase(phrase) => h.on_translation_phrase(phrase),
+        ServerEvent::TranslationAudioEnd(end) => h.on_translation_audio_end(end),
+        // Not decoded from text messages
+        ServerEvent::Connect(_)
+        | ServerEvent::Disconnect
+        | ServerEvent::TranslationAudio(_)
+        | ServerEvent::Unknown => {}
+    };
+
+    Some(path)
+}
+
+/// Parse a binary message from the server and invoke the matching `Handler`
+/// callback
+///
+/// Only "translation.synthesis" audio is expected. Returns the message's
+/// `Path` header, or `None` for malformed messages, which are logged and
+/// otherwise ignored.
+pub(crate) fn dispatch_binary_message(
+    data: &[u8],
+    handler: &Mutex<Handler + Send + Sync>,
+    events: &ServerEvents,
+) -> Option<String> {
+    let message = match protocol::parse_binary_message(data) {
+        Ok(message) => message,
+        Err(err) => {
+            warn!("{}", err);
+            return None;
+        }
+    };
+    let path = message.path().unwrap_or("");
+
+    match path {
+        "translation.synthesis" => {
+            if !events.is_empty() {
+                events.send(ServerEvent::TranslationAudio(message.payload.to_vec()));
+            }
+            handler
+                .lock()
+                .unwrap()
+                .on_translation_audio(message.payload);
+        }
+        other => warn!("Unexpected binary message {:?}", other),
+    };
+    Some(path.to_string())
+}
+
 impl ws::Handler for MyHandler {
     fn build_request(&mut self, url: &Url) -> ws::Result<ws::Request> {
-        info!("Building request");
         let mut request = ws::Request::from_url(url)?;
         {
             let headers = request.headers_mut();
             let token = format!("Bearer {}", self.token.lock().unwrap())
                 .as_bytes()
                 .to_vec();
-            let connection_id = Uuid::new_v4()
-                .to_string()
-                .replace("-", "")
-                .as_bytes()
-                .to_vec();
+            let connection_id = Uuid::new_v4().to_string().replace("-", "");
+            self.factory.telemetry.connecting(&connection_id);
             headers.push(("Authorization".to_string(), token));
-            headers.push(("X-ConnectionId".to_string(), connection_id));
+            headers.push((
+                "X-ConnectionId".to_string(),
+                connection_id.as_bytes().to_vec(),
+            ));
+            *self.factory.shared.connection_id.lock().unwrap() = Some(connection_id.clone());
+            self.connection_id = Some(connection_id);
+            if let Some(ref correlation_id) = self.correlation_id {
+                if correlation_id.parse::<HeaderValue>().is_ok() {
+                    headers.push((
+                        CORRELATION_ID_HEADER.to_string(),
+                        correlation_id.as_bytes().to_vec(),
+                    ));
+                } else {
+                    warn!(
+                        "Correlation ID {:?} is not a valid header value",
+                        correlation_id
+                    );
+                }
+            }
         }
         Ok(request)
     }
 
     fn on_open(&mut self, _shake: ws::Handshake) -> ws::Result<()> {
-        info!("Connected");
+        info!("[{}] Connected", self.log_id());
+        self.opened = true;
+        self.factory.shared.open.store(true, Ordering::SeqCst);
+        self.factory.telemetry.connected();
+        if let Some(ref sender) = *self.factory.sender.lock().unwrap() {
+            self.factory.events.send(ServerEvent::Connect(sender.clone()));
+        }
+        if let Some(ref event_log) = self.event_log {
+            event_log.connected();
+        }
+        // After a reconnect the queue is flushed once "speech.config" was
+        // sent again
+        if !self.factory.shared.reconnecting.load(Ordering::SeqCst) {
+            let sender = self.factory.sender.lock().unwrap().clone();
+            if let Some(sender) = sender {
+                self.factory.shared.flush_queue(&sender);
+            }
+        }
+        let connection_id = self.connection_id.as_ref().map(|id| id.as_str());
+        self.handler
+            .lock()
+            .unwrap()
+            .on_connect(connection_id.unwrap_or(""));
         Ok(())
     }
 
     fn on_message(&mut self, msg: ws::Message) -> ws::Result<()> {
-        info!("Got message");
+        if let Some(ref recorder) = self.recorder {
+            match msg {
+                ws::Message::Text(ref text) => recorder.text(Direction::Received, text),
+                ws::Message::Binary(ref data) => recorder.binary(Direction::Received, data),
+            };
+        }
+        if let Some(ref event_log) = self.event_log {
+            match msg {
+                ws::Message::Text(ref text) => event_log.text(Direction::Received, text),
+                ws::Message::Binary(ref data) => event_log.binary(Direction::Received, data),
+            };
+        }
         self.parse_server_message(msg)?;
         Ok(())
     }
 
-    fn on_close(&mut self, _code: ws::CloseCode, _reason: &str) {
-        info!("Disconnected");
+    fn on_close(&mut self, code: ws::CloseCode, reason: &str) {
+        info!("[{}] Disconnected", self.log_id());
+        self.complete_request();
+        let shared = self.factory.shared.clone();
+        let was_open = shared.open.swap(false, Ordering::SeqCst);
+        if was_open {
+            *self.factory.sender.lock().unwrap() = None;
+            self.handler
+                .lock()
+                .unwrap()
+                .on_disconnect(code.into(), reason);
+            self.factory.events.send(ServerEvent::Disconnect);
+        }
+
+        let expected = shared.closing.load(Ordering::SeqCst);
+        if was_open && !expected {
+            shared.lost.store(true, Ordering::SeqCst);
+            if let Some(reconnect) = self.factory.reconnect.clone() {
+                if !shared.reconnecting.swap(true, Ordering::SeqCst) {
+                    let factory = self.factory.clone();
+                    thread::spawn(move || {
+                        reconnect_with_backoff(factory, &reconnect);
+                        shared.reconnecting.store(false, Ordering::SeqCst);
+                    });
+                }
+            }
+        }
+        if let Some(ref recorder) = self.recorder {
+            if let Err(err) = recorder.flush() {
+                error!("{}", err);
+            }
+        }
+        if let Some(ref event_log) = self.event_log {
+            event_log.disconnected(reason);
+            if let Err(err) = event_log.flush() {
+                error!("{}", err);
+            }
+        }
     }
 
     fn on_error(&mut self, err: ws::Error) {
-        error!("{}", err);
+        error!("[{}] {}", self.log_id(), err);
+        if !self.opened {
+            self.factory.telemetry.connection_failed(&err.to_string());
+            self.factory
+                .metrics
+                .error(RequestKind::Websocket, metrics::ErrorClass::Network);
+            self.complete_request();
+        }
+        if let Some(ref event_log) = self.event_log {
+            event_log.error(&err.to_string());
+        }
+        self.handler.lock().unwrap().on_error(&Error::from(err));
+    }
+
+    fn on_shutdown(&mut self) {
+        self.complete_request();
+    }
+}
+
+/// Send `telemetry` for the turn `request_id`
+fn send_telemetry(
+    sender: &ws::Sender,
+    request_id: &str,
+    telemetry: &Telemetry,
+    recorder: Option<&Recorder>,
+    event_log: Option<&EventLog>,
+    trace: &TraceHook,
+) -> ws::Result<()> {
+    let body = telemetry
+        .to_json()
+        .map_err(|err| ws::Error::new(ws::ErrorKind::Internal, err.to_string()))?;
+    let text = protocol::build_text_message(
+        "telemetry",
+        request_id,
+        &Local::now().to_rfc3339(),
+        "application/json",
+        &body,
+    );
+
+    if let Some(recorder) = recorder {
+        recorder.text(Direction::Sent, &text);
+    }
+    if let Some(event_log) = event_log {
+        event_log.text(Direction::Sent, &text);
+    }
+    trace.on_ws_frame(Direction::Sent, "telemetry");
+    sender.send(ws::Message::Text(text))
+}
+
+/// "speech.config" message carrying `body`, with a new request ID and the
+/// current time
+fn config_message(body: &str) -> String {
+    protocol::build_text_message(
+        "speech.config",
+        &generate_uuid(),
+        &Local::now().to_rfc3339(),
+        "application/json; charset=utf-8",
+        body,
+    )
+}
+
+/// Re-open a dropped connection, retrying according to the policy
+fn reconnect_with_backoff(factory: Factory, reconnect: &Reconnect) {
+    let policy = reconnect.policy;
+    let shared = factory.shared.clone();
+
+    for attempt in 1..=policy.max_attempts {
+        thread::sleep(policy.delay(attempt));
+        if shared.closing.load(Ordering::SeqCst) {
+            return;
+        }
+
+        info!("Reconnecting (attempt {}/{})", attempt, policy.max_attempts);
+        reconnect.metrics.reconnect();
+        let url = match reconnect.url.parse() {
+            Ok(url) => url,
+            Err(err) => {
+                error!("Can't reconnect to {}: {}", reconnect.url, err);
+                return;
+            }
+        };
+        let mut ws = match ws::WebSocket::new(factory.clone()) {
+            Ok(ws) => ws,
+            Err(err) => {
+                warn!("Reconnect attempt {} failed: {}", attempt, err);
+                continue;
+            }
+        };
+        let started = Instant::now();
+        reconnect.metrics.request_started(RequestKind::Websocket);
+        if let Err(err) = ws.connect(url) {
+            warn!("Reconnect attempt {} failed: {}", attempt, err);
+            reconnect
+                .metrics
+                .request_completed(RequestKind::Websocket, None, started.elapsed());
+            *factory.sender.lock().unwrap() = None;
+            continue;
+        }
+        let broadcaster = ws.broadcaster();
+        let worker = thread::spawn(move || {
+            if let Err(err) = ws.run() {
+                error!("{}", err);
+            }
+        });
+
+        let start = Instant::now();
+        while !shared.open.load(Ordering::SeqCst) && start.elapsed() < policy.connect_timeout {
+            thread::sleep(Duration::from_millis(10));
+        }
+        if !shared.open.load(Ordering::SeqCst) {
+            warn!("Reconnect attempt {} timed out", attempt);
+            // Shut the attempt down so a late handshake can't leave a second
+            // live connection behind the next one
+            if let Err(err) = broadcaster.shutdown() {
+                warn!("Failed to cancel reconnect attempt {}: {}", attempt, err);
+            }
+            let _ = worker.join();
+            // The abandoned attempt's sender is set as soon as its TCP
+            // connection is made, open or not
+            shared.open.store(false, Ordering::SeqCst);
+            *factory.sender.lock().unwrap() = None;
+            continue;
+        }
+
+        // Start a new turn with the same configuration
+        *factory.audio_uuid.lock().unwrap() = None;
+        let config = shared.config.lock().unwrap().clone();
+        if let Some(config) = config {
+            if let Some(ref sender) = *factory.sender.lock().unwrap() {
+                let text = config_message(&config);
+                if let Err(err) = sender.send(ws::Message::Text(text)) {
+                    warn!("Failed to re-send speech.config: {}", err);
+                }
+            }
+        }
+        let sender = factory.sender.lock().unwrap().clone();
+        if let Some(sender) = sender {
+            shared.flush_queue(&sender);
+        }
+        shared.lost.store(false, Ordering::SeqCst);
+        factory.handler.lock().unwrap().on_reconnect(attempt);
+        return;
     }
+
+    error!("Giving up reconnecting after {} attempts", policy.max_attempts);
 }
 
 /// Configuration struct for "speech.config" payload
@@ -0,0 +1,143 @@
+//! Bing Spell Check v7 client
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::spellcheck::*;
+//!
+//! let spellcheck = SpellCheck::new("your_subscription_key").unwrap();
+//! let response = spellcheck
+//!     .check("Hollo, wrld!", SpellCheckMode::Proof, "en-US")
+//!     .unwrap();
+//! for token in response.flagged_tokens {
+//!     if let Some(suggestion) = token.suggestions.first() {
+//!         println!("{} -> {}", token.token, suggestion.suggestion);
+//!     }
+//! }
+//! ```
+
+// internal
+use client::{ApiClient, HyperTransport, Transport};
+use errors::*;
+use search::DEFAULT_ENDPOINT;
+
+// std
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// How thoroughly the text is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpellCheckMode {
+    /// Spelling, capitalization and punctuation; only en-US, es-ES and pt-BR
+    Proof,
+    /// Spelling only, for short strings such as search queries
+    Spell,
+}
+
+impl Display for SpellCheckMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpellCheckMode::Proof => write!(f, "proof"),
+            SpellCheckMode::Spell => write!(f, "spell"),
+        }
+    }
+}
+
+impl FromStr for SpellCheckMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "proof" => Ok(SpellCheckMode::Proof),
+            "spell" => Ok(SpellCheckMode::Spell),
+            _ => bail!(ErrorKind::UnknownVariant("SpellCheckMode", s.to_string())),
+        }
+    }
+}
+
+/// Bing Spell Check client
+pub struct SpellCheck {
+    client: ApiClient,
+}
+
+impl SpellCheck {
+    /// Creates a new Bing Spell Check handle
+    pub fn new<T>(subscription_key: &T) -> Result<Self>
+    where
+        T: ToString,
+    {
+        let transport = HyperTransport::new()?;
+        Ok(Self::with_transport(subscription_key, transport))
+    }
+
+    /// Creates a new Bing Spell Check handle sending requests through
+    /// `transport`
+    pub fn with_transport<T, U>(subscription_key: &T, transport: U) -> Self
+    where
+        T: ToString,
+        U: Transport + 'static,
+    {
+        SpellCheck {
+            client: ApiClient::new(&subscription_key.to_string(), DEFAULT_ENDPOINT, transport),
+        }
+    }
+
+    /// Sets Bing Spell Check subscription key
+    pub fn set_subscription_key(&mut self, key: &str) {
+        self.client.set_subscription_key(key);
+    }
+
+    /// Override the base URL, e.g. for a custom Azure resource
+    pub fn set_endpoint(&mut self, endpoint: &str) {
+        self.client.set_endpoint(endpoint);
+    }
+
+    /// Check `text` for spelling and grammar errors
+    ///
+    /// `market` is a market code such as "en-US". The text is sent in the
+    /// request body so it may be longer than a URL allows.
+    pub fn check(
+        &self,
+        text: &str,
+        mode: SpellCheckMode,
+        market: &str,
+    ) -> Result<SpellCheckResponse> {
+        let params = vec![
+            ("mode".to_string(), mode.to_string()),
+            ("mkt".to_string(), market.to_string()),
+        ];
+        let form = vec![("text".to_string(), text.to_string())];
+        self.client.post_form("spellcheck", &params, &form)
+    }
+}
+
+/// Response of `SpellCheck::check`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpellCheckResponse {
+    #[serde(default)]
+    pub flagged_tokens: Vec<FlaggedToken>,
+}
+
+/// Word or phrase the service considers misspelled or misused
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FlaggedToken {
+    /// Offset of the token in the checked text, in characters
+    pub offset: usize,
+    pub token: String,
+    /// "UnknownToken" for spelling errors, "RepeatedToken" for repeated words
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub suggestions: Vec<SpellingSuggestion>,
+}
+
+/// Suggested replacement of a `FlaggedToken`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpellingSuggestion {
+    pub suggestion: String,
+    /// Confidence between 0 and 1
+    pub score: f64,
+}
@@ -0,0 +1,119 @@
+//! Latency statistics
+//!
+//! `Speech::stats()` and `Websocket::stats()` return a `ClientStats` snapshot
+//! with percentiles over the most recent samples, so operators can alert on
+//! degradation without wrapping every call themselves.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Number of most recent samples kept per metric
+pub const MAX_SAMPLES: usize = 1000;
+
+/// Percentiles of a latency metric
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Percentiles {
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Snapshot of client latency statistics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientStats {
+    /// Time taken to fetch a token
+    pub token_fetch: Percentiles,
+    /// REST recognition round-trip time
+    pub recognition: Percentiles,
+    /// Time from the first hypothesis of a turn to its final phrase
+    pub hypothesis_to_phrase: Percentiles,
+    /// Requests sent again by `speech::RetryPolicy`
+    pub retries: u64,
+}
+
+/// Metric tracked by `StatsTracker`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    TokenFetch,
+    Recognition,
+    HypothesisToPhrase,
+}
+
+#[derive(Default)]
+struct Samples {
+    token_fetch: VecDeque<Duration>,
+    recognition: VecDeque<Duration>,
+    hypothesis_to_phrase: VecDeque<Duration>,
+    retries: u64,
+}
+
+/// Collects latency samples
+///
+/// Clones share the same samples, so a `Speech` and a `Websocket` can report
+/// into one tracker via `Websocket::set_stats_tracker`.
+#[derive(Clone, Default)]
+pub struct StatsTracker {
+    samples: Arc<Mutex<Samples>>,
+}
+
+impl StatsTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a latency sample
+    pub fn record(&self, metric: Metric, elapsed: Duration) {
+        let mut samples = self.samples.lock().unwrap();
+        let samples = match metric {
+            Metric::TokenFetch => &mut samples.token_fetch,
+            Metric::Recognition => &mut samples.recognition,
+            Metric::HypothesisToPhrase => &mut samples.hypothesis_to_phrase,
+        };
+        if samples.len() == MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(elapsed);
+    }
+
+    /// Count a request sent again after a transient failure
+    pub fn record_retry(&self) {
+        self.samples.lock().unwrap().retries += 1;
+    }
+
+    /// Compute percentiles over the recorded samples
+    pub fn snapshot(&self) -> ClientStats {
+        let samples = self.samples.lock().unwrap();
+        ClientStats {
+            token_fetch: percentiles(&samples.token_fetch),
+            recognition: percentiles(&samples.recognition),
+            hypothesis_to_phrase: percentiles(&samples.hypothesis_to_phrase),
+            retries: samples.retries,
+        }
+    }
+
+    /// Forget all recorded samples
+    pub fn reset(&self) {
+        *self.samples.lock().unwrap() = Samples::default();
+    }
+}
+
+fn percentiles(samples: &VecDeque<Duration>) -> Percentiles {
+    if samples.is_empty() {
+        return Percentiles::default();
+    }
+
+    let mut sorted: Vec<Duration> = samples.iter().cloned().collect();
+    sorted.sort();
+    let at = |percentile: usize| sorted[((sorted.len() - 1) * percentile + 50) / 100];
+
+    Percentiles {
+        count: sorted.len(),
+        p50: at(50),
+        p90: at(90),
+        p99: at(99),
+        max: sorted[sorted.len() - 1],
+    }
+}
@@ -0,0 +1,158 @@
+//! Builders and sample JSON for recognition results
+
+use speech::{
+    DetailedPhrase, DetailedPhraseItem, Hypothesis, Phrase, SilencePhrase, SimplePhrase,
+};
+
+/// Sample "simple" format recognition result
+pub const SIMPLE_PHRASE_JSON: &str = r#"{"RecognitionStatus":"Success","DisplayText":"Remind me to buy five pencils.","Offset":3600000,"Duration":23100000}"#;
+
+/// Sample "detailed" format recognition result
+pub const DETAILED_PHRASE_JSON: &str = r#"{"RecognitionStatus":"Success","Offset":3600000,"Duration":23100000,"NBest":[{"Confidence":0.9052885,"Lexical":"remind me to buy five pencils","ITN":"remind me to buy 5 pencils","MaskedITN":"remind me to buy 5 pencils","Display":"Remind me to buy 5 pencils."},{"Confidence":0.6471585,"Lexical":"remind me to buy five pencil","ITN":"remind me to buy 5 pencil","MaskedITN":"remind me to buy 5 pencil","Display":"Remind me to buy 5 pencil."}]}"#;
+
+/// Sample result when no speech was detected
+pub const SILENCE_PHRASE_JSON: &str =
+    r#"{"RecognitionStatus":"InitialSilenceTimeout","Offset":0,"Duration":50000000}"#;
+
+/// Sample partial result received over the websocket
+pub const HYPOTHESIS_JSON: &str =
+    r#"{"Text":"remind me to buy","Offset":3600000,"Duration":12300000}"#;
+
+/// Default duration in ticks (1 second) used by the builders
+const DEFAULT_DURATION: f64 = 10_000_000.0;
+
+/// A successful "simple" format phrase with the given text
+///
+/// # Examples
+///
+/// ```
+/// use bing_rs::testing::fixtures;
+///
+/// let phrase = fixtures::simple_phrase("Hello world.");
+/// ```
+pub fn simple_phrase(text: &str) -> Phrase {
+    Phrase::Simple(SimplePhrase {
+        recognition_status: "Success".to_string(),
+        display_text: text.to_string(),
+        offset: 0.0,
+        duration: DEFAULT_DURATION,
+        language: None,
+        speaker_id: None,
+    })
+}
+
+/// A silence timeout phrase
+pub fn silence_phrase() -> Phrase {
+    Phrase::Silence(SilencePhrase {
+        recognition_status: "InitialSilenceTimeout".to_string(),
+        offset: 0.0,
+        duration: DEFAULT_DURATION,
+    })
+}
+
+/// A hypothesis with the given text
+pub fn hypothesis(text: &str) -> Hypothesis {
+    Hypothesis {
+        text: text.to_string(),
+        offset: 0.0,
+        duration: DEFAULT_DURATION,
+    }
+}
+
+/// A single NBest alternative using `display` for every text form
+pub fn nbest_item(display: &str, confidence: f64) -> DetailedPhraseItem {
+    let lexical = display
+        .trim_end_matches(|c: char| c.is_ascii_punctuation())
+        .to_lowercase();
+    DetailedPhraseItem {
+        confidence,
+        lexical: lexical.clone(),
+        itn: lexical.clone(),
+        masked_itn: lexical,
+        display: display.to_string(),
+        words: Vec::new(),
+    }
+}
+
+/// Builder for `DetailedPhrase` results
+///
+/// # Examples
+///
+/// ```
+/// use bing_rs::testing::fixtures::DetailedPhraseBuilder;
+///
+/// let phrase = DetailedPhraseBuilder::new()
+///     .alternative("Remind me to buy 5 pencils.", 0.9)
+///     .alternative("Remind me to buy 5 pencil.", 0.6)
+///     .offset(3600000.0)
+///     .into_phrase();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DetailedPhraseBuilder {
+    phrase: DetailedPhrase,
+}
+
+impl DetailedPhraseBuilder {
+    pub fn new() -> Self {
+        DetailedPhraseBuilder {
+            phrase: DetailedPhrase {
+                recognition_status: "Success".to_string(),
+                offset: 0.0,
+                duration: DEFAULT_DURATION,
+                nbest: Vec::new(),
+                language: None,
+                speaker_id: None,
+            },
+        }
+    }
+
+    /// Sets the recognition status, "Success" by default
+    pub fn status(mut self, status: &str) -> Self {
+        self.phrase.recognition_status = status.to_string();
+        self
+    }
+
+    /// Sets the offset in ticks
+    pub fn offset(mut self, offset: f64) -> Self {
+        self.phrase.offset = offset;
+        self
+    }
+
+    /// Sets the duration in ticks
+    pub fn duration(mut self, duration: f64) -> Self {
+        self.phrase.duration = duration;
+        self
+    }
+
+    /// Sets the speaker identified by conversation transcription
+    pub fn speaker(mut self, speaker_id: &str) -> Self {
+        self.phrase.speaker_id = Some(speaker_id.to_string());
+        self
+    }
+
+    /// Appends an NBest alternative
+    pub fn alternative(self, display: &str, confidence: f64) -> Self {
+        self.item(nbest_item(display, confidence))
+    }
+
+    /// Appends a fully specified NBest alternative
+    pub fn item(mut self, item: DetailedPhraseItem) -> Self {
+        self.phrase.nbest.push(item);
+        self
+    }
+
+    pub fn build(self) -> DetailedPhrase {
+        self.phrase
+    }
+
+    /// Build and wrap in `Phrase::Detailed`
+    pub fn into_phrase(self) -> Phrase {
+        Phrase::Detailed(self.phrase)
+    }
+}
+
+impl Default for DetailedPhraseBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
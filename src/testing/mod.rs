@@ -0,0 +1,22 @@
+//! Utilities for testing code that uses this crate (requires the `testing` feature)
+//!
+//! # Examples
+//!
+//! ```
+//! use bing_rs::speech::*;
+//! use bing_rs::testing::MockTransport;
+//!
+//! let transport = MockTransport::new()
+//!     .token("mock-token")
+//!     .recognition(r#"{"RecognitionStatus":"Success","DisplayText":"Hello.","Offset":0,"Duration":10000000}"#);
+//! let mut speech = Speech::with_transport(&"key", transport.clone());
+//! speech.fetch_token().unwrap();
+//! assert_eq!(transport.requests().len(), 1);
+//! ```
+
+pub mod fixtures;
+mod server;
+mod transport;
+
+pub use self::server::{MockScript, MockServer, RecordedMessage};
+pub use self::transport::{MockResponse, MockTransport, RecordedRequest};
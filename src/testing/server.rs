@@ -0,0 +1,345 @@
+// futures
+use futures::sync::oneshot;
+use futures::{Future, Stream};
+
+// hyper
+use hyper::service::service_fn;
+use hyper::{self, Body, Request, Response, Server};
+
+// std
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// ws
+use ws;
+
+// internal
+use super::transport::{request_kind, MockResponse, RecordedRequest};
+use errors::*;
+use metrics::RequestKind;
+use speech::Endpoints;
+
+/// Scripted responses served by `MockServer`
+#[derive(Clone, Default)]
+pub struct MockScript {
+    responses: HashMap<RequestKind, MockResponse>,
+    messages: Vec<(String, String)>,
+}
+
+impl MockScript {
+    /// Creates a script answering HTTP requests with empty 200 OKs and
+    /// websocket turns with no results
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Respond to token requests with `token`
+    pub fn token(self, token: &str) -> Self {
+        self.respond(RequestKind::Token, MockResponse::ok(token))
+    }
+
+    /// Respond to REST recognition requests with the given JSON result
+    pub fn recognition(self, json: &str) -> Self {
+        self.respond(RequestKind::Recognize, MockResponse::ok(json))
+    }
+
+    /// Respond to synthesis requests with the given audio
+    pub fn synthesis(self, audio: Vec<u8>) -> Self {
+        self.respond(RequestKind::Synthesize, MockResponse::ok(audio))
+    }
+
+    /// Respond to voice list requests with the given JSON list
+    pub fn voices(self, json: &str) -> Self {
+        self.respond(RequestKind::Voices, MockResponse::ok(json))
+    }
+
+    /// Respond to HTTP requests of `kind` with an arbitrary response
+    pub fn respond(mut self, kind: RequestKind, response: MockResponse) -> Self {
+        self.responses.insert(kind, response);
+        self
+    }
+
+    /// Send a `speech.hypothesis` message with the given JSON body during each turn
+    pub fn hypothesis(self, json: &str) -> Self {
+        self.message("speech.hypothesis", json)
+    }
+
+    /// Send a `speech.phrase` message with the given JSON body during each turn
+    pub fn phrase(self, json: &str) -> Self {
+        self.message("speech.phrase", json)
+    }
+
+    /// Send an arbitrary message once the client signals the end of audio
+    pub fn message(mut self, path: &str, body: &str) -> Self {
+        self.messages.push((path.to_string(), body.to_string()));
+        self
+    }
+}
+
+/// Websocket message captured by `MockServer`
+#[derive(Debug, Clone)]
+pub struct RecordedMessage {
+    /// `Path` header, e.g. "speech.config" or "audio"
+    pub path: String,
+    /// `X-RequestId` header, empty if missing
+    pub request_id: String,
+    /// Body after the headers; empty for the end-of-audio message
+    pub body: Vec<u8>,
+}
+
+impl RecordedMessage {
+    fn parse(header: &str, body: Vec<u8>) -> Self {
+        let value = |name: &str| {
+            header
+                .split("\r\n")
+                .filter_map(|line| {
+                    let mut kv = line.splitn(2, ':');
+                    match (kv.next(), kv.next()) {
+                        (Some(key), Some(value)) if key.trim() == name => {
+                            Some(value.trim().to_string())
+                        }
+                        _ => None,
+                    }
+                }).next()
+                .unwrap_or_default()
+        };
+        RecordedMessage {
+            path: value("Path"),
+            request_id: value("X-RequestId"),
+            body,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Recording {
+    requests: Vec<RecordedRequest>,
+    messages: Vec<RecordedMessage>,
+}
+
+/// Local HTTP and websocket server emulating the Bing Speech protocols
+///
+/// Point a client at it with `Speech::set_endpoints(Some(server.endpoints()))`
+/// (and likewise for `Websocket`) to run integration tests without
+/// credentials. The server shuts down when dropped.
+///
+/// For websocket sessions, the server answers the first audio message of a
+/// turn with `turn.start` and `speech.startDetected`, and the end-of-audio
+/// message (see `Websocket::end_audio`) with the scripted messages followed
+/// by `speech.endDetected` and `turn.end`.
+///
+/// HTTP requests and websocket messages are recorded, so tests can check
+/// what the client sent with `requests` and `messages`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use bing_rs::speech::*;
+/// use bing_rs::testing::{MockScript, MockServer};
+///
+/// let server = MockServer::start(MockScript::new().token("mock-token")).unwrap();
+/// let mut speech = Speech::new(&"key").unwrap();
+/// speech.set_endpoints(Some(server.endpoints()));
+/// speech.fetch_token().unwrap();
+/// ```
+pub struct MockServer {
+    http_addr: SocketAddr,
+    ws_addr: SocketAddr,
+    http_shutdown: Option<oneshot::Sender<()>>,
+    ws_broadcaster: ws::Sender,
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl MockServer {
+    /// Start serving `script` on random local ports
+    pub fn start(script: MockScript) -> Result<Self> {
+        let recording = Arc::new(Mutex::new(Recording::default()));
+        let (http_addr, http_shutdown) =
+            start_http(script.responses.clone(), recording.clone())?;
+        let (ws_addr, ws_broadcaster) =
+            start_websocket(script.messages.clone(), recording.clone())?;
+
+        Ok(MockServer {
+            http_addr,
+            ws_addr,
+            http_shutdown: Some(http_shutdown),
+            ws_broadcaster,
+            recording,
+        })
+    }
+
+    /// HTTP requests received so far, oldest first
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.recording.lock().unwrap().requests.clone()
+    }
+
+    /// Websocket messages received so far on any connection, oldest first
+    pub fn messages(&self) -> Vec<RecordedMessage> {
+        self.recording.lock().unwrap().messages.clone()
+    }
+
+    /// Forget the recorded requests and messages
+    pub fn clear_recording(&self) {
+        let mut recording = self.recording.lock().unwrap();
+        recording.requests.clear();
+        recording.messages.clear();
+    }
+
+    /// Service URLs pointing at this server
+    pub fn endpoints(&self) -> Endpoints {
+        Endpoints {
+            token: format!("http://{}/sts/v1.0/issueToken", self.http_addr),
+            recognition: format!("http://{}/speech/recognition", self.http_addr),
+            websocket: format!("ws://{}/speech/recognition", self.ws_addr),
+            synthesis: format!("http://{}/synthesize", self.http_addr),
+            voices: format!("http://{}/cognitiveservices/voices/list", self.http_addr),
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.http_shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        if let Err(err) = self.ws_broadcaster.shutdown() {
+            error!("{}", err);
+        }
+    }
+}
+
+fn start_http(
+    responses: HashMap<RequestKind, MockResponse>,
+    recording: Arc<Mutex<Recording>>,
+) -> Result<(SocketAddr, oneshot::Sender<()>)> {
+    let responses = Arc::new(responses);
+    let new_service = move || {
+        let responses = responses.clone();
+        let recording = recording.clone();
+        service_fn(move |request: Request<Body>| {
+            let responses = responses.clone();
+            let recording = recording.clone();
+            let (parts, body) = request.into_parts();
+            body.concat2().map(move |body| {
+                let kind = request_kind(&parts.uri);
+                recording.lock().unwrap().requests.push(RecordedRequest {
+                    kind,
+                    method: parts.method,
+                    uri: parts.uri,
+                    headers: parts.headers,
+                    body: body.to_vec(),
+                });
+                let response = responses
+                    .get(&kind)
+                    .cloned()
+                    .unwrap_or_else(|| MockResponse::ok(Vec::new()));
+
+                let mut res = Response::new(Body::from(response.body));
+                *res.status_mut() = response.status;
+                *res.headers_mut() = response.headers;
+                res
+            })
+        })
+    };
+
+    let addr = ([127, 0, 0, 1], 0).into();
+    let server = Server::try_bind(&addr)?.serve(new_service);
+    let addr = server.local_addr();
+    let (shutdown, signal) = oneshot::channel();
+    let server = server
+        .with_graceful_shutdown(signal)
+        .map_err(|err| error!("Mock server error: {}", err));
+    thread::spawn(move || hyper::rt::run(server));
+
+    Ok((addr, shutdown))
+}
+
+fn start_websocket(
+    messages: Vec<(String, String)>,
+    recording: Arc<Mutex<Recording>>,
+) -> Result<(SocketAddr, ws::Sender)> {
+    let messages = Arc::new(messages);
+    let socket = ws::WebSocket::new(move |out| MockSession {
+        out,
+        messages: messages.clone(),
+        recording: recording.clone(),
+        request_id: None,
+    })?.bind("127.0.0.1:0")?;
+    let addr = socket.local_addr()?;
+    let broadcaster = socket.broadcaster();
+    thread::spawn(move || {
+        if let Err(err) = socket.run() {
+            error!("Mock websocket server error: {}", err);
+        }
+    });
+
+    Ok((addr, broadcaster))
+}
+
+struct MockSession {
+    out: ws::Sender,
+    messages: Arc<Vec<(String, String)>>,
+    recording: Arc<Mutex<Recording>>,
+    request_id: Option<String>,
+}
+
+impl MockSession {
+    fn send(&self, path: &str, body: &str) -> ws::Result<()> {
+        let request_id = self.request_id.clone().unwrap_or_default();
+        let text = format!(
+            "X-RequestId: {}\r\nPath: {}\r\nContent-Type: {}\r\n\r\n{}",
+            request_id, path, "application/json; charset=utf-8", body
+        );
+        self.out.send(ws::Message::Text(text))
+    }
+}
+
+impl ws::Handler for MockSession {
+    fn on_message(&mut self, msg: ws::Message) -> ws::Result<()> {
+        let data = match msg {
+            ws::Message::Binary(data) => data,
+            // speech.config and telemetry are recorded but not answered
+            ws::Message::Text(text) => {
+                let mut split = text.splitn(2, "\r\n\r\n");
+                let header = split.next().unwrap_or("");
+                let body = split.next().unwrap_or("").as_bytes().to_vec();
+                self.recording
+                    .lock()
+                    .unwrap()
+                    .messages
+                    .push(RecordedMessage::parse(header, body));
+                return Ok(());
+            }
+        };
+        if data.len() < 2 {
+            return Ok(());
+        }
+
+        let header_len = ((data[0] as usize) << 8) | data[1] as usize;
+        let header_end = (2 + header_len).min(data.len());
+        let header = String::from_utf8_lossy(&data[2..header_end]);
+        let is_end_of_audio = data.len() <= 2 + header_len;
+        let message = RecordedMessage::parse(&header, data[header_end..].to_vec());
+        let request_id = message.request_id.clone();
+        self.recording.lock().unwrap().messages.push(message);
+
+        if self.request_id.is_none() {
+            self.request_id = Some(request_id);
+            self.send("turn.start", "{}")?;
+            self.send("speech.startDetected", "{}")?;
+        }
+
+        if is_end_of_audio {
+            for &(ref path, ref body) in self.messages.iter() {
+                self.send(path, body)?;
+            }
+            self.send("speech.endDetected", "{}")?;
+            self.send("turn.end", "{}")?;
+            self.request_id = None;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+// futures
+use futures::{Future, Stream};
+
+// hyper
+use hyper::header::{HeaderValue, CONTENT_TYPE};
+use hyper::{Body, HeaderMap, Method, Request, StatusCode, Uri};
+
+// std
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// internal
+use client::Transport;
+use errors::*;
+use metrics::RequestKind;
+
+/// Canned response returned by `MockTransport`
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// A 200 OK response with the given body
+    pub fn ok<B>(body: B) -> Self
+    where
+        B: Into<Vec<u8>>,
+    {
+        Self::with_status(StatusCode::OK, body)
+    }
+
+    /// A response with an arbitrary status and body
+    pub fn with_status<B>(status: StatusCode, body: B) -> Self
+    where
+        B: Into<Vec<u8>>,
+    {
+        MockResponse {
+            status,
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+}
+
+/// Request captured by `MockTransport`
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub kind: RequestKind,
+    pub method: Method,
+    pub uri: Uri,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+#[derive(Default)]
+struct State {
+    responses: HashMap<RequestKind, MockResponse>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// Transport answering token, recognition and synthesis requests with
+/// canned responses instead of hitting the network
+///
+/// Clones share the same state, so keep a clone around to inspect the
+/// recorded requests after handing the transport to `Speech`.
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockTransport {
+    /// Creates a mock that answers every request with an empty 200 OK
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Respond to token requests with `token`
+    pub fn token(self, token: &str) -> Self {
+        self.respond(RequestKind::Token, MockResponse::ok(token))
+    }
+
+    /// Respond to recognition requests with the given JSON result
+    pub fn recognition(self, json: &str) -> Self {
+        let mut response = MockResponse::ok(json);
+        response
+            .headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        self.respond(RequestKind::Recognize, response)
+    }
+
+    /// Respond to synthesis requests with the given audio
+    pub fn synthesis(self, audio: Vec<u8>) -> Self {
+        self.respond(RequestKind::Synthesize, MockResponse::ok(audio))
+    }
+
+    /// Respond to voice list requests with the given JSON list
+    pub fn voices(self, json: &str) -> Self {
+        self.respond(RequestKind::Voices, MockResponse::ok(json))
+    }
+
+    /// Respond to requests of `kind` with an arbitrary response
+    pub fn respond(self, kind: RequestKind, response: MockResponse) -> Self {
+        self.state.lock().unwrap().responses.insert(kind, response);
+        self
+    }
+
+    /// Requests received so far, oldest first
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Forget the recorded requests
+    pub fn clear_requests(&self) {
+        self.state.lock().unwrap().requests.clear();
+    }
+}
+
+/// Guess which endpoint a request targets from its URI
+pub fn request_kind(uri: &Uri) -> RequestKind {
+    let path = uri.path();
+    if path.contains("issueToken") {
+        RequestKind::Token
+    } else if path.contains("voices/list") {
+        RequestKind::Voices
+    } else if path.contains("synthesize") || uri.host().map_or(false, |h| h.contains(".tts.")) {
+        RequestKind::Synthesize
+    } else {
+        RequestKind::Recognize
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: Request<Body>) -> Result<(HeaderMap, StatusCode, Vec<u8>)> {
+        let (parts, body) = request.into_parts();
+        let body = body.concat2().wait()?.to_vec();
+        let kind = request_kind(&parts.uri);
+
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(RecordedRequest {
+            kind,
+            method: parts.method,
+            uri: parts.uri,
+            headers: parts.headers,
+            body,
+        });
+
+        let response = state
+            .responses
+            .get(&kind)
+            .cloned()
+            .unwrap_or_else(|| MockResponse::ok(Vec::new()));
+        Ok((response.headers, response.status, response.body))
+    }
+}
@@ -0,0 +1,77 @@
+//! Tracing hooks
+//!
+//! Like `metrics`, the crate doesn't pick a tracing backend. Implement
+//! `TraceHook` to follow every HTTP request and websocket message with the
+//! IDs the service knows them by, and register it with
+//! `Speech::set_trace_hook` / `Websocket::set_trace_hook`. Quote the
+//! X-RequestId or X-ConnectionId values in support tickets with Microsoft.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::sync::Arc;
+//! use bing_rs::speech::*;
+//! use bing_rs::trace::LogTrace;
+//!
+//! let mut speech = Speech::new("your_subscription_key").unwrap();
+//! speech.set_trace_hook(Arc::new(LogTrace));
+//! ```
+
+// hyper
+use hyper::{Method, Uri};
+
+// std
+use std::sync::Arc;
+use std::time::Duration;
+
+// internal
+pub use speech::record::Direction;
+
+/// Receiver of tracing events
+///
+/// All methods have empty default implementations so hooks only need to
+/// implement the events they care about.
+pub trait TraceHook: Send + Sync {
+    /// An HTTP request with the X-RequestId `id` is about to be sent
+    fn on_request(&self, _id: &str, _method: &Method, _uri: &Uri) {}
+    /// The request `id` completed, with `status` unless it failed without
+    /// a response
+    fn on_response(&self, _id: &str, _status: Option<u16>, _elapsed: Duration) {}
+    /// A websocket message with the `Path` header `path` was sent or
+    /// received
+    fn on_ws_frame(&self, _direction: Direction, _path: &str) {}
+}
+
+/// Hook that discards every event
+pub struct NoopTrace;
+
+impl TraceHook for NoopTrace {}
+
+/// Default hook used until one is registered
+pub fn noop() -> Arc<TraceHook> {
+    Arc::new(NoopTrace)
+}
+
+/// Hook logging every event at debug level with the `log` crate
+pub struct LogTrace;
+
+impl TraceHook for LogTrace {
+    fn on_request(&self, id: &str, method: &Method, uri: &Uri) {
+        debug!("[{}] {} {}", id, method, uri);
+    }
+
+    fn on_response(&self, id: &str, status: Option<u16>, elapsed: Duration) {
+        match status {
+            Some(status) => debug!("[{}] {} after {:?}", id, status, elapsed),
+            None => debug!("[{}] failed after {:?}", id, elapsed),
+        }
+    }
+
+    fn on_ws_frame(&self, direction: Direction, path: &str) {
+        let arrow = match direction {
+            Direction::Sent => "->",
+            Direction::Received => "<-",
+        };
+        debug!("websocket {} {}", arrow, path);
+    }
+}
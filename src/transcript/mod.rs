@@ -0,0 +1,321 @@
+//! Transcript utilities
+//!
+//! Recognition results carry their position in the audio stream as 100-ns
+//! tick offsets. The types here turn phrases into timed text segments that
+//! can be exported, e.g. as subtitles.
+
+pub mod stabilize;
+pub mod subtitles;
+
+// std
+use std::io::Write;
+use std::time::Duration;
+
+// serde_json
+use serde_json;
+
+// internal
+use errors::*;
+use speech::websocket::Handler;
+use speech::{Hypothesis, Phrase};
+
+/// Recognized text with its position in the audio stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub offset: Duration,
+    pub duration: Duration,
+    pub text: String,
+    /// Timed words of the text, when the phrase came with word-level
+    /// timestamps
+    pub words: Vec<Segment>,
+}
+
+impl Segment {
+    pub fn new<T>(offset: Duration, duration: Duration, text: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Segment {
+            offset,
+            duration,
+            text: text.into(),
+            words: Vec::new(),
+        }
+    }
+
+    /// Segment for a recognized phrase
+    ///
+    /// Returns `None` for silence, unknown or empty results. Word timings
+    /// of detailed results are kept in `words`, with the displayed words if
+    /// they match up one to one and the lexical ones otherwise.
+    pub fn from_phrase(phrase: &Phrase) -> Option<Self> {
+        let text = phrase.text()?.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        let mut segment = Segment::new(phrase.offset()?, phrase.duration()?, text);
+        if let Phrase::Detailed(ref detailed) = *phrase {
+            if let Some(best) = detailed.best() {
+                let display: Vec<&str> = text.split_whitespace().collect();
+                let matches = display.len() == best.words.len();
+                segment.words = best
+                    .words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, word)| {
+                        let text = if matches {
+                            display[i]
+                        } else {
+                            word.word.as_str()
+                        };
+                        Segment::new(word.offset(), word.duration(), text)
+                    }).collect();
+            }
+        }
+        Some(segment)
+    }
+
+    /// Segment for the text recognized so far in an utterance
+    ///
+    /// Returns `None` if the hypothesis is empty.
+    pub fn from_hypothesis(hypothesis: &Hypothesis) -> Option<Self> {
+        let text = hypothesis.text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        Some(Segment::new(
+            hypothesis.offset(),
+            hypothesis.duration(),
+            text,
+        ))
+    }
+
+    /// End of the segment in the audio stream
+    pub fn end(&self) -> Duration {
+        self.offset + self.duration
+    }
+
+    /// Move the segment and its words `by` further into the stream
+    fn shift(&mut self, by: Duration) {
+        self.offset += by;
+        for word in &mut self.words {
+            word.offset += by;
+        }
+    }
+}
+
+/// Segments for every phrase that recognized text, in order
+pub fn segments<'a, I>(phrases: I) -> Vec<Segment>
+where
+    I: IntoIterator<Item = &'a Phrase>,
+{
+    phrases
+        .into_iter()
+        .filter_map(Segment::from_phrase)
+        .collect()
+}
+
+/// Transcript collected across turns, reconnects or REST calls
+///
+/// Offsets reported by the service restart at zero with every new websocket
+/// connection or REST request. Call `start_stream` (or `start_stream_at`)
+/// before feeding the results of a new stream so segment offsets stay
+/// cumulative. Phrases from a websocket session can be collected by using
+/// the transcript as the session's `Handler`; hypotheses are kept as the
+/// `partial` segment until the utterance's phrase arrives.
+///
+/// # Examples
+///
+/// ```no_run
+/// use bing_rs::speech::*;
+/// use bing_rs::transcript::Transcript;
+///
+/// let speech = Speech::new("your_subscription_key").unwrap();
+/// let mode = Mode::Dictation(Language::EnglishUnitedStates);
+/// let mut transcript = Transcript::new();
+/// for path in &["part1.raw", "part2.raw"] {
+///     transcript.start_stream();
+///     if let Ok((_, _, Some(phrase))) = speech.recognize_file(path, mode, Format::Simple) {
+///         transcript.push(&phrase);
+///     }
+/// }
+/// println!("{}", transcript.text());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    segments: Vec<Segment>,
+    partial: Option<Segment>,
+    base: Duration,
+}
+
+#[derive(Serialize)]
+struct JsonSegment<'a> {
+    offset_ms: u64,
+    duration_ms: u64,
+    text: &'a str,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a phrase, shifting it by the current stream's start
+    ///
+    /// Returns the added segment, or `None` if the phrase had no text.
+    pub fn push(&mut self, phrase: &Phrase) -> Option<&Segment> {
+        self.partial = None;
+        let segment = Segment::from_phrase(phrase)?;
+        self.push_segment(segment);
+        self.segments.last()
+    }
+
+    /// Replace the partial segment with the utterance recognized so far
+    ///
+    /// The partial segment is dropped once a phrase is pushed or the turn
+    /// ends.
+    pub fn push_hypothesis(&mut self, hypothesis: &Hypothesis) -> Option<&Segment> {
+        let base = self.base;
+        self.partial = Segment::from_hypothesis(hypothesis).map(|mut segment| {
+            segment.shift(base);
+            segment
+        });
+        self.partial.as_ref()
+    }
+
+    /// Utterance in progress, from the latest hypothesis
+    pub fn partial(&self) -> Option<&Segment> {
+        self.partial.as_ref()
+    }
+
+    /// Drop the partial segment
+    pub fn clear_partial(&mut self) {
+        self.partial = None;
+    }
+
+    /// Add a segment, shifting it by the current stream's start
+    pub fn push_segment(&mut self, mut segment: Segment) {
+        segment.shift(self.base);
+        self.segments.push(segment);
+    }
+
+    /// Start a new stream right after the end of the last segment
+    pub fn start_stream(&mut self) {
+        self.base = self.duration();
+    }
+
+    /// Start a new stream at `offset` into the overall audio, e.g. the total
+    /// duration of the audio sent so far
+    pub fn start_stream_at(&mut self, offset: Duration) {
+        self.base = offset;
+    }
+
+    /// Segments in the order they were added
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// End of the last segment
+    pub fn duration(&self) -> Duration {
+        self.segments
+            .iter()
+            .map(|segment| segment.end())
+            .max()
+            .unwrap_or(self.base)
+    }
+
+    /// Full text with segments separated by spaces
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// Full text followed by the utterance in progress, for live display
+    pub fn live_text(&self) -> String {
+        let mut text = self.text();
+        if let Some(ref partial) = self.partial {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            text.push_str(&partial.text);
+        }
+        text
+    }
+
+    /// Export the segments as SubRip (`.srt`) subtitles
+    pub fn to_srt(&self) -> String {
+        subtitles::to_srt(&self.segments)
+    }
+
+    /// Export the segments as WebVTT (`.vtt`) subtitles
+    pub fn to_webvtt(&self) -> String {
+        subtitles::to_webvtt(&self.segments)
+    }
+
+    /// Write the SubRip export to `writer`
+    pub fn write_srt<W>(&self, mut writer: W) -> Result<()>
+    where
+        W: Write,
+    {
+        subtitles::write_srt(&mut writer, &self.segments)
+    }
+
+    /// Write the WebVTT export to `writer`
+    pub fn write_webvtt<W>(&self, mut writer: W) -> Result<()>
+    where
+        W: Write,
+    {
+        subtitles::write_webvtt(&mut writer, &self.segments)
+    }
+
+    /// Export the segments as a JSON array of
+    /// `{"offset_ms", "duration_ms", "text"}` objects
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&self.json_segments())?)
+    }
+
+    /// Write the JSON export to `writer`
+    pub fn write_json<W>(&self, writer: W) -> Result<()>
+    where
+        W: Write,
+    {
+        Ok(serde_json::to_writer(writer, &self.json_segments())?)
+    }
+
+    fn json_segments(&self) -> Vec<JsonSegment> {
+        self.segments
+            .iter()
+            .map(|segment| JsonSegment {
+                offset_ms: millis(segment.offset),
+                duration_ms: millis(segment.duration),
+                text: &segment.text,
+            }).collect()
+    }
+}
+
+impl Handler for Transcript {
+    fn on_speech_hypothesis(&mut self, hypothesis: Hypothesis) {
+        self.push_hypothesis(&hypothesis);
+    }
+
+    fn on_speech_phrase(&mut self, phrase: Phrase) {
+        self.push(&phrase);
+    }
+
+    fn on_turn_end(&mut self) {
+        self.clear_partial();
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_secs() * 1000 + u64::from(duration.subsec_millis())
+}
@@ -0,0 +1,145 @@
+//! Incremental hypothesis diffing for live captions
+//!
+//! Each `speech.hypothesis` message repeats the whole utterance recognized so
+//! far, and the last words are often revised. `Stabilizer` compares
+//! successive hypotheses and yields only words that are unlikely to change,
+//! so caption UIs can append text instead of re-rendering the line.
+//!
+//! # Examples
+//!
+//! ```
+//! use bing_rs::speech::Hypothesis;
+//! use bing_rs::transcript::stabilize::Stabilizer;
+//!
+//! let hypothesis = |text: &str| Hypothesis {
+//!     text: text.to_string(),
+//!     offset: 0.0,
+//!     duration: 10_000_000.0,
+//! };
+//!
+//! let mut stabilizer = Stabilizer::new();
+//! assert!(stabilizer.push(&hypothesis("what's the")).is_empty());
+//! let words = stabilizer.push(&hypothesis("what's the weather"));
+//! let words: Vec<&str> = words.iter().map(|word| word.text.as_str()).collect();
+//! assert_eq!(words, vec!["what's", "the"]);
+//! ```
+
+// std
+use std::time::Duration;
+
+// internal
+use speech::{Hypothesis, Phrase};
+
+/// Word that won't change in later hypotheses of the same utterance
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StableWord {
+    pub text: String,
+    /// Position of the word in the utterance
+    pub index: usize,
+    /// Estimated start of the word in the audio stream
+    ///
+    /// Hypotheses only carry timing for the whole utterance, so this is
+    /// interpolated from the word's position in the text.
+    pub offset: Duration,
+}
+
+/// Yields newly stabilized words from successive hypotheses
+#[derive(Debug, Clone, Default)]
+pub struct Stabilizer {
+    previous: Vec<String>,
+    emitted: usize,
+}
+
+impl Stabilizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next hypothesis and get the words that became stable
+    ///
+    /// A word is stable once two successive hypotheses agree on it and on
+    /// everything before it, and it's no longer the last word. Words that
+    /// were already returned are never returned again, even if the service
+    /// revises them later.
+    pub fn push(&mut self, hypothesis: &Hypothesis) -> Vec<StableWord> {
+        let words = split(&hypothesis.text);
+        let agreed = self
+            .previous
+            .iter()
+            .zip(words.iter())
+            .take_while(|&(previous, current)| previous == current)
+            .count();
+        let stable = agreed.min(words.len().saturating_sub(1));
+
+        let new_words = self.take(&words, stable, hypothesis.offset(), hypothesis.duration());
+        self.previous = words;
+        new_words
+    }
+
+    /// Flush the remaining words of the final phrase and start over for the
+    /// next utterance
+    ///
+    /// Words are matched by position, so the flushed words carry the final
+    /// phrase's punctuation and capitalization.
+    pub fn finish(&mut self, phrase: &Phrase) -> Vec<StableWord> {
+        let words = match phrase.text() {
+            Some(text) => split(text),
+            None => Vec::new(),
+        };
+        let offset = phrase.offset().unwrap_or_default();
+        let duration = phrase.duration().unwrap_or_default();
+        let new_words = self.take(&words, words.len(), offset, duration);
+        self.reset();
+        new_words
+    }
+
+    /// Forget the current utterance, e.g. at the end of a turn
+    pub fn reset(&mut self) {
+        self.previous.clear();
+        self.emitted = 0;
+    }
+
+    fn take(
+        &mut self,
+        words: &[String],
+        stable: usize,
+        offset: Duration,
+        duration: Duration,
+    ) -> Vec<StableWord> {
+        if stable <= self.emitted {
+            return Vec::new();
+        }
+
+        let total_chars: usize = words.iter().map(|word| word.chars().count() + 1).sum();
+        let mut chars_before: usize = words[..self.emitted]
+            .iter()
+            .map(|word| word.chars().count() + 1)
+            .sum();
+        let mut new_words = Vec::new();
+        for (index, word) in words.iter().enumerate().take(stable).skip(self.emitted) {
+            new_words.push(StableWord {
+                text: word.clone(),
+                index,
+                offset: offset + interpolate(duration, chars_before, total_chars),
+            });
+            chars_before += word.chars().count() + 1;
+        }
+        self.emitted = stable;
+        new_words
+    }
+}
+
+fn split(text: &str) -> Vec<String> {
+    text.split_whitespace().map(|word| word.to_string()).collect()
+}
+
+/// `duration * part / total`
+fn interpolate(duration: Duration, part: usize, total: usize) -> Duration {
+    if total == 0 {
+        return Duration::from_secs(0);
+    }
+
+    let nanos = duration.as_secs() * 1_000_000_000 + u64::from(duration.subsec_nanos());
+    let nanos = nanos / total as u64 * part as u64;
+    Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+}
@@ -0,0 +1,150 @@
+//! SRT and WebVTT subtitle export
+//!
+//! Segments with word timings are split into cues of at most two lines,
+//! each shown while its words are spoken. Other segments make one cue each.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::transcript::{self, subtitles};
+//!
+//! # let phrases = Vec::new();
+//! let segments = transcript::segments(&phrases);
+//! println!("{}", subtitles::to_srt(&segments));
+//! ```
+
+// std
+use std::io::Write;
+use std::time::Duration;
+
+// internal
+use super::Segment;
+use errors::*;
+
+/// Maximum characters per subtitle line before text is wrapped
+pub const DEFAULT_LINE_LENGTH: usize = 42;
+
+/// Maximum characters per cue when splitting segments at word timings
+const MAX_CUE_LENGTH: usize = 2 * DEFAULT_LINE_LENGTH;
+
+/// Render segments as SubRip (`.srt`) subtitles
+pub fn to_srt(segments: &[Segment]) -> String {
+    let mut srt = String::new();
+    for (i, segment) in cues(segments).iter().enumerate() {
+        srt.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            timestamp(segment.offset, ','),
+            timestamp(segment.end(), ','),
+            wrap(&segment.text, DEFAULT_LINE_LENGTH)
+        ));
+    }
+    srt
+}
+
+/// Render segments as WebVTT (`.vtt`) subtitles
+pub fn to_webvtt(segments: &[Segment]) -> String {
+    let mut vtt = String::from("WEBVTT\n\n");
+    for segment in &cues(segments) {
+        vtt.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            timestamp(segment.offset, '.'),
+            timestamp(segment.end(), '.'),
+            wrap(&segment.text, DEFAULT_LINE_LENGTH)
+        ));
+    }
+    vtt
+}
+
+/// Write segments as SubRip subtitles
+pub fn write_srt<W>(writer: &mut W, segments: &[Segment]) -> Result<()>
+where
+    W: Write,
+{
+    Ok(writer.write_all(to_srt(segments).as_bytes())?)
+}
+
+/// Write segments as WebVTT subtitles
+pub fn write_webvtt<W>(writer: &mut W, segments: &[Segment]) -> Result<()>
+where
+    W: Write,
+{
+    Ok(writer.write_all(to_webvtt(segments).as_bytes())?)
+}
+
+/// Split segments with word timings into cues of at most
+/// `MAX_CUE_LENGTH` characters
+fn cues(segments: &[Segment]) -> Vec<Segment> {
+    let mut cues = Vec::new();
+    for segment in segments {
+        if segment.words.is_empty() {
+            cues.push(Segment::new(
+                segment.offset,
+                segment.duration,
+                segment.text.as_str(),
+            ));
+            continue;
+        }
+
+        let mut words: Vec<&Segment> = Vec::new();
+        let mut len = 0;
+        for word in &segment.words {
+            let word_len = word.text.chars().count();
+            if !words.is_empty() && len + 1 + word_len > MAX_CUE_LENGTH {
+                cues.push(cue(&words));
+                words.clear();
+                len = 0;
+            }
+            if !words.is_empty() {
+                len += 1;
+            }
+            len += word_len;
+            words.push(word);
+        }
+        if !words.is_empty() {
+            cues.push(cue(&words));
+        }
+    }
+    cues
+}
+
+/// Cue spanning `words`, from the start of the first to the end of the last
+fn cue(words: &[&Segment]) -> Segment {
+    let offset = words[0].offset;
+    let end = words[words.len() - 1].end().max(offset);
+    let text: Vec<&str> = words.iter().map(|word| word.text.as_str()).collect();
+    Segment::new(offset, end - offset, text.join(" "))
+}
+
+/// Format as `HH:MM:SS<separator>mmm`
+fn timestamp(time: Duration, separator: char) -> String {
+    let secs = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        separator,
+        time.subsec_millis()
+    )
+}
+
+/// Break text into lines of at most `width` characters at word boundaries
+fn wrap(text: &str, width: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(line);
+            line = String::new();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,155 @@
+//! Microsoft Translator Text v3 client
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use bing_rs::translate::*;
+//!
+//! let mut translator = Translator::new("your_subscription_key").unwrap();
+//! translator.set_region(Some("westeurope"));
+//! let result = translator.translate("Hello, world!", "de").unwrap();
+//! println!("{}", result.text().unwrap_or(""));
+//! ```
+
+// internal
+use client::{ApiClient, HyperTransport, Transport};
+use errors::*;
+
+/// Global Translator Text endpoint
+pub const DEFAULT_ENDPOINT: &str = "https://api.cognitive.microsofttranslator.com";
+
+/// Translator Text API version sent with every request
+const API_VERSION: &str = "3.0";
+
+/// Microsoft Translator Text client
+pub struct Translator {
+    client: ApiClient,
+}
+
+impl Translator {
+    /// Creates a new Translator Text handle
+    pub fn new<T>(subscription_key: &T) -> Result<Self>
+    where
+        T: ToString,
+    {
+        let transport = HyperTransport::new()?;
+        Ok(Self::with_transport(subscription_key, transport))
+    }
+
+    /// Creates a new Translator Text handle sending requests through
+    /// `transport`
+    pub fn with_transport<T, U>(subscription_key: &T, transport: U) -> Self
+    where
+        T: ToString,
+        U: Transport + 'static,
+    {
+        Translator {
+            client: ApiClient::new(&subscription_key.to_string(), DEFAULT_ENDPOINT, transport),
+        }
+    }
+
+    /// Sets Translator Text subscription key
+    pub fn set_subscription_key(&mut self, key: &str) {
+        self.client.set_subscription_key(key);
+    }
+
+    /// Region of the Translator or multi-service resource, e.g.
+    /// "westeurope", or `None` for a global Translator resource
+    pub fn set_region(&mut self, region: Option<&str>) {
+        self.client.set_region(region);
+    }
+
+    /// Override the base URL, e.g. for a custom Azure resource
+    pub fn set_endpoint(&mut self, endpoint: &str) {
+        self.client.set_endpoint(endpoint);
+    }
+
+    /// Translate `text` into the language `to`, e.g. "de" or "zh-Hans",
+    /// detecting the source language
+    pub fn translate(&self, text: &str, to: &str) -> Result<TranslationResult> {
+        self.translate_from(text, None, to)
+    }
+
+    /// Translate `text` from the language `from`, or a detected one if
+    /// `None`, into the language `to`
+    pub fn translate_from(
+        &self,
+        text: &str,
+        from: Option<&str>,
+        to: &str,
+    ) -> Result<TranslationResult> {
+        match self.translate_all(&[text], from, &[to])?.pop() {
+            Some(result) => Ok(result),
+            None => bail!("no translation returned"),
+        }
+    }
+
+    /// Translate every text of `texts` into every language of `to` in one
+    /// request
+    ///
+    /// Results are in the order of `texts`, and their translations in the
+    /// order of `to`.
+    pub fn translate_all(
+        &self,
+        texts: &[&str],
+        from: Option<&str>,
+        to: &[&str],
+    ) -> Result<Vec<TranslationResult>> {
+        if to.is_empty() {
+            bail!(ErrorKind::InvalidRequest("no target language".to_string()));
+        }
+
+        let mut params = vec![("api-version".to_string(), API_VERSION.to_string())];
+        if let Some(from) = from {
+            params.push(("from".to_string(), from.to_string()));
+        }
+        for to in to {
+            params.push(("to".to_string(), to.to_string()));
+        }
+        let body: Vec<TextItem> = texts.iter().map(|&text| TextItem { text }).collect();
+        self.client.post_json("translate", &params, &body)
+    }
+}
+
+/// Element of the request body
+#[derive(Serialize)]
+struct TextItem<'a> {
+    #[serde(rename = "Text")]
+    text: &'a str,
+}
+
+/// Translations of one text
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranslationResult {
+    /// Source language, if it was detected rather than given
+    #[serde(default)]
+    pub detected_language: Option<DetectedLanguage>,
+    #[serde(default)]
+    pub translations: Vec<Translation>,
+}
+
+impl TranslationResult {
+    /// Text of the first translation
+    pub fn text(&self) -> Option<&str> {
+        self.translations
+            .first()
+            .map(|translation| translation.text.as_str())
+    }
+}
+
+/// Language the service detected in the source text
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DetectedLanguage {
+    pub language: String,
+    /// Confidence between 0 and 1
+    pub score: f64,
+}
+
+/// Text translated into one language
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Translation {
+    pub text: String,
+    /// Language code of the translation
+    pub to: String,
+}